@@ -0,0 +1,567 @@
+//! Programmatic construction of `Board` positions.
+//!
+//! `BoardBuilder` places pieces and sets the state of a position without going through a FEN
+//! string, and `build()` rejects positions that cannot arise in a legal game.
+use thiserror::Error;
+
+use super::movegen::lookup::{between_bb, line_bb};
+use super::{Board, ValidationError, king_attack};
+use crate::core::*;
+
+/******************************************\
+|==========================================|
+|              Board Builder               |
+|==========================================|
+\******************************************/
+
+/// Builds a `Board` square by square.
+///
+/// ```
+/// use chess::board::BoardBuilder;
+/// use chess::core::*;
+///
+/// let board = BoardBuilder::new()
+///     .piece(Square::E1, Piece::WhiteKing)
+///     .piece(Square::H1, Piece::WhiteRook)
+///     .piece(Square::E8, Piece::BlackKing)
+///     .castle(Castling::WK)
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(board.fen(), "4k3/8/8/8/8/8/8/4K2R w K - 0 1");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoardBuilder {
+    board: [Option<Piece>; Square::NUM],
+    stm: Colour,
+    /// Castling rights using the outermost rook of their side
+    castling: Castling,
+    /// Castling rights given by the file of their rook, as in Shredder-FEN
+    rook_files: Vec<(Colour, File)>,
+    ep: Option<Square>,
+    fifty_move: u8,
+    full_moves: u16,
+    chess960: bool,
+}
+
+impl Default for BoardBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BoardBuilder {
+    /// Creates a builder with an empty board, white to move and no castling rights
+    pub fn new() -> Self {
+        Self {
+            board: [None; Square::NUM],
+            stm: Colour::White,
+            castling: Castling::NONE,
+            rook_files: Vec::new(),
+            ep: None,
+            fifty_move: 0,
+            full_moves: 1,
+            chess960: false,
+        }
+    }
+
+    /// Places a piece on the square, replacing the piece already there
+    pub fn piece(mut self, sq: Square, piece: Piece) -> Self {
+        self.board[sq.index()] = Some(piece);
+        self
+    }
+
+    /// Removes the piece on the square, if any
+    pub fn remove(mut self, sq: Square) -> Self {
+        self.board[sq.index()] = None;
+        self
+    }
+
+    /// Sets the side to move
+    pub fn side_to_move(mut self, col: Colour) -> Self {
+        self.stm = col;
+        self
+    }
+
+    /// Adds castling rights, each using the outermost rook on its side of the king
+    pub fn castle(mut self, rights: Castling) -> Self {
+        self.castling.set(rights);
+        self
+    }
+
+    /// Adds the castling right of `col` with the rook on `file` of its back rank,
+    /// the side being given by the position of the rook relative to the king
+    pub fn castle_with(mut self, col: Colour, file: File) -> Self {
+        self.rook_files.push((col, file));
+        self
+    }
+
+    /// Sets the en passant square, behind the pawn that just made a double push
+    pub fn ep(mut self, ep: Option<Square>) -> Self {
+        self.ep = ep;
+        self
+    }
+
+    /// Sets the number of half-moves since the last capture or pawn move
+    pub fn fifty_move(mut self, fifty_move: u8) -> Self {
+        self.fifty_move = fifty_move;
+        self
+    }
+
+    /// Sets the fullmove number, starting at 1 like in FEN
+    pub fn full_moves(mut self, full_moves: u16) -> Self {
+        self.full_moves = full_moves.max(1);
+        self
+    }
+
+    /// Sets whether the board follows Chess960 castling rules
+    pub fn chess960(mut self, chess960: bool) -> Self {
+        self.chess960 = chess960;
+        self
+    }
+
+    /// # Build Board
+    ///
+    /// Creates the board, checking that the position can arise in a legal game.
+    ///
+    /// ## Errors
+    /// Returns `PositionError` if:
+    /// * A side does not have exactly one king, or the kings are adjacent.
+    /// * A pawn is on the first or last rank.
+    /// * The fullmove number is too large to count the plies played.
+    /// * A castling right has no king or rook on its back rank.
+    /// * The side not to move is in check, or the side to move is in check by more than two
+    ///   pieces or by two pieces that a single move cannot give check with.
+    /// * The en passant square is not behind a pawn that just made a double push.
+    pub fn build(&self) -> Result<Board, PositionError> {
+        let mut board = Board::new();
+
+        for sq in Square::iter() {
+            if let Some(piece) = self.board[sq.index()] {
+                board.add_piece(piece, sq);
+            }
+        }
+
+        for col in [Colour::White, Colour::Black] {
+            let kings = board.piece_bb(col, PieceType::King).count_bits();
+            if kings != 1 {
+                return Err(PositionError::KingCount(col, kings));
+            }
+        }
+
+        let back_ranks = Rank::Rank1.bb() | Rank::Rank8.bb();
+        if let Some(sq) = (board.piecetype_bb(PieceType::Pawn) & back_ranks).lsb() {
+            return Err(PositionError::PawnOnBackRank(sq));
+        }
+
+        if king_attack(board.ksq(Colour::White)).contains(board.ksq(Colour::Black)) {
+            return Err(PositionError::AdjacentKings);
+        }
+
+        board.stm = self.stm;
+        board.chess960 = self.chess960;
+        board.state.fifty_move = self.fifty_move;
+        board.state.enpassant = self.ep;
+        board.half_moves = (self.full_moves - 1)
+            .checked_mul(2)
+            .and_then(|plies| plies.checked_add(self.stm as u16))
+            .ok_or(PositionError::FullMoves(self.full_moves))?;
+
+        self.set_castling(&mut board)?;
+
+        board.state.keys.key = board.calc_key();
+        board.state.keys.pawn_key = board.calc_pawn_key();
+        board.state.keys.non_pawn_key = board.calc_non_pawn_key();
+
+        board.update_masks();
+
+        board.validate()?;
+        Self::check_checkers(&board)?;
+
+        Ok(board)
+    }
+
+    /// Sets the castling rights and castling mask of the board
+    fn set_castling(&self, board: &mut Board) -> Result<(), PositionError> {
+        let mut rights = Vec::new();
+        board.state.castle = Castling::NONE;
+
+        for col in [Colour::White, Colour::Black] {
+            let ksq = board.ksq(col);
+            let back_rank = Rank::Rank1.relative(col);
+            let col_rights = match col {
+                Colour::White => Castling::WHITE_CASTLING,
+                Colour::Black => Castling::BLACK_CASTLING,
+            };
+
+            board.castling_mask.castling[ksq.index()].remove(col_rights);
+
+            let has_rights =
+                self.castling.has(col_rights) || self.rook_files.iter().any(|&(c, _)| c == col);
+
+            if has_rights && ksq.rank() != back_rank {
+                return Err(PositionError::InvalidCastling(col_rights));
+            }
+
+            let rooks = board.piece_bb(col, PieceType::Rook) & back_rank.bb();
+            let king_side = Castling::king_side(col);
+            let queen_side = Castling::queen_side(col);
+
+            let rook_on = |file: File| {
+                let sq = Square::from_parts(file, back_rank);
+                rooks.contains(sq).then_some(sq)
+            };
+
+            // The outermost rook on each side of the king
+            if self.castling.has(king_side) {
+                let rook = File::iter()
+                    .rev()
+                    .filter(|&f| f > ksq.file())
+                    .find_map(rook_on);
+                rights.push((king_side, rook));
+            }
+            if self.castling.has(queen_side) {
+                let rook = File::iter().filter(|&f| f < ksq.file()).find_map(rook_on);
+                rights.push((queen_side, rook));
+            }
+
+            for &(_, file) in self.rook_files.iter().filter(|&&(c, _)| c == col) {
+                let right = if file > ksq.file() {
+                    king_side
+                } else {
+                    queen_side
+                };
+                rights.push((right, rook_on(file)));
+            }
+        }
+
+        for (right, rook) in rights {
+            let rook = rook.ok_or(PositionError::InvalidCastling(right))?;
+            let index = right.0.trailing_zeros() as usize;
+
+            board.state.castle.set(right);
+            board.castling_mask.rook_sq[index] = Some(rook);
+            board.castling_mask.castling[rook.index()].remove(right);
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the checks of the position can be given by the last move
+    fn check_checkers(board: &Board) -> Result<(), PositionError> {
+        let occ = board.all_occupied_bb();
+        let us = board.stm();
+        let them = !us;
+
+        if (board.attackers_to(board.ksq(them), occ) & board.occupied_bb(us)).is_occupied() {
+            return Err(PositionError::OpponentInCheck);
+        }
+
+        let ksq = board.ksq(us);
+        let checkers = board.attackers_to(ksq, occ) & board.occupied_bb(them);
+        let count = checkers.count_bits();
+
+        if count > 2 {
+            return Err(PositionError::TooManyCheckers(count));
+        }
+
+        // A double check is a discovered check, so one of the checkers is a slider,
+        // and both checkers cannot be on the same line through the king
+        if count == 2 {
+            let sliders = board.bishop_queen_bb(them) | board.rook_queen_bb(them);
+            let first = checkers.lsb_unchecked();
+            let second = (checkers ^ first.bb()).lsb_unchecked();
+
+            if (checkers & sliders).is_empty() || line_bb(first, second).contains(ksq) {
+                return Err(PositionError::ImpossibleCheck);
+            }
+        }
+
+        // After a double push, the checkers are the pushed pawn or sliders it uncovered
+        if let Some(ep) = board.ep() {
+            let pushed = unsafe { ep.add_unchecked(-us.forward()) };
+            let origin = unsafe { ep.add_unchecked(us.forward()) };
+
+            let mut explained = true;
+            checkers.for_each(|checker| {
+                explained &= checker == pushed || between_bb(ksq, checker).contains(origin);
+            });
+
+            if !explained {
+                return Err(PositionError::ImpossibleCheck);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl From<&Board> for BoardBuilder {
+    /// Creates a builder holding the position of the board
+    fn from(board: &Board) -> Self {
+        let mut builder = Self::new()
+            .side_to_move(board.stm())
+            .ep(board.ep())
+            .fifty_move(board.fifty_move())
+            .full_moves(board.half_moves() / 2 + 1)
+            .chess960(board.chess960());
+
+        for sq in Square::iter() {
+            builder.board[sq.index()] = board.on(sq);
+        }
+
+        for right in [Castling::WK, Castling::WQ, Castling::BK, Castling::BQ] {
+            if board.castling().has(right) {
+                let col = if Castling::WHITE_CASTLING.has(right) {
+                    Colour::White
+                } else {
+                    Colour::Black
+                };
+
+                builder = builder.castle_with(col, board.rook_sq(right).file());
+            }
+        }
+
+        builder
+    }
+}
+
+/******************************************\
+|==========================================|
+|             Position Errors              |
+|==========================================|
+\******************************************/
+
+/// Represents the reasons a `BoardBuilder` position cannot arise in a legal game.
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum PositionError {
+    #[error("Expected one {0:?} king, found {1}")]
+    KingCount(Colour, u32),
+    #[error("Pawn on the back rank at {0}")]
+    PawnOnBackRank(Square),
+    #[error("The kings are adjacent")]
+    AdjacentKings,
+    #[error("Fullmove number {0} is out of range")]
+    FullMoves(u16),
+    #[error("Castling right {0:?} has no king or rook on its back rank")]
+    InvalidCastling(Castling),
+    #[error("The side not to move is in check")]
+    OpponentInCheck,
+    #[error("The side to move is in check by {0} pieces")]
+    TooManyCheckers(u32),
+    #[error("The checks cannot be given by a single move")]
+    ImpossibleCheck,
+    #[error("Inconsistent position: {0}")]
+    Inconsistent(#[from] ValidationError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{START_FEN, TRICKY_FEN};
+
+    fn kings() -> BoardBuilder {
+        BoardBuilder::new()
+            .piece(Square::E1, Piece::WhiteKing)
+            .piece(Square::E8, Piece::BlackKing)
+    }
+
+    #[test]
+    fn test_build_example() {
+        let board = BoardBuilder::new()
+            .piece(Square::E1, Piece::WhiteKing)
+            .piece(Square::H1, Piece::WhiteRook)
+            .piece(Square::E8, Piece::BlackKing)
+            .castle(Castling::WK)
+            .build()
+            .unwrap();
+
+        assert_eq!(board.fen(), "4k3/8/8/8/8/8/8/4K2R w K - 0 1");
+    }
+
+    #[test]
+    fn test_build() {
+        for fen in [
+            START_FEN,
+            TRICKY_FEN,
+            "rnbqkbnr/pp1ppppp/8/2p5/4P3/8/PPPP1PPP/RNBQKBNR w KQkq c6 0 2",
+            "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9",
+        ] {
+            let board = Board::from_fen(fen).unwrap();
+            let built = BoardBuilder::from(&board).build().unwrap();
+
+            assert_eq!(built, board, "{fen}");
+        }
+
+        let board = kings()
+            .piece(Square::A1, Piece::WhiteRook)
+            .piece(Square::B1, Piece::WhiteRook)
+            .piece(Square::D7, Piece::BlackPawn)
+            .side_to_move(Colour::Black)
+            .castle(Castling::WQ)
+            .fifty_move(3)
+            .full_moves(20)
+            .build()
+            .unwrap();
+
+        // The queen side right uses the outermost rook, written as its file since two rooks qualify
+        assert_eq!(board.fen(), "4k3/3p4/8/8/8/8/8/RR2K3 b A - 3 20");
+        assert_eq!(board.rook_sq(Castling::WQ), Square::A1);
+
+        // Chess960 rights given by rook files
+        let board = BoardBuilder::new()
+            .piece(Square::B1, Piece::WhiteKing)
+            .piece(Square::A1, Piece::WhiteRook)
+            .piece(Square::C1, Piece::WhiteRook)
+            .piece(Square::G8, Piece::BlackKing)
+            .castle_with(Colour::White, File::FileA)
+            .castle_with(Colour::White, File::FileC)
+            .chess960(true)
+            .build()
+            .unwrap();
+
+        assert!(board.chess960());
+        assert_eq!(board.castling(), Castling::WHITE_CASTLING);
+        assert_eq!(board.rook_sq(Castling::WK), Square::C1);
+        assert_eq!(board.rook_sq(Castling::WQ), Square::A1);
+    }
+
+    #[test]
+    fn test_piece_errors() {
+        assert_eq!(
+            BoardBuilder::new().build(),
+            Err(PositionError::KingCount(Colour::White, 0))
+        );
+        assert_eq!(
+            kings().piece(Square::A2, Piece::BlackKing).build(),
+            Err(PositionError::KingCount(Colour::Black, 2))
+        );
+        assert_eq!(
+            kings().piece(Square::C8, Piece::WhitePawn).build(),
+            Err(PositionError::PawnOnBackRank(Square::C8))
+        );
+        assert_eq!(
+            kings()
+                .remove(Square::E8)
+                .piece(Square::E2, Piece::BlackKing)
+                .build(),
+            Err(PositionError::AdjacentKings)
+        );
+        assert!(
+            kings()
+                .side_to_move(Colour::Black)
+                .full_moves(32768)
+                .build()
+                .is_ok()
+        );
+        assert_eq!(
+            kings().full_moves(32769).build(),
+            Err(PositionError::FullMoves(32769))
+        );
+        assert_eq!(
+            kings().castle(Castling::BK).build(),
+            Err(PositionError::InvalidCastling(Castling::BK))
+        );
+        assert_eq!(
+            kings()
+                .piece(Square::D1, Piece::WhiteRook)
+                .castle_with(Colour::White, File::FileA)
+                .build(),
+            Err(PositionError::InvalidCastling(Castling::WQ))
+        );
+    }
+
+    #[test]
+    fn test_check_errors() {
+        assert_eq!(
+            kings().piece(Square::E4, Piece::WhiteRook).build(),
+            Err(PositionError::OpponentInCheck)
+        );
+
+        let checked = kings().side_to_move(Colour::Black);
+        assert!(
+            checked
+                .clone()
+                .piece(Square::E4, Piece::WhiteRook)
+                .build()
+                .is_ok()
+        );
+
+        assert_eq!(
+            checked
+                .clone()
+                .piece(Square::E4, Piece::WhiteRook)
+                .piece(Square::A4, Piece::WhiteBishop)
+                .piece(Square::F6, Piece::WhiteKnight)
+                .build(),
+            Err(PositionError::TooManyCheckers(3))
+        );
+
+        // A knight and a rook is a possible double check, two knights are not
+        assert!(
+            checked
+                .clone()
+                .piece(Square::E4, Piece::WhiteRook)
+                .piece(Square::F6, Piece::WhiteKnight)
+                .build()
+                .is_ok()
+        );
+        assert_eq!(
+            checked
+                .clone()
+                .piece(Square::D6, Piece::WhiteKnight)
+                .piece(Square::F6, Piece::WhiteKnight)
+                .build(),
+            Err(PositionError::ImpossibleCheck)
+        );
+
+        // Two rooks on either side of the king
+        assert_eq!(
+            checked
+                .clone()
+                .piece(Square::A8, Piece::WhiteRook)
+                .piece(Square::H8, Piece::WhiteRook)
+                .build(),
+            Err(PositionError::ImpossibleCheck)
+        );
+
+        // A check from a piece the double push could not have uncovered
+        assert_eq!(
+            checked
+                .piece(Square::D4, Piece::WhitePawn)
+                .piece(Square::E4, Piece::WhiteRook)
+                .ep(Some(Square::D3))
+                .build(),
+            Err(PositionError::ImpossibleCheck)
+        );
+    }
+
+    #[test]
+    fn test_ep_errors() {
+        let black = kings().side_to_move(Colour::Black);
+
+        assert!(
+            black
+                .clone()
+                .piece(Square::D4, Piece::WhitePawn)
+                .ep(Some(Square::D3))
+                .build()
+                .is_ok()
+        );
+        assert_eq!(
+            black.clone().ep(Some(Square::D3)).build(),
+            Err(PositionError::Inconsistent(ValidationError::EnPassant(
+                Square::D3
+            )))
+        );
+        assert_eq!(
+            black
+                .piece(Square::D4, Piece::WhitePawn)
+                .ep(Some(Square::D6))
+                .build(),
+            Err(PositionError::Inconsistent(ValidationError::EnPassant(
+                Square::D6
+            )))
+        );
+    }
+}
@@ -0,0 +1,359 @@
+//! Chess960 (Fischer Random) and Double Fischer Random (DFRC) start positions.
+//!
+//! Start positions are numbered with the Scharnagl scheme, where index 518 is the
+//! standard chess start position. A DFRC position uses an independent index for each side.
+use thiserror::Error;
+
+use super::Board;
+use crate::core::*;
+
+/******************************************\
+|==========================================|
+|                Constants                 |
+|==========================================|
+\******************************************/
+
+/// Number of distinct Chess960 back rank arrangements.
+pub const CHESS960_POSITIONS: usize = 960;
+
+/// Scharnagl index of the standard chess start position.
+pub const STANDARD_CHESS960_INDEX: usize = 518;
+
+/// Knight placements on the five squares left after placing the bishops and the queen,
+/// indexed by the Scharnagl knight code (0..10).
+const KNIGHT_TABLE: [[usize; 2]; 10] = [
+    [0, 1],
+    [0, 2],
+    [0, 3],
+    [0, 4],
+    [1, 2],
+    [1, 3],
+    [1, 4],
+    [2, 3],
+    [2, 4],
+    [3, 4],
+];
+
+/******************************************\
+|==========================================|
+|             Back Rank Layout             |
+|==========================================|
+\******************************************/
+
+/// # Chess960 Back Rank
+///
+/// Builds the back rank (file A to file H) of the Chess960 start position with the given
+/// Scharnagl index.
+///
+/// ## Errors
+/// Returns `Chess960Error::InvalidIndex` if `index` is not in `0..960`.
+pub fn chess960_back_rank(index: usize) -> Result<[PieceType; File::NUM], Chess960Error> {
+    if index >= CHESS960_POSITIONS {
+        return Err(Chess960Error::InvalidIndex(index));
+    }
+
+    let mut rank: [Option<PieceType>; File::NUM] = [None; File::NUM];
+
+    // Light squared bishop on files b, d, f, h; dark squared bishop on files a, c, e, g
+    let (n, light) = (index / 4, index % 4);
+    let (n, dark) = (n / 4, n % 4);
+    rank[2 * light + 1] = Some(PieceType::Bishop);
+    rank[2 * dark] = Some(PieceType::Bishop);
+
+    // Queen on one of the six remaining squares
+    let (knights, queen) = (n / 6, n % 6);
+    let empty = empty_files(&rank);
+    rank[empty[queen]] = Some(PieceType::Queen);
+
+    // Knights on two of the five remaining squares
+    let empty = empty_files(&rank);
+    for i in KNIGHT_TABLE[knights] {
+        rank[empty[i]] = Some(PieceType::Knight);
+    }
+
+    // Rook, king, rook on the last three squares
+    let empty = empty_files(&rank);
+    for (file, pt) in empty
+        .into_iter()
+        .zip([PieceType::Rook, PieceType::King, PieceType::Rook])
+    {
+        rank[file] = Some(pt);
+    }
+
+    Ok(rank.map(|pt| pt.unwrap()))
+}
+
+/// # Chess960 Index
+///
+/// Computes the Scharnagl index of a back rank layout, if it is a valid Chess960 arrangement.
+pub fn chess960_index(rank: &[PieceType; File::NUM]) -> Option<usize> {
+    let files_of = |pt: PieceType| (0..File::NUM).filter(move |&file| rank[file] == pt);
+
+    // Exactly one bishop on each square colour
+    let light = files_of(PieceType::Bishop).find(|file| file % 2 == 1)?;
+    let dark = files_of(PieceType::Bishop).find(|file| file % 2 == 0)?;
+
+    let mut placed: [Option<PieceType>; File::NUM] = [None; File::NUM];
+    placed[light] = Some(PieceType::Bishop);
+    placed[dark] = Some(PieceType::Bishop);
+
+    // Position of the queen among the squares left after the bishops
+    let queen = files_of(PieceType::Queen).next()?;
+    let queen_idx = empty_files(&placed)
+        .iter()
+        .position(|&file| file == queen)?;
+    placed[queen] = Some(PieceType::Queen);
+
+    // Position of the knights among the squares left after the queen
+    let empty = empty_files(&placed);
+    let mut knights = files_of(PieceType::Knight).map(|file| empty.iter().position(|&f| f == file));
+    let knight_pair = [knights.next()??, knights.next()??];
+    let knight_code = KNIGHT_TABLE.iter().position(|&pair| pair == knight_pair)?;
+
+    let index = light / 2 + 4 * (dark / 2) + 16 * queen_idx + 96 * knight_code;
+
+    // Validates the remaining pieces (piece counts and king between the rooks)
+    (chess960_back_rank(index).ok()? == *rank).then_some(index)
+}
+
+/// Returns the files (as indices) that are still empty on a partially placed back rank.
+fn empty_files(rank: &[Option<PieceType>; File::NUM]) -> Vec<usize> {
+    (0..File::NUM)
+        .filter(|&file| rank[file].is_none())
+        .collect()
+}
+
+/******************************************\
+|==========================================|
+|             Board Constructors           |
+|==========================================|
+\******************************************/
+
+impl Board {
+    /// # Chess960 Board
+    ///
+    /// Creates the Chess960 start position with the given Scharnagl index, with
+    /// Chess960 rules enabled and full castling rights.
+    ///
+    /// ## Errors
+    /// Returns `Chess960Error::InvalidIndex` if `index` is not in `0..960`.
+    pub fn from_chess960(index: usize) -> Result<Self, Chess960Error> {
+        Self::from_dfrc(index, index)
+    }
+
+    /// # DFRC Board
+    ///
+    /// Creates the Double Fischer Random start position where white and black use
+    /// the back ranks with Scharnagl indices `white` and `black` respectively.
+    ///
+    /// ## Errors
+    /// Returns `Chess960Error::InvalidIndex` if either index is not in `0..960`.
+    pub fn from_dfrc(white: usize, black: usize) -> Result<Self, Chess960Error> {
+        let white_rank = chess960_back_rank(white)?;
+        let black_rank = chess960_back_rank(black)?;
+
+        let rank_str = |rank: &[PieceType; File::NUM], col: Colour| -> String {
+            rank.iter()
+                .map(|&pt| Piece::from_parts(col, pt).to_string())
+                .collect()
+        };
+
+        // Castling rights use rook files (Shredder-FEN), king side rook first
+        let castling_str = |rank: &[PieceType; File::NUM], col: Colour| -> String {
+            (0..File::NUM)
+                .rev()
+                .filter(|&file| rank[file] == PieceType::Rook)
+                .map(|file| {
+                    let c = (b'a' + file as u8) as char;
+                    match col {
+                        Colour::White => c.to_ascii_uppercase(),
+                        Colour::Black => c,
+                    }
+                })
+                .collect()
+        };
+
+        let fen = format!(
+            "{}/pppppppp/8/8/8/8/PPPPPPPP/{} w {}{} - 0 1",
+            rank_str(&black_rank, Colour::Black),
+            rank_str(&white_rank, Colour::White),
+            castling_str(&white_rank, Colour::White),
+            castling_str(&black_rank, Colour::Black),
+        );
+
+        // The generated fen is always well formed
+        let mut board = Board::from_fen(&fen).expect("Generated Chess960 fen should be valid");
+        board.set_chess960(true);
+
+        Ok(board)
+    }
+
+    /// # Chess960 Index
+    ///
+    /// Returns the Scharnagl index of the position if both back ranks hold the same
+    /// valid Chess960 arrangement, otherwise `None`.
+    pub fn chess960_index(&self) -> Option<usize> {
+        match self.dfrc_index()? {
+            (white, black) if white == black => Some(white),
+            _ => None,
+        }
+    }
+
+    /// # DFRC Indices
+    ///
+    /// Returns the Scharnagl indices of the white and black back ranks if both hold
+    /// valid Chess960 arrangements of their own pieces, otherwise `None`.
+    pub fn dfrc_index(&self) -> Option<(usize, usize)> {
+        let back_rank = |col: Colour| -> Option<usize> {
+            let rank = Rank::Rank1.relative(col);
+            let mut layout = [PieceType::Pawn; File::NUM];
+
+            for file in File::iter() {
+                let piece = self.on(Square::from_parts(file, rank))?;
+
+                if piece.colour() != col {
+                    return None;
+                }
+
+                layout[file.index()] = piece.pt();
+            }
+
+            chess960_index(&layout)
+        };
+
+        Some((back_rank(Colour::White)?, back_rank(Colour::Black)?))
+    }
+}
+
+/******************************************\
+|==========================================|
+|              Chess960 Errors             |
+|==========================================|
+\******************************************/
+
+/// Represents errors that can occur when building Chess960 or DFRC start positions.
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum Chess960Error {
+    #[error("Chess960 index must be in 0..960, found: {0}")]
+    InvalidIndex(usize),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::START_FEN;
+
+    // Helper to turn a back rank layout into its letter representation
+    fn layout_str(index: usize) -> String {
+        chess960_back_rank(index)
+            .unwrap()
+            .iter()
+            .map(|&pt| Piece::from_parts(Colour::White, pt).to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_published_table() {
+        // Entries from the Scharnagl numbering table
+        let table = [
+            (0, "BBQNNRKR"),
+            (1, "BQNBNRKR"),
+            (2, "BQNNRBKR"),
+            (3, "BQNNRKRB"),
+            (4, "QBBNNRKR"),
+            (518, "RNBQKBNR"),
+            (959, "RKRNNQBB"),
+        ];
+
+        for (index, expected) in table {
+            assert_eq!(layout_str(index), expected, "Index {index}");
+        }
+    }
+
+    #[test]
+    fn test_standard_start_position() {
+        let board = Board::from_chess960(STANDARD_CHESS960_INDEX).unwrap();
+        let standard = Board::from_fen(START_FEN).unwrap();
+
+        assert!(board.chess960());
+        assert_eq!(board.key(), standard.key());
+        assert_eq!(board.castling(), Castling::ALL);
+        assert_eq!(board.chess960_index(), Some(STANDARD_CHESS960_INDEX));
+    }
+
+    #[test]
+    fn test_all_positions_round_trip() {
+        for index in 0..CHESS960_POSITIONS {
+            let rank = chess960_back_rank(index).unwrap();
+            assert_eq!(chess960_index(&rank), Some(index), "Index {index}");
+
+            let board = Board::from_chess960(index).unwrap();
+            assert_eq!(board.chess960_index(), Some(index), "Index {index}");
+            assert_eq!(board.dfrc_index(), Some((index, index)));
+        }
+    }
+
+    #[test]
+    fn test_layouts_are_valid() {
+        for index in 0..CHESS960_POSITIONS {
+            let rank = chess960_back_rank(index).unwrap();
+            let find = |pt: PieceType| (0..File::NUM).filter(move |&f| rank[f] == pt);
+            let bishops: Vec<usize> = find(PieceType::Bishop).collect();
+            let rooks: Vec<usize> = find(PieceType::Rook).collect();
+            let king = find(PieceType::King).next().unwrap();
+
+            assert_ne!(bishops[0] % 2, bishops[1] % 2, "Index {index}");
+            assert!(rooks[0] < king && king < rooks[1], "Index {index}");
+        }
+    }
+
+    #[test]
+    fn test_castling_mask() {
+        // Index 0: BBQNNRKR
+        let board = Board::from_chess960(0).unwrap();
+
+        assert_eq!(board.castling(), Castling::ALL);
+        assert_eq!(board.rook_sq(Castling::WK), Square::H1);
+        assert_eq!(board.rook_sq(Castling::WQ), Square::F1);
+        assert_eq!(board.rook_sq(Castling::BK), Square::H8);
+        assert_eq!(board.rook_sq(Castling::BQ), Square::F8);
+
+        assert!(!board.castling_rights(Square::G1).has(Castling::WK));
+        assert!(!board.castling_rights(Square::F1).has(Castling::WQ));
+        assert!(board.castling_rights(Square::F1).has(Castling::WK));
+        assert!(!board.castling_rights(Square::H8).has(Castling::BK));
+    }
+
+    #[test]
+    fn test_dfrc() {
+        let board = Board::from_dfrc(0, 959).unwrap();
+
+        assert_eq!(
+            board.fen(),
+            "rkrnnqbb/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1"
+        );
+        assert_eq!(board.dfrc_index(), Some((0, 959)));
+        assert_eq!(board.chess960_index(), None);
+    }
+
+    #[test]
+    fn test_invalid_index() {
+        assert_eq!(
+            Board::from_chess960(960).err(),
+            Some(Chess960Error::InvalidIndex(960))
+        );
+        assert_eq!(
+            Board::from_dfrc(0, 1000).err(),
+            Some(Chess960Error::InvalidIndex(1000))
+        );
+    }
+
+    #[test]
+    fn test_non_start_position_has_no_index() {
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(board.dfrc_index(), None);
+
+        let rank = [PieceType::Rook; File::NUM];
+        assert_eq!(chess960_index(&rank), None);
+    }
+}
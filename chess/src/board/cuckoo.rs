@@ -0,0 +1,231 @@
+//! Upcoming repetition detection with cuckoo hashing.
+//!
+//! A reversible move changes the Zobrist key by the keys of its piece on both squares and the
+//! side key. Storing the key differences of every reversible move in a cuckoo table lets
+//! `Board::upcoming_repetition` find whether a single move returns to an earlier position, based
+//! on the algorithm of Marcel van Kervinck ("The Cuckoo Tables").
+use std::sync::LazyLock;
+
+use super::Board;
+use super::movegen::{attacks, lookup::between_bb};
+use super::zobrist::{Key, piece_key, side_key};
+use crate::core::*;
+
+/******************************************\
+|==========================================|
+|               Cuckoo Tables              |
+|==========================================|
+\******************************************/
+
+/// Number of slots of the cuckoo tables
+const CUCKOO_SIZE: usize = 0x2000;
+
+/// Number of reversible piece moves on an empty board, for both colours
+const REVERSIBLE_MOVES: usize = 3668;
+
+/// Cuckoo tables of the Zobrist key difference of every reversible move and the move itself
+pub struct CuckooTables {
+    keys: [Key; CUCKOO_SIZE],
+    moves: [Move; CUCKOO_SIZE],
+}
+
+/// First hash function of the cuckoo tables
+#[inline]
+const fn h1(key: Key) -> usize {
+    (key & 0x1FFF) as usize
+}
+
+/// Second hash function of the cuckoo tables
+#[inline]
+const fn h2(key: Key) -> usize {
+    ((key >> 16) & 0x1FFF) as usize
+}
+
+pub static CUCKOO: LazyLock<Box<CuckooTables>> = LazyLock::new(|| {
+    let mut tables = Box::new(CuckooTables {
+        keys: [0; CUCKOO_SIZE],
+        moves: [Move::NONE; CUCKOO_SIZE],
+    });
+    let mut count = 0;
+
+    for piece in Piece::iter().filter(|piece| piece.pt() != PieceType::Pawn) {
+        for from in Square::iter() {
+            let targets = attacks(piece.colour(), piece.pt(), from, Bitboard::EMPTY);
+
+            targets.for_each(|to| {
+                // Each pair of squares is stored once, the table is probed in both directions
+                if to.index() <= from.index() {
+                    return;
+                }
+
+                let mut key = piece_key(piece, from) ^ piece_key(piece, to) ^ side_key();
+                let mut move_ = Move::new(from, to, MoveFlag::QuietMove);
+                let mut slot = h1(key);
+
+                // Insert the move, evicting the entries in its way to their other slot
+                loop {
+                    std::mem::swap(&mut tables.keys[slot], &mut key);
+                    std::mem::swap(&mut tables.moves[slot], &mut move_);
+
+                    if move_ == Move::NONE {
+                        break;
+                    }
+
+                    slot = if slot == h1(key) { h2(key) } else { h1(key) };
+                }
+
+                count += 1;
+            });
+        }
+    }
+
+    assert_eq!(count, REVERSIBLE_MOVES, "Failed to build cuckoo tables");
+
+    tables
+});
+
+pub fn init_cuckoo_tables() {
+    let _ = &*CUCKOO;
+}
+
+impl CuckooTables {
+    /// Returns the reversible move changing the Zobrist key by `key`, if there is one
+    #[inline]
+    fn probe(&self, key: Key) -> Option<Move> {
+        [h1(key), h2(key)]
+            .into_iter()
+            .find(|&slot| self.keys[slot] == key)
+            .map(|slot| self.moves[slot])
+    }
+}
+
+/******************************************\
+|==========================================|
+|           Upcoming Repetition            |
+|==========================================|
+\******************************************/
+
+impl Board {
+    /// # Upcoming Repetition
+    ///
+    /// Returns `true` if the side to move has a reversible move reaching an earlier position,
+    /// so it can force a repetition draw. Only the positions since the last irreversible move
+    /// or null move are considered.
+    ///
+    /// `ply` is the distance to the search root. An earlier position inside the search tree
+    /// (`ply` plies ago or less) counts once it is reached, one before the root only if it
+    /// was already repeated, which matches `is_draw`.
+    pub fn upcoming_repetition(&self, ply: u16) -> bool {
+        let end = (self.state.fifty_move as usize).min(self.history.len());
+
+        if end < 3 {
+            return false;
+        }
+
+        let key = self.state.keys.key;
+        let key_at = |plies_ago: usize| self.history[self.history.len() - plies_ago].keys.key;
+
+        // Difference of the positions made by the opponent's moves, which must cancel out
+        let mut other = key ^ key_at(1) ^ side_key();
+
+        for i in (3..=end).step_by(2) {
+            other ^= key_at(i - 1) ^ key_at(i) ^ side_key();
+
+            if other != 0 {
+                continue;
+            }
+
+            let Some(move_) = CUCKOO.probe(key ^ key_at(i)) else {
+                continue;
+            };
+
+            if (between_bb(move_.from(), move_.to()) & self.all_occupied_bb()).is_empty()
+                && (ply as usize > i || self.history[self.history.len() - i].repetitions != 0)
+            {
+                return true;
+            }
+        }
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::START_FEN;
+
+    fn play(board: &mut Board, moves: &[(Square, Square)]) {
+        for &(from, to) in moves {
+            board.make_move(Move::new(from, to, MoveFlag::QuietMove));
+        }
+    }
+
+    #[test]
+    fn test_cuckoo_tables() {
+        let key = piece_key(Piece::WhiteKnight, Square::G1)
+            ^ piece_key(Piece::WhiteKnight, Square::F3)
+            ^ side_key();
+
+        assert_eq!(
+            CUCKOO.probe(key),
+            Some(Move::new(Square::G1, Square::F3, MoveFlag::QuietMove))
+        );
+        assert_eq!(CUCKOO.probe(key ^ side_key()), None);
+    }
+
+    #[test]
+    fn test_upcoming_repetition() {
+        let mut board = Board::from_fen(START_FEN).unwrap();
+
+        play(
+            &mut board,
+            &[(Square::G1, Square::F3), (Square::G8, Square::F6)],
+        );
+        assert!(!board.upcoming_repetition(10));
+
+        // Ng1 lets black return to the start position with Ng8
+        play(&mut board, &[(Square::F3, Square::G1)]);
+        assert!(board.upcoming_repetition(4));
+
+        // The start position is before the root and was not repeated yet
+        assert!(!board.upcoming_repetition(3));
+
+        // Once repeated, the start position counts before the root too
+        play(
+            &mut board,
+            &[
+                (Square::F6, Square::G8),
+                (Square::G1, Square::F3),
+                (Square::G8, Square::F6),
+                (Square::F3, Square::G1),
+            ],
+        );
+        assert!(board.upcoming_repetition(0));
+
+        // An irreversible move clears the window
+        play(&mut board, &[(Square::E7, Square::E6)]);
+        assert!(!board.upcoming_repetition(10));
+    }
+
+    #[test]
+    fn test_blocked_repetition() {
+        let moves = [
+            (Square::H8, Square::H7),
+            (Square::A1, Square::B2),
+            (Square::H7, Square::G8),
+            (Square::B2, Square::A3),
+            (Square::G8, Square::H8),
+        ];
+
+        // Qa3-a1 returns to the first position
+        let mut board = Board::from_fen("7k/8/8/8/8/8/2P5/Q3K3 b - - 0 1").unwrap();
+        play(&mut board, &moves);
+        assert!(board.upcoming_repetition(10));
+
+        // The pawn on a2 blocks Qa3-a1
+        let mut board = Board::from_fen("7k/8/8/8/8/8/P7/Q3K3 b - - 0 1").unwrap();
+        play(&mut board, &moves);
+        assert!(!board.upcoming_repetition(10));
+    }
+}
@@ -400,6 +400,9 @@ impl Board {
     /// This is because in a double check, only king moves are legal, so pinned pieces
     /// cannot move anyway, making the pin masks irrelevant for move generation in that specific state.
     pub(crate) fn update_masks(&mut self) {
+        // The position changed, so the cached threats are stale.
+        self.threats.clear();
+
         // Calculate squares attacked by the opponent.
         self.state.attacked = self.calc_attacked_bb();
 
@@ -7,21 +7,35 @@
 //! - FEN parsing and generation.
 //! - Move generation and application (delegated to submodules).
 //! - Zobrist hashing for position identification.
+pub mod builder;
+pub mod chess960;
+pub mod cuckoo;
 pub mod fen;
 pub mod mask;
 pub mod movegen;
 pub mod movement;
+pub mod packed;
+pub mod san;
+pub mod threats;
+pub mod transform;
+pub mod validate;
 pub mod zobrist;
 
+pub use builder::{BoardBuilder, PositionError};
+pub use chess960::{CHESS960_POSITIONS, Chess960Error, STANDARD_CHESS960_INDEX};
 pub use fen::{KILLER_FEN, START_FEN, TRICKY_FEN};
 pub use movegen::{
-    CaptureGen, LegalGen, MoveList, QuietGen, attacks, bishop_attacks, king_attack, knight_attack,
-    pawn_attack, queen_attacks, rook_attacks, sq_dist,
+    CaptureGen, EvasionGen, LegalGen, MoveList, QuietCheckGen, QuietGen, attacks, bishop_attacks,
+    king_attack, knight_attack, pawn_attack, queen_attacks, rook_attacks, sq_dist,
 };
+pub use packed::{PackError, PackedBoard};
+pub use threats::Threats;
+pub use validate::ValidationError;
 pub use zobrist::{Key, KeyBundle};
 
 use crate::core::*;
 use mask::CastlingMask;
+use threats::ThreatCache;
 
 /******************************************\
 |==========================================|
@@ -137,6 +151,10 @@ pub struct Board {
 
     /// A history of `BoardState` objects, used for undoing moves.
     history: Vec<BoardState>,
+
+    /// Attack maps and threats of the current position, computed on first use.
+    /// Kept out of `BoardState` so the history stays small, and cleared whenever the position changes.
+    threats: ThreatCache,
 }
 
 /******************************************\
@@ -167,6 +185,7 @@ impl Board {
             state: BoardState::default(),
             history: Vec::with_capacity(MAX_MOVES),
             chess960: false,
+            threats: ThreatCache::default(),
         }
     }
 
@@ -232,6 +251,12 @@ impl Board {
         self.half_moves
     }
 
+    /// Returns the number of half-moves since the last capture or pawn move (fifty-move rule counter).
+    #[inline]
+    pub fn fifty_move(&self) -> u8 {
+        self.state.fifty_move
+    }
+
     /// Returns the castling rights associated with a piece moving from or to a specific square.
     /// Used to determine if a move (e.g., king or rook move) affects castling availability.
     #[inline]
@@ -282,6 +307,8 @@ impl Board {
     fn restore_state(&mut self) {
         // Restore the last saved state from history.
         self.state = self.history.pop().unwrap();
+        // The position changed, so the cached threats are stale.
+        self.threats.clear();
     }
 
     /// Returns the Zobrist key for the current board position.
@@ -329,6 +356,17 @@ impl Board {
         return self.state.repetitions != 0 && self.state.repetitions < ply_from_null as i8;
     }
 
+    /// Returns `true` if a position with the current side to move has repeated since the last
+    /// irreversible move. Repetitions before a capture or pawn move are ignored.
+    pub fn has_repeated(&self) -> bool {
+        let end = (self.state.fifty_move as usize).min(self.history.len());
+
+        self.state.repetitions != 0
+            || (2..=end)
+                .step_by(2)
+                .any(|i| self.history[self.history.len() - i].repetitions != 0)
+    }
+
     /// Checks if a file is semi-open for the given color (no pawns of that color on the file).
     #[inline]
     pub fn is_semi_open_file(&self, col: Colour, sq: Square) -> bool {
@@ -27,11 +27,29 @@ pub use move_list::MoveList;
 /// # Move Generation Type representation
 ///
 /// - The current move generator allows generating all legal moves, quiet legal moves or capture legal moves.
+/// - Evasions are the legal moves of a position in check, and nothing otherwise.
+/// - Quiet checks are the quiet legal moves giving a direct or discovered check.
 #[derive(PartialEq, Eq)]
 pub enum MoveGenType {
     Legal,
     Quiet,
     Capture,
+    Evasion,
+    QuietCheck,
+}
+
+impl MoveGenType {
+    /// Returns whether captures, including promotion captures and en passant, are generated
+    #[inline]
+    pub const fn has_captures(&self) -> bool {
+        matches!(self, Self::Legal | Self::Capture | Self::Evasion)
+    }
+
+    /// Returns whether quiet moves, including quiet promotions and castling, are generated
+    #[inline]
+    pub const fn has_quiets(&self) -> bool {
+        !matches!(self, Self::Capture)
+    }
 }
 
 /******************************************\
@@ -47,6 +65,8 @@ pub trait GenTypeTrait {
 pub struct LegalGen;
 pub struct QuietGen;
 pub struct CaptureGen;
+pub struct EvasionGen;
+pub struct QuietCheckGen;
 
 impl GenTypeTrait for LegalGen {
     fn gen_type() -> MoveGenType {
@@ -63,6 +83,16 @@ impl GenTypeTrait for CaptureGen {
         MoveGenType::Capture
     }
 }
+impl GenTypeTrait for EvasionGen {
+    fn gen_type() -> MoveGenType {
+        MoveGenType::Evasion
+    }
+}
+impl GenTypeTrait for QuietCheckGen {
+    fn gen_type() -> MoveGenType {
+        MoveGenType::QuietCheck
+    }
+}
 
 /******************************************\
 |==========================================|
@@ -162,3 +192,86 @@ impl Board {
             && !self.hv_pin().contains(rook_sq)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{KILLER_FEN, START_FEN, TRICKY_FEN};
+
+    fn moves<G: GenTypeTrait>(board: &Board) -> Vec<u16> {
+        let mut move_list = MoveList::new();
+        board.generate_moves::<G>(&mut move_list);
+
+        let mut moves: Vec<u16> = move_list.iter().map(|m| m.raw()).collect();
+        moves.sort_unstable();
+        moves
+    }
+
+    /// Checks every generator against the legal moves at each node of the tree
+    fn check_generators(board: &mut Board, depth: usize) {
+        let legal = moves::<LegalGen>(board);
+        let captures = moves::<CaptureGen>(board);
+        let quiets = moves::<QuietGen>(board);
+
+        let mut union = [captures.clone(), quiets.clone()].concat();
+        union.sort_unstable();
+        assert_eq!(union, legal, "{}", board.fen());
+
+        let evasions = moves::<EvasionGen>(board);
+        if board.in_check() {
+            assert_eq!(evasions, legal, "{}", board.fen());
+        } else {
+            assert!(evasions.is_empty(), "{}", board.fen());
+        }
+
+        let mut move_list = MoveList::new();
+        board.generate_moves::<LegalGen>(&mut move_list);
+
+        let mut quiet_checks = Vec::new();
+
+        for &move_ in move_list.iter() {
+            let gives_check = board.gives_check(move_);
+
+            board.make_move(move_);
+            assert_eq!(gives_check, board.in_check(), "{} {move_:?}", board.fen());
+            if depth > 1 {
+                check_generators(board, depth - 1);
+            }
+            board.undo_move(move_);
+
+            if gives_check && quiets.contains(&move_.raw()) {
+                quiet_checks.push(move_.raw());
+            }
+        }
+
+        quiet_checks.sort_unstable();
+        assert_eq!(
+            moves::<QuietCheckGen>(board),
+            quiet_checks,
+            "{}",
+            board.fen()
+        );
+    }
+
+    #[test]
+    fn test_generators() {
+        #[rustfmt::skip]
+        let positions = [
+            (START_FEN, 3),
+            (TRICKY_FEN, 3),
+            (KILLER_FEN, 3),
+            ("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", 4),
+            ("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1", 3),
+            ("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8", 3),
+            // En passant discovering a rook check, and castling giving check
+            ("8/8/8/1k1pP1R1/8/8/8/4K3 w - d6 0 1", 2),
+            ("5k2/8/8/8/8/8/8/4K2R w K - 0 1", 2),
+            ("1rkr4/8/8/8/8/8/8/1RKR4 w KQkq - 0 1", 2),
+        ];
+
+        for (fen, depth) in positions {
+            let mut board = Board::from_fen(fen).unwrap();
+            check_generators(&mut board, depth);
+        }
+    }
+}
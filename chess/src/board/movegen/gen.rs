@@ -58,13 +58,13 @@ fn add_piece_moves<G: GenTypeTrait>(
     let enemy_bb = board.occupied_bb(them);
     let empty_bb = !board.all_occupied_bb();
 
-    if G::gen_type() != MoveGenType::Quiet {
+    if G::gen_type().has_captures() {
         (dest & enemy_bb).for_each(|to| {
             move_list.add_move(Move::new(from, to, MoveFlag::Capture));
         });
     }
 
-    if G::gen_type() != MoveGenType::Capture {
+    if G::gen_type().has_quiets() {
         (dest & empty_bb).for_each(|to| {
             move_list.add_move(Move::new(from, to, MoveFlag::QuietMove));
         })
@@ -96,6 +96,12 @@ fn add_castling_move(castle: Castling, board: &Board, move_list: &mut MoveList)
 \******************************************/
 
 pub(crate) fn generate_move<G: GenTypeTrait>(board: &Board, move_list: &mut MoveList) {
+    match G::gen_type() {
+        MoveGenType::Evasion if !board.in_check() => return,
+        MoveGenType::QuietCheck => return gen_quiet_checks(board, move_list),
+        _ => {}
+    }
+
     if board.check_mask() == Bitboard::EMPTY {
         gen_king_moves::<G>(board, move_list);
     } else {
@@ -105,20 +111,19 @@ pub(crate) fn generate_move<G: GenTypeTrait>(board: &Board, move_list: &mut Move
         gen_hv_slider_moves::<G>(board, move_list);
         gen_king_moves::<G>(board, move_list);
 
-        if board.check_mask() == Bitboard::FULL && G::gen_type() != MoveGenType::Capture {
+        if board.check_mask() == Bitboard::FULL && G::gen_type().has_quiets() {
             gen_castling_moves(board, move_list);
         }
     }
 }
 
 fn gen_pawn_moves<G: GenTypeTrait>(board: &Board, move_list: &mut MoveList) {
-    match G::gen_type() {
-        MoveGenType::Quiet => gen_pawn_quiets(board, move_list),
-        MoveGenType::Capture => gen_pawn_captures(board, move_list),
-        MoveGenType::Legal => {
-            gen_pawn_captures(board, move_list);
-            gen_pawn_quiets(board, move_list);
-        }
+    if G::gen_type().has_captures() {
+        gen_pawn_captures(board, move_list);
+    }
+
+    if G::gen_type().has_quiets() {
+        gen_pawn_quiets(board, move_list);
     }
 }
 
@@ -304,6 +309,44 @@ fn gen_king_moves<G: GenTypeTrait>(board: &Board, move_list: &mut MoveList) {
     add_piece_moves::<G>(board, from, dest, move_list);
 }
 
+/// Generates the quiet moves giving check. Only the moves which can give check are tested:
+/// moves of pieces blocking one of our sliders from their king, moves to a square attacking
+/// the king, and castling or promotions.
+fn gen_quiet_checks(board: &Board, move_list: &mut MoveList) {
+    let us = board.stm();
+    let ksq = board.ksq(!us);
+    let occ = board.all_occupied_bb();
+
+    let mut quiets = MoveList::new();
+    generate_move::<QuietGen>(board, &mut quiets);
+
+    // Our pieces between one of our sliders and their king discover a check when moving
+    let snipers = bishop_attacks(ksq, Bitboard::EMPTY) & board.bishop_queen_bb(us)
+        | rook_attacks(ksq, Bitboard::EMPTY) & board.rook_queen_bb(us);
+    let mut blockers = Bitboard::EMPTY;
+
+    snipers.for_each(|sniper| {
+        let between = lookup::between_bb(ksq, sniper) & occ;
+        if between.count_bits() == 1 {
+            blockers |= between & board.occupied_bb(us);
+        }
+    });
+
+    for &move_ in quiets.iter() {
+        let from = move_.from();
+        let pt = unsafe { board.on(from).unwrap_unchecked() }.pt();
+
+        let may_check = blockers.contains(from)
+            || move_.is_castle()
+            || move_.is_promotion()
+            || attacks(us, pt, move_.to(), occ ^ from.bb()).contains(ksq);
+
+        if may_check && board.gives_check(move_) {
+            move_list.add_move(move_);
+        }
+    }
+}
+
 fn gen_castling_moves(board: &Board, move_list: &mut MoveList) {
     let us = board.stm();
 
@@ -1,8 +1,8 @@
 use std::usize;
 
 use super::Board;
-use super::attacks;
 use super::movegen::aligned;
+use super::{attacks, bishop_attacks, rook_attacks};
 use crate::core::*;
 
 impl Board {
@@ -665,6 +665,61 @@ impl Board {
     pub fn is_capture(&self, move_: Move) -> bool {
         move_.is_valid() && self.on(move_.to()).is_some_and(|p| p.colour() != self.stm)
     }
+
+    /// Checks if a legal move gives check, without making it.
+    ///
+    /// Detects direct checks, including those of a promoted piece or of the rook
+    /// when castling, and discovered checks, including those revealed by removing
+    /// the pawn captured en passant.
+    pub fn gives_check(&self, move_: Move) -> bool {
+        use MoveFlag::*;
+
+        let us = self.stm;
+        let ksq = self.ksq(!us);
+
+        let from = move_.from();
+        let to = move_.to();
+        let flag = move_.flag();
+
+        let mut occ = self.all_occupied_bb() ^ from.bb();
+        let diag_sliders = self.bishop_queen_bb(us) & !from.bb();
+        let mut hv_sliders = self.rook_queen_bb(us) & !from.bb();
+
+        // The piece type and square of the piece which could give a direct check
+        let (pt, sq) = match flag {
+            KingCastle | QueenCastle => {
+                let castle = if flag == KingCastle {
+                    Castling::king_side(us)
+                } else {
+                    Castling::queen_side(us)
+                };
+                let rook_sq = self.rook_sq(castle);
+                let rook_dest = self.castling_rook_dest(castle);
+
+                occ = (occ & !rook_sq.bb()) | rook_dest.bb();
+                hv_sliders &= !rook_sq.bb();
+
+                (PieceType::Rook, rook_dest)
+            }
+            EPCapture => {
+                occ ^= unsafe { to.add_unchecked(-us.forward()) }.bb();
+                (PieceType::Pawn, to)
+            }
+            _ if move_.is_promotion() => (unsafe { move_.promotion_pt() }, to),
+            _ => (unsafe { self.on(from).unwrap_unchecked() }.pt(), to),
+        };
+
+        occ |= to.bb();
+
+        // Direct check
+        if attacks(us, pt, sq, occ).contains(ksq) {
+            return true;
+        }
+
+        // Discovered check by one of the sliders that did not move
+        (bishop_attacks(ksq, occ) & diag_sliders).is_occupied()
+            || (rook_attacks(ksq, occ) & hv_sliders).is_occupied()
+    }
 }
 
 /******************************************\
@@ -1185,4 +1240,31 @@ mod tests {
         board.make_move(nc6);
         assert_repetitions(&board, -3);
     }
+
+    #[test]
+    fn test_has_repeated() {
+        let mut board = board_from_fen(START_FEN);
+
+        let nf3 = Move::new(Square::G1, Square::F3, MoveFlag::QuietMove);
+        let nc6 = Move::new(Square::B8, Square::C6, MoveFlag::QuietMove);
+        let ng1 = Move::new(Square::F3, Square::G1, MoveFlag::QuietMove);
+        let nb8 = Move::new(Square::C6, Square::B8, MoveFlag::QuietMove);
+        let na6 = Move::new(Square::B8, Square::A6, MoveFlag::QuietMove);
+        let e4 = Move::new(Square::E2, Square::E4, MoveFlag::DoublePawnPush);
+
+        board.make_move(nf3);
+        board.make_move(nc6);
+        board.make_move(ng1);
+        board.make_move(nb8);
+        assert!(board.has_repeated());
+
+        // The repetition stays visible from a new position until an irreversible move
+        board.make_move(nf3);
+        board.make_move(na6);
+        assert_repetitions(&board, 0);
+        assert!(board.has_repeated());
+
+        board.make_move(e4);
+        assert!(!board.has_repeated());
+    }
 }
@@ -0,0 +1,271 @@
+//! Fixed-size binary encoding of a position.
+//!
+//! A `PackedBoard` stores everything needed to rebuild a `Board` in 32 bytes, so positions can
+//! be written as fixed-size records by datasets, book files or hash table dumps.
+use thiserror::Error;
+
+use super::{Board, BoardBuilder, PositionError};
+use crate::core::*;
+
+/******************************************\
+|==========================================|
+|               Packed Board               |
+|==========================================|
+\******************************************/
+
+/// Maximum number of pieces a packed board can hold
+const MAX_PIECES: usize = 32;
+
+/// Castling rights order of the packed castling field
+const RIGHTS: [Castling; 4] = [Castling::WK, Castling::WQ, Castling::BK, Castling::BQ];
+
+/// Value of the en passant byte when there is no en passant square
+const NO_EP: u8 = 0xFF;
+
+/// A position packed into 32 little endian bytes:
+/// - bytes 0-7: occupancy bitboard
+/// - bytes 8-23: one nibble per occupied square, from a1 to h8, holding the piece index
+/// - byte 24: side to move in bit 0, Chess960 flag in bit 1
+/// - bytes 25-26: one nibble per castling right (`WK`, `WQ`, `BK`, `BQ`) holding the rook file,
+///   with bit 3 set when the right is available
+/// - byte 27: en passant square, `0xFF` if none
+/// - byte 28: fifty-move counter
+/// - bytes 29-30: half-move counter
+/// - byte 31: reserved, always zero
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PackedBoard([u8; PackedBoard::SIZE]);
+
+impl PackedBoard {
+    /// Size of a packed board in bytes
+    pub const SIZE: usize = 32;
+
+    /// # Pack Board
+    ///
+    /// Encodes the position of the board. The move history is not stored.
+    ///
+    /// ## Errors
+    /// Returns `PackError::TooManyPieces` if the board holds more than 32 pieces.
+    pub fn pack(board: &Board) -> Result<Self, PackError> {
+        let occupied = board.all_occupied_bb();
+        let pieces = occupied.count_bits();
+
+        if pieces as usize > MAX_PIECES {
+            return Err(PackError::TooManyPieces(pieces));
+        }
+
+        let mut bytes = [0; Self::SIZE];
+        bytes[0..8].copy_from_slice(&occupied.0.to_le_bytes());
+
+        let mut nibble = 0;
+        occupied.for_each(|sq| {
+            // Safety: the square is occupied
+            let piece = unsafe { board.on_unchecked(sq) };
+            bytes[8 + nibble / 2] |= (piece as u8) << (4 * (nibble % 2));
+            nibble += 1;
+        });
+
+        bytes[24] = board.stm() as u8 | (board.chess960() as u8) << 1;
+
+        let mut castling = 0u16;
+        for (i, right) in RIGHTS.into_iter().enumerate() {
+            if board.castling().has(right) {
+                let file = board.rook_sq(right).file() as u16;
+                castling |= (0b1000 | file) << (4 * i);
+            }
+        }
+        bytes[25..27].copy_from_slice(&castling.to_le_bytes());
+
+        bytes[27] = board.ep().map_or(NO_EP, |sq| sq as u8);
+        bytes[28] = board.fifty_move();
+        bytes[29..31].copy_from_slice(&board.half_moves().to_le_bytes());
+
+        Ok(Self(bytes))
+    }
+
+    /// # Unpack Board
+    ///
+    /// Decodes the position, which is checked like one made with `BoardBuilder`.
+    ///
+    /// ## Errors
+    /// Returns `PackError` if a field holds an invalid value or the position is illegal.
+    pub fn unpack(&self) -> Result<Board, PackError> {
+        let bytes = &self.0;
+        let occupied = Bitboard(u64::from_le_bytes(bytes[0..8].try_into().unwrap()));
+
+        if occupied.count_bits() as usize > MAX_PIECES {
+            return Err(PackError::TooManyPieces(occupied.count_bits()));
+        }
+
+        let mut builder = BoardBuilder::new();
+        let squares = Square::iter().filter(|&sq| occupied.contains(sq));
+
+        for (nibble, sq) in squares.enumerate() {
+            let index = bytes[8 + nibble / 2] >> (4 * (nibble % 2)) & 0xF;
+
+            if index as usize >= Piece::NUM {
+                return Err(PackError::InvalidPiece(index));
+            }
+
+            // Safety: the index was checked above
+            builder = builder.piece(sq, unsafe { Piece::from_unchecked(index) });
+        }
+
+        let stm = match bytes[24] & 1 {
+            0 => Colour::White,
+            _ => Colour::Black,
+        };
+
+        builder = builder
+            .side_to_move(stm)
+            .chess960(bytes[24] & 0b10 != 0)
+            .fifty_move(bytes[28])
+            .full_moves(u16::from_le_bytes([bytes[29], bytes[30]]) / 2 + 1);
+
+        let castling = u16::from_le_bytes([bytes[25], bytes[26]]);
+        for (i, right) in RIGHTS.into_iter().enumerate() {
+            let field = (castling >> (4 * i)) as u8 & 0xF;
+
+            if field & 0b1000 != 0 {
+                let col = if Castling::WHITE_CASTLING.has(right) {
+                    Colour::White
+                } else {
+                    Colour::Black
+                };
+
+                // Safety: the file takes 3 bits
+                let file = unsafe { File::from_unchecked(field & 0b111) };
+                builder = builder.castle_with(col, file);
+            }
+        }
+
+        let ep = match bytes[27] {
+            NO_EP => None,
+            // Safety: the square index is on the board
+            sq if sq < Square::NUM as u8 => Some(unsafe { Square::from_unchecked(sq) }),
+            sq => return Err(PackError::InvalidEnPassant(sq)),
+        };
+
+        Ok(builder.ep(ep).build()?)
+    }
+
+    /// Creates a packed board from its bytes
+    pub const fn from_bytes(bytes: [u8; Self::SIZE]) -> Self {
+        Self(bytes)
+    }
+
+    /// Returns the bytes of the packed board
+    pub const fn to_bytes(&self) -> [u8; Self::SIZE] {
+        self.0
+    }
+}
+
+/******************************************\
+|==========================================|
+|               Pack Errors                |
+|==========================================|
+\******************************************/
+
+/// Represents errors that can occur when packing or unpacking a board.
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum PackError {
+    #[error("A packed board holds at most 32 pieces, found: {0}")]
+    TooManyPieces(u32),
+    #[error("Invalid packed piece: {0}")]
+    InvalidPiece(u8),
+    #[error("Invalid packed en passant square: {0}")]
+    InvalidEnPassant(u8),
+    #[error("Invalid packed position: {0}")]
+    InvalidPosition(#[from] PositionError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{LegalGen, MoveList, START_FEN, TRICKY_FEN};
+
+    // Packs every position of the tree up to the depth and checks it unpacks to the same position
+    fn round_trip(board: &mut Board, depth: usize) {
+        let unpacked = PackedBoard::pack(board).unwrap().unpack().unwrap();
+
+        assert_eq!(unpacked.fen(), board.fen());
+        assert_eq!(unpacked.key(), board.key());
+        assert_eq!(unpacked.chess960(), board.chess960());
+
+        if depth == 0 {
+            return;
+        }
+
+        let mut move_list = MoveList::new();
+        board.generate_moves::<LegalGen>(&mut move_list);
+
+        for move_ in move_list.iter() {
+            board.make_move(*move_);
+            round_trip(board, depth - 1);
+            board.undo_move(*move_);
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for fen in [
+            START_FEN,
+            TRICKY_FEN,
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+        ] {
+            let mut board = Board::from_fen(fen).unwrap();
+            assert_eq!(PackedBoard::pack(&board).unwrap().unpack().unwrap(), board);
+
+            round_trip(&mut board, 2);
+        }
+
+        for index in [0, 518, 959] {
+            round_trip(&mut Board::from_chess960(index).unwrap(), 2);
+        }
+    }
+
+    #[test]
+    fn test_bytes() {
+        let board = Board::from_fen("4k3/8/8/3pP3/8/8/8/R3K3 w Q d6 5 40").unwrap();
+        let packed = PackedBoard::pack(&board).unwrap();
+        let bytes = packed.to_bytes();
+
+        assert_eq!(PackedBoard::from_bytes(bytes), packed);
+        assert_eq!(bytes[8..11], [0xA6, 0x01, 0x0B]);
+        assert_eq!(bytes[24], 0);
+        assert_eq!(u16::from_le_bytes([bytes[25], bytes[26]]), 0b1000 << 4);
+        assert_eq!(bytes[27], Square::D6 as u8);
+        assert_eq!(bytes[28], 5);
+        assert_eq!(u16::from_le_bytes([bytes[29], bytes[30]]), 78);
+    }
+
+    #[test]
+    fn test_errors() {
+        let board =
+            Board::from_fen("qqqqkqqq/qqqqqqqq/qqqqqqqq/qqqqqqqq/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(PackedBoard::pack(&board), Err(PackError::TooManyPieces(33)));
+
+        let mut bytes = PackedBoard::pack(&Board::default()).unwrap().to_bytes();
+        bytes[27] = 64;
+        assert_eq!(
+            PackedBoard::from_bytes(bytes).unpack(),
+            Err(PackError::InvalidEnPassant(64))
+        );
+
+        bytes[27] = NO_EP;
+        bytes[8] = 0xFF;
+        assert_eq!(
+            PackedBoard::from_bytes(bytes).unpack(),
+            Err(PackError::InvalidPiece(0xF))
+        );
+
+        // Unpacked positions are checked, here the side not to move is in check
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/4R1K1 w - - 0 1").unwrap();
+        assert_eq!(
+            PackedBoard::pack(&board).unwrap().unpack(),
+            Err(PackError::InvalidPosition(PositionError::OpponentInCheck))
+        );
+    }
+}
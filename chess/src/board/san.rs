@@ -0,0 +1,200 @@
+use thiserror::Error;
+
+use super::{Board, LegalGen, MoveList};
+use crate::core::*;
+
+/******************************************\
+|==========================================|
+|                SAN Parsing               |
+|==========================================|
+\******************************************/
+
+impl Board {
+    /// # Parse SAN Move
+    ///
+    /// Converts a move in Standard Algebraic Notation (e.g. `Nf3`, `exd5`, `e8=Q+`, `O-O`)
+    /// into the matching legal move in the current position.
+    ///
+    /// Check and annotation suffixes (`+`, `#`, `!`, `?`) are ignored, and castling
+    /// may be written with either letter O or digit 0.
+    ///
+    /// ## Errors
+    /// Returns `SanParseError` if the string is malformed, or does not identify exactly one legal move.
+    pub fn parse_san(&self, san: &str) -> Result<Move, SanParseError> {
+        let trimmed = san.trim_end_matches(['+', '#', '!', '?']);
+
+        let mut move_list = MoveList::new();
+        self.generate_moves::<LegalGen>(&mut move_list);
+
+        // --- Castling ---
+        let castle_flag = match trimmed {
+            "O-O" | "0-0" => Some(MoveFlag::KingCastle),
+            "O-O-O" | "0-0-0" => Some(MoveFlag::QueenCastle),
+            _ => None,
+        };
+
+        if let Some(flag) = castle_flag {
+            return move_list
+                .iter()
+                .find(|m| m.flag() == flag)
+                .copied()
+                .ok_or_else(|| SanParseError::IllegalMove(san.to_string()));
+        }
+
+        // --- Promotion suffix ---
+        let (body, promotion) = match trimmed.char_indices().last() {
+            Some((i, c @ ('N' | 'B' | 'R' | 'Q'))) if i >= 2 => {
+                let body = trimmed[..i].trim_end_matches('=');
+                (body, Some(Self::san_piece_type(c).unwrap()))
+            }
+            _ => (trimmed, None),
+        };
+
+        // --- Moving piece ---
+        let (piece_type, body) = match body.chars().next().and_then(Self::san_piece_type) {
+            Some(pt) => (pt, &body[1..]),
+            None => (PieceType::Pawn, body),
+        };
+
+        // --- Destination square and disambiguation ---
+        if body.len() < 2 || !body.is_ascii() {
+            return Err(SanParseError::InvalidFormat(san.to_string()));
+        }
+
+        let (disambiguation, to) = body.split_at(body.len() - 2);
+        let to: Square = to
+            .parse()
+            .map_err(|_| SanParseError::InvalidFormat(san.to_string()))?;
+
+        let mut from_file = None;
+        let mut from_rank = None;
+
+        for c in disambiguation.chars().filter(|&c| c != 'x') {
+            match c {
+                'a'..='h' => from_file = Some(c.to_string().parse::<File>().unwrap()),
+                '1'..='8' => from_rank = Some(c.to_string().parse::<Rank>().unwrap()),
+                _ => return Err(SanParseError::InvalidFormat(san.to_string())),
+            }
+        }
+
+        let matches = |move_: &&Move| {
+            let from = move_.from();
+            !move_.is_castle()
+                && move_.to() == to
+                && self.on(from).map(|pc| pc.pt()) == Some(piece_type)
+                && from_file.is_none_or(|file| from.file() == file)
+                && from_rank.is_none_or(|rank| from.rank() == rank)
+                && promotion
+                    == move_
+                        .is_promotion()
+                        .then(|| unsafe { move_.promotion_pt() })
+        };
+
+        let mut candidates = move_list.iter().filter(matches);
+
+        match (candidates.next(), candidates.next()) {
+            (Some(&move_), None) => Ok(move_),
+            (Some(_), Some(_)) => Err(SanParseError::AmbiguousMove(san.to_string())),
+            (None, _) => Err(SanParseError::IllegalMove(san.to_string())),
+        }
+    }
+
+    /// Converts a SAN piece letter to its piece type, pawns have no letter.
+    fn san_piece_type(c: char) -> Option<PieceType> {
+        match c {
+            'N' => Some(PieceType::Knight),
+            'B' => Some(PieceType::Bishop),
+            'R' => Some(PieceType::Rook),
+            'Q' => Some(PieceType::Queen),
+            'K' => Some(PieceType::King),
+            _ => None,
+        }
+    }
+}
+
+/******************************************\
+|==========================================|
+|             SAN Parse Errors             |
+|==========================================|
+\******************************************/
+
+/// Represents errors that can occur when parsing a move in Standard Algebraic Notation.
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum SanParseError {
+    #[error("Invalid SAN format: {0}")]
+    InvalidFormat(String),
+    #[error("No legal move matches SAN: {0}")]
+    IllegalMove(String),
+    #[error("More than one legal move matches SAN: {0}")]
+    AmbiguousMove(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::TRICKY_FEN;
+
+    fn assert_san(board: &Board, san: &str, uci: &str) {
+        let move_ = board
+            .parse_san(san)
+            .unwrap_or_else(|e| panic!("Failed to parse '{san}': {e}"));
+        assert_eq!(move_.to_str(board), uci, "SAN: {san}");
+    }
+
+    #[test]
+    fn test_basic_moves() {
+        let board = Board::default();
+
+        assert_san(&board, "e4", "e2e4");
+        assert_san(&board, "Nf3", "g1f3");
+        assert_san(&board, "Nc3!?", "b1c3");
+        assert!(matches!(
+            board.parse_san("e5"),
+            Err(SanParseError::IllegalMove(_))
+        ));
+        assert!(matches!(
+            board.parse_san("Z"),
+            Err(SanParseError::InvalidFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_captures_and_castling() {
+        let board = Board::from_fen(TRICKY_FEN).unwrap();
+
+        assert_san(&board, "O-O", "e1g1");
+        assert_san(&board, "0-0-0", "e1c1");
+        assert_san(&board, "Bxa6", "e2a6");
+        assert_san(&board, "Nxf7", "e5f7");
+        assert_san(&board, "dxe6", "d5e6");
+    }
+
+    #[test]
+    fn test_disambiguation() {
+        let board = Board::from_fen("4k3/8/8/8/8/8/4K3/R6R w - - 0 1").unwrap();
+
+        assert!(matches!(
+            board.parse_san("Rd1"),
+            Err(SanParseError::AmbiguousMove(_))
+        ));
+        assert_san(&board, "Rad1", "a1d1");
+        assert_san(&board, "Rhf1", "h1f1");
+
+        let board = Board::from_fen("4k3/8/8/8/8/N7/8/N3K3 w - - 0 1").unwrap();
+        assert_san(&board, "N1b3", "a1b3");
+        assert_san(&board, "N3b5", "a3b5");
+    }
+
+    #[test]
+    fn test_promotions() {
+        let board = Board::from_fen("1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+
+        assert_san(&board, "a8=Q+", "a7a8q");
+        assert_san(&board, "a8N", "a7a8n");
+        assert_san(&board, "axb8=R", "a7b8r");
+        assert!(matches!(
+            board.parse_san("a8"),
+            Err(SanParseError::IllegalMove(_))
+        ));
+    }
+}
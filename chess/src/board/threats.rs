@@ -0,0 +1,266 @@
+use std::sync::OnceLock;
+
+use super::Board;
+use super::movegen::{attacks, bishop_attacks, lookup::between_bb, rook_attacks};
+use crate::core::*;
+
+/******************************************\
+|==========================================|
+|                 Threats                  |
+|==========================================|
+\******************************************/
+
+/// Attack maps of both colours in a position, with the threats derived from them.
+///
+/// Attacks are computed with the full occupancy, so sliders do not x-ray through other pieces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Threats {
+    /// Squares attacked by each piece type of each colour
+    by_piece: [[Bitboard; PieceType::NUM]; Colour::NUM],
+    /// Squares attacked by each colour
+    by_colour: [Bitboard; Colour::NUM],
+    /// Pieces of each colour attacked by a lower valued piece
+    by_lower: [Bitboard; Colour::NUM],
+    /// Pieces of each colour, kings excluded, attacked and not defended
+    hanging: [Bitboard; Colour::NUM],
+    /// Pieces of each colour pinned to their king
+    pinned: [Bitboard; Colour::NUM],
+    /// Sliders pinning a piece of the other colour to its king
+    pinners: [Bitboard; Colour::NUM],
+}
+
+impl Threats {
+    /// Computes the attack maps and threats of the board
+    fn new(board: &Board) -> Self {
+        let occ = board.all_occupied_bb();
+        let mut by_piece = [[Bitboard::EMPTY; PieceType::NUM]; Colour::NUM];
+        let mut by_colour = [Bitboard::EMPTY; Colour::NUM];
+
+        for col in [Colour::White, Colour::Black] {
+            for pt in PieceType::iter() {
+                board.piece_bb(col, pt).for_each(|sq| {
+                    by_piece[col.index()][pt.index()] |= attacks(col, pt, sq, occ);
+                });
+
+                by_colour[col.index()] |= by_piece[col.index()][pt.index()];
+            }
+        }
+
+        let mut threats = Self {
+            by_piece,
+            by_colour,
+            by_lower: [Bitboard::EMPTY; Colour::NUM],
+            hanging: [Bitboard::EMPTY; Colour::NUM],
+            pinned: [Bitboard::EMPTY; Colour::NUM],
+            pinners: [Bitboard::EMPTY; Colour::NUM],
+        };
+
+        for col in [Colour::White, Colour::Black] {
+            let them = !col;
+            let enemy = |pt: PieceType| by_piece[them.index()][pt.index()];
+            let ours = |pt: PieceType| board.piece_bb(col, pt);
+
+            let by_pawns = enemy(PieceType::Pawn);
+            let by_minors = by_pawns | enemy(PieceType::Knight) | enemy(PieceType::Bishop);
+            let by_rooks = by_minors | enemy(PieceType::Rook);
+
+            threats.by_lower[col.index()] = (ours(PieceType::Knight) | ours(PieceType::Bishop))
+                & by_pawns
+                | ours(PieceType::Rook) & by_minors
+                | ours(PieceType::Queen) & by_rooks;
+
+            threats.hanging[col.index()] = board.occupied_bb(col)
+                & !ours(PieceType::King)
+                & by_colour[them.index()]
+                & !by_colour[col.index()];
+
+            (threats.pinned[col.index()], threats.pinners[them.index()]) = Self::pins(board, col);
+        }
+
+        threats
+    }
+
+    /// Returns the pieces of `col` pinned to their king, and the enemy sliders pinning them
+    fn pins(board: &Board, col: Colour) -> (Bitboard, Bitboard) {
+        let them = !col;
+        let ksq = board.ksq(col);
+        let occ = board.all_occupied_bb();
+
+        let snipers = bishop_attacks(ksq, Bitboard::EMPTY) & board.bishop_queen_bb(them)
+            | rook_attacks(ksq, Bitboard::EMPTY) & board.rook_queen_bb(them);
+
+        let mut pinned = Bitboard::EMPTY;
+        let mut pinners = Bitboard::EMPTY;
+
+        snipers.for_each(|sniper| {
+            let blockers = between_bb(ksq, sniper) & occ;
+
+            if blockers.count_bits() == 1 && (blockers & board.occupied_bb(col)).is_occupied() {
+                pinned |= blockers;
+                pinners.set(sniper);
+            }
+        });
+
+        (pinned, pinners)
+    }
+}
+
+/// Lazily computed `Threats` of a `Board`.
+///
+/// The cache is cleared whenever the position changes, and is ignored when comparing boards.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ThreatCache(OnceLock<Threats>);
+
+impl ThreatCache {
+    /// Drops the cached threats, so they are computed again on next use
+    #[inline]
+    pub(crate) fn clear(&mut self) {
+        self.0.take();
+    }
+}
+
+impl PartialEq for ThreatCache {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for ThreatCache {}
+
+/******************************************\
+|==========================================|
+|             Board Threat API             |
+|==========================================|
+\******************************************/
+
+impl Board {
+    /// Returns the attack maps and threats of the position, computed on first use
+    #[inline]
+    pub fn threats(&self) -> &Threats {
+        self.threats.0.get_or_init(|| Threats::new(self))
+    }
+
+    /// Returns the squares attacked by the pieces of the given colour and type
+    #[inline]
+    pub fn attacks_by(&self, col: Colour, pt: PieceType) -> Bitboard {
+        self.threats().by_piece[col.index()][pt.index()]
+    }
+
+    /// Returns the squares attacked by the given colour
+    #[inline]
+    pub fn attacked_by(&self, col: Colour) -> Bitboard {
+        self.threats().by_colour[col.index()]
+    }
+
+    /// Returns the pieces of the given colour attacked by a lower valued enemy piece:
+    /// minor pieces attacked by pawns, rooks by minor pieces or pawns, and queens by any of those or rooks
+    #[inline]
+    pub fn attacked_by_lower(&self, col: Colour) -> Bitboard {
+        self.threats().by_lower[col.index()]
+    }
+
+    /// Returns the pieces of the given colour, king excluded, that are attacked and not defended
+    #[inline]
+    pub fn hanging(&self, col: Colour) -> Bitboard {
+        self.threats().hanging[col.index()]
+    }
+
+    /// Returns the pieces of the given colour pinned to their king
+    #[inline]
+    pub fn pinned(&self, col: Colour) -> Bitboard {
+        self.threats().pinned[col.index()]
+    }
+
+    /// Returns the sliders of the given colour pinning an enemy piece to its king
+    #[inline]
+    pub fn pinners(&self, col: Colour) -> Bitboard {
+        self.threats().pinners[col.index()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{START_FEN, TRICKY_FEN};
+
+    fn bb(squares: &[Square]) -> Bitboard {
+        squares.iter().fold(Bitboard::EMPTY, |bb, sq| bb | sq.bb())
+    }
+
+    #[test]
+    fn test_attack_maps() {
+        let board = Board::from_fen(START_FEN).unwrap();
+
+        assert_eq!(
+            board.attacked_by(Colour::White),
+            Rank::Rank3.bb()
+                | Rank::Rank2.bb()
+                | Rank::Rank1.bb() ^ Square::A1.bb() ^ Square::H1.bb()
+        );
+        assert_eq!(
+            board.attacks_by(Colour::White, PieceType::Knight),
+            bb(&[
+                Square::A3,
+                Square::C3,
+                Square::D2,
+                Square::E2,
+                Square::F3,
+                Square::H3
+            ])
+        );
+        assert_eq!(
+            board.attacks_by(Colour::White, PieceType::Pawn),
+            Rank::Rank3.bb()
+        );
+
+        // The attacks of the side not to move match the mask used by move generation
+        let board = Board::from_fen(TRICKY_FEN).unwrap();
+        assert_eq!(board.attacked_by(Colour::Black), board.attacked());
+    }
+
+    #[test]
+    fn test_threats() {
+        // The knight on c6 is attacked by the d5 pawn and not defended,
+        // and the d5 pawn is attacked by the queen and not defended
+        let board = Board::from_fen("3q2k1/8/2n5/1B1P4/8/8/8/R3K3 w - - 0 1").unwrap();
+
+        assert_eq!(board.attacked_by_lower(Colour::Black), bb(&[Square::C6]));
+        assert_eq!(board.hanging(Colour::Black), bb(&[Square::C6]));
+        assert_eq!(board.attacked_by_lower(Colour::White), Bitboard::EMPTY);
+        assert_eq!(board.hanging(Colour::White), bb(&[Square::D5]));
+
+        let board = Board::from_fen("4k3/8/8/8/3q4/8/5B2/4K2R w - - 0 1").unwrap();
+        assert_eq!(board.attacked_by_lower(Colour::Black), bb(&[Square::D4]));
+        assert_eq!(board.hanging(Colour::Black), bb(&[Square::D4]));
+        assert_eq!(board.hanging(Colour::White), Bitboard::EMPTY);
+    }
+
+    #[test]
+    fn test_pins() {
+        let board = Board::from_fen("4k3/8/8/8/1b6/8/3N4/4K3 w - - 0 1").unwrap();
+
+        assert_eq!(board.pinned(Colour::White), bb(&[Square::D2]));
+        assert_eq!(board.pinners(Colour::Black), bb(&[Square::B4]));
+        assert_eq!(board.pinned(Colour::Black), Bitboard::EMPTY);
+        assert_eq!(board.pinners(Colour::White), Bitboard::EMPTY);
+
+        // Two pieces between the king and the slider are not pinned
+        let board = Board::from_fen("4k3/4r3/8/8/4P3/8/4N3/4K3 w - - 0 1").unwrap();
+        assert_eq!(board.pinned(Colour::White), Bitboard::EMPTY);
+        assert_eq!(board.pinners(Colour::Black), Bitboard::EMPTY);
+    }
+
+    #[test]
+    fn test_cache_is_cleared() {
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+        let before = board.threats().clone();
+
+        let move_ = Move::new(Square::A1, Square::A8, MoveFlag::QuietMove);
+        board.make_move(move_);
+        assert_ne!(board.threats(), &before);
+        assert!(board.attacked_by(Colour::White).contains(Square::E8));
+
+        board.undo_move(move_);
+        assert_eq!(board.threats(), &before);
+    }
+}
@@ -0,0 +1,197 @@
+//! Symmetry transforms of a position.
+//!
+//! A colour-flipped position must get the same evaluation and search result as the original,
+//! and a mirrored position the same move counts, which makes both useful for testing as well as
+//! for augmenting training data.
+use super::{Board, mask::CastlingMask};
+use crate::core::*;
+
+/******************************************\
+|==========================================|
+|                Transforms                |
+|==========================================|
+\******************************************/
+
+/// Swaps the white and black castling rights
+const fn swap_rights(rights: Castling) -> Castling {
+    Castling((rights.0 & Castling::WHITE_CASTLING.0) << 2 | rights.0 >> 2)
+}
+
+impl Board {
+    /// # Flipped Board
+    ///
+    /// Returns the position with the colours swapped and the board flipped vertically, so
+    /// a white piece on e2 becomes a black piece on e7 and the other side is to move.
+    /// Castling rights and the en passant square are flipped along, the move history is not kept.
+    pub fn flipped(&self) -> Board {
+        let mut board = Board::new();
+
+        for sq in Square::iter() {
+            if let Some(piece) = self.on(sq) {
+                board.add_piece(
+                    Piece::from_parts(!piece.colour(), piece.pt()),
+                    sq.flip_rank(),
+                );
+            }
+        }
+
+        let mut castling_mask = CastlingMask::default();
+        for sq in Square::iter() {
+            castling_mask.castling[sq.flip_rank().index()] =
+                swap_rights(self.castling_mask.castling[sq.index()]);
+        }
+        for (i, rook_sq) in self.castling_mask.rook_sq.into_iter().enumerate() {
+            // White and black rights are two indices apart
+            castling_mask.rook_sq[i ^ 2] = rook_sq.map(|sq| sq.flip_rank());
+        }
+
+        board.castling_mask = castling_mask;
+        board.state.castle = swap_rights(self.state.castle);
+        board.state.enpassant = self.state.enpassant.map(|sq| sq.flip_rank());
+
+        // Keep the fullmove number, the parity of the ply giving the side to move
+        board.half_moves = self.half_moves & !1 | !self.stm as u16;
+        board.stm = !self.stm;
+
+        self.finish_transform(board)
+    }
+
+    /// # Mirrored Board
+    ///
+    /// Returns the position flipped horizontally, so a piece on b1 goes to g1. Castling is not
+    /// symmetric along the files, so the mirrored position has no castling rights.
+    /// The move history is not kept.
+    pub fn mirrored(&self) -> Board {
+        let mut board = Board::new();
+
+        for sq in Square::iter() {
+            if let Some(piece) = self.on(sq) {
+                board.add_piece(piece, sq.flip_file());
+            }
+        }
+
+        board.state.castle = Castling::NONE;
+        board.state.enpassant = self.state.enpassant.map(|sq| sq.flip_file());
+        board.half_moves = self.half_moves;
+        board.stm = self.stm;
+
+        self.finish_transform(board)
+    }
+
+    /// Copies the untransformed fields to the transformed board, then computes its keys and masks
+    fn finish_transform(&self, mut board: Board) -> Board {
+        board.chess960 = self.chess960;
+        board.state.fifty_move = self.state.fifty_move;
+
+        board.state.keys.key = board.calc_key();
+        board.state.keys.pawn_key = board.calc_pawn_key();
+        board.state.keys.non_pawn_key = board.calc_non_pawn_key();
+
+        board.update_masks();
+
+        board
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{KILLER_FEN, LegalGen, MoveList, START_FEN, TRICKY_FEN};
+    use crate::utils::perft;
+
+    const FENS: [&str; 5] = [
+        START_FEN,
+        TRICKY_FEN,
+        KILLER_FEN,
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    ];
+
+    fn legal_moves(board: &Board) -> Vec<Move> {
+        let mut move_list = MoveList::new();
+        board.generate_moves::<LegalGen>(&mut move_list);
+
+        let mut moves: Vec<Move> = move_list.iter().copied().collect();
+        moves.sort_by_key(|move_| move_.raw());
+        moves
+    }
+
+    #[test]
+    fn test_flipped() {
+        let board = Board::from_fen(START_FEN).unwrap();
+        let flipped = board.flipped();
+
+        assert_eq!(
+            flipped.fen(),
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1"
+        );
+
+        let board = Board::from_fen(KILLER_FEN).unwrap();
+        assert_eq!(
+            board.flipped().fen(),
+            "rnbqkbnr/p1p1p3/3p3p/1p1p4/2P1Pp2/8/PP1P1PpP/RNBQKB1R b KQkq e3 0 1"
+        );
+
+        for fen in FENS {
+            let board = Board::from_fen(fen).unwrap();
+            let flipped = board.flipped();
+
+            assert_eq!(flipped.validate(), Ok(()), "{fen}");
+            assert_eq!(flipped.flipped(), board, "{fen}");
+
+            let mut moves: Vec<Move> = legal_moves(&board).into_iter().map(Move::flipped).collect();
+            moves.sort_by_key(|move_| move_.raw());
+            assert_eq!(legal_moves(&flipped), moves, "{fen}");
+
+            assert_eq!(
+                perft(&mut board.clone(), 3),
+                perft(&mut flipped.clone(), 3),
+                "{fen}"
+            );
+        }
+
+        // Chess960 castling rights follow their rooks
+        for (white, black) in [(0, 959), (518, 518), (123, 456)] {
+            let board = Board::from_dfrc(white, black).unwrap();
+            let flipped = board.flipped();
+
+            assert_eq!(flipped.flipped(), board);
+            assert_eq!(perft(&mut board.clone(), 3), perft(&mut flipped.clone(), 3));
+        }
+    }
+
+    #[test]
+    fn test_mirrored() {
+        let board = Board::from_fen(TRICKY_FEN).unwrap();
+        assert_eq!(
+            board.mirrored().fen(),
+            "r2k3r/1bpqpp1p/1pnp2nb/3NP3/3P2p1/p1Q2N2/PPPBBPPP/R2K3R w - - 0 1"
+        );
+
+        for fen in FENS {
+            let board = Board::from_fen(fen).unwrap();
+            let mirrored = board.mirrored();
+
+            assert_eq!(mirrored.validate(), Ok(()), "{fen}");
+
+            // Without castling rights, mirroring is its own inverse and mirrors the legal moves
+            if board.castling() == Castling::NONE {
+                assert_eq!(mirrored.mirrored().fen(), fen);
+                assert_eq!(mirrored.mirrored().key(), board.key());
+
+                let mut moves: Vec<Move> = legal_moves(&board)
+                    .into_iter()
+                    .map(Move::mirrored)
+                    .collect();
+                moves.sort_by_key(|move_| move_.raw());
+                assert_eq!(legal_moves(&mirrored), moves, "{fen}");
+
+                assert_eq!(
+                    perft(&mut board.clone(), 3),
+                    perft(&mut mirrored.clone(), 3),
+                    "{fen}"
+                );
+            }
+        }
+    }
+}
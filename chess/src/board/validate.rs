@@ -0,0 +1,324 @@
+//! Consistency checks of the incrementally updated board state.
+//!
+//! `make_move` and `undo_move` update the bitboards, Zobrist keys and masks incrementally.
+//! `Board::validate` recomputes all of them from the mailbox, so a bug in an update shows up
+//! on the position where it happens instead of as odd search behaviour later on.
+use thiserror::Error;
+
+use super::{Board, Key};
+use crate::core::*;
+
+/******************************************\
+|==========================================|
+|                Validation                |
+|==========================================|
+\******************************************/
+
+impl Board {
+    /// # Validate Board
+    ///
+    /// Recomputes the bitboards, castling rights, en passant square, Zobrist keys and
+    /// check and pin masks from the mailbox and compares them to the stored ones.
+    ///
+    /// ## Errors
+    /// Returns the first `ValidationError` found.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        self.validate_bitboards()?;
+        self.validate_castling()?;
+        self.validate_ep()?;
+        self.validate_keys()?;
+        self.validate_masks()
+    }
+
+    /// Panics with the first mismatch found by `validate` when the `debug_board` feature is enabled,
+    /// and does nothing otherwise
+    #[inline]
+    pub fn debug_validate(&self) {
+        #[cfg(feature = "debug_board")]
+        if let Err(err) = self.validate() {
+            panic!("Invalid board: {err}\n{self}");
+        }
+    }
+
+    /// Checks that the piece type and colour bitboards match the mailbox
+    fn validate_bitboards(&self) -> Result<(), ValidationError> {
+        let mut pieces = [Bitboard::EMPTY; PieceType::NUM];
+        let mut occupied = [Bitboard::EMPTY; Colour::NUM];
+
+        for sq in Square::iter() {
+            if let Some(piece) = self.on(sq) {
+                pieces[piece.pt().index()].set(sq);
+                occupied[piece.colour().index()].set(sq);
+            }
+        }
+
+        for pt in PieceType::iter() {
+            compare_bb(
+                format!("{pt:?} bitboard"),
+                self.pieces[pt.index()],
+                pieces[pt.index()],
+            )?;
+        }
+
+        for col in [Colour::White, Colour::Black] {
+            compare_bb(
+                format!("{col:?} bitboard"),
+                self.occupied[col.index()],
+                occupied[col.index()],
+            )?;
+
+            let kings = self.piece_bb(col, PieceType::King).count_bits();
+            if kings != 1 {
+                return Err(ValidationError::KingCount(col, kings));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the Zobrist keys match the ones computed from scratch
+    fn validate_keys(&self) -> Result<(), ValidationError> {
+        let keys = self.state.keys;
+        let non_pawn_key = self.calc_non_pawn_key();
+
+        compare_key("key", keys.key, self.calc_key())?;
+        compare_key("pawn key", keys.pawn_key, self.calc_pawn_key())?;
+        compare_key(
+            "white non pawn key",
+            keys.non_pawn_key[Colour::White.index()],
+            non_pawn_key[Colour::White.index()],
+        )?;
+        compare_key(
+            "black non pawn key",
+            keys.non_pawn_key[Colour::Black.index()],
+            non_pawn_key[Colour::Black.index()],
+        )
+    }
+
+    /// Checks that every castling right has its king and rook on their castling squares
+    fn validate_castling(&self) -> Result<(), ValidationError> {
+        let rights = [Castling::WK, Castling::WQ, Castling::BK, Castling::BQ];
+
+        for (index, right) in rights.into_iter().enumerate() {
+            if !self.state.castle.has(right) {
+                continue;
+            }
+
+            let col = [Colour::White, Colour::Black][index / 2];
+            let ksq = self.ksq(col);
+
+            if ksq.rank() != Rank::Rank1.relative(col) || self.castling_rights(ksq).has(right) {
+                return Err(ValidationError::Castling(right, format!("king on {ksq}")));
+            }
+
+            let Some(rook_sq) = self.castling_mask.rook_sq[index] else {
+                return Err(ValidationError::Castling(
+                    right,
+                    "no rook square".to_string(),
+                ));
+            };
+
+            if self.on(rook_sq) != Some(Piece::from_parts(col, PieceType::Rook))
+                || self.castling_rights(rook_sq).has(right)
+            {
+                return Err(ValidationError::Castling(
+                    right,
+                    format!("no rook on {rook_sq}"),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the en passant square is behind a pawn that just made a double push
+    fn validate_ep(&self) -> Result<(), ValidationError> {
+        let Some(ep) = self.ep() else {
+            return Ok(());
+        };
+
+        let them = !self.stm;
+        let pushed = self.ep_target().and_then(|sq| self.on(sq));
+        let origin = ep.add(self.stm.forward()).ok();
+
+        let valid = ep.rank() == Rank::Rank6.relative(self.stm)
+            && self.on(ep).is_none()
+            && pushed == Some(Piece::from_parts(them, PieceType::Pawn))
+            && origin.is_some_and(|sq| self.on(sq).is_none());
+
+        if !valid {
+            return Err(ValidationError::EnPassant(ep));
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the attack, check and pin masks match the ones computed from scratch
+    fn validate_masks(&self) -> Result<(), ValidationError> {
+        let mut fresh = self.clone();
+        fresh.state = self.state.snapshot();
+        fresh.update_masks();
+
+        compare_bb("attacked mask", self.state.attacked, fresh.state.attacked)?;
+        compare_bb("check mask", self.state.check_mask, fresh.state.check_mask)?;
+        compare_bb(
+            "diagonal pin mask",
+            self.state.diag_pin,
+            fresh.state.diag_pin,
+        )?;
+        compare_bb("orthogonal pin mask", self.state.hv_pin, fresh.state.hv_pin)?;
+
+        if self.state.ep_pin != fresh.state.ep_pin {
+            return Err(ValidationError::EpPin {
+                stored: self.state.ep_pin,
+                computed: fresh.state.ep_pin,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+fn compare_key(name: &'static str, stored: Key, computed: Key) -> Result<(), ValidationError> {
+    if stored != computed {
+        return Err(ValidationError::Key {
+            name,
+            stored,
+            computed,
+        });
+    }
+
+    Ok(())
+}
+
+fn compare_bb(
+    name: impl Into<String>,
+    stored: Bitboard,
+    computed: Bitboard,
+) -> Result<(), ValidationError> {
+    if stored != computed {
+        return Err(ValidationError::Bitboard {
+            name: name.into(),
+            stored,
+            computed,
+            diff: stored ^ computed,
+        });
+    }
+
+    Ok(())
+}
+
+/******************************************\
+|==========================================|
+|            Validation Errors             |
+|==========================================|
+\******************************************/
+
+/// Represents the first inconsistency found by `Board::validate`.
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum ValidationError {
+    #[error("{name} mismatch, stored: {stored:#018x}, computed: {computed:#018x}")]
+    Key {
+        name: &'static str,
+        stored: Key,
+        computed: Key,
+    },
+    #[error("{name} mismatch\nstored:{stored}\ncomputed:{computed}\ndifference:{diff}")]
+    Bitboard {
+        name: String,
+        stored: Bitboard,
+        computed: Bitboard,
+        diff: Bitboard,
+    },
+    #[error("Expected one {0:?} king, found {1}")]
+    KingCount(Colour, u32),
+    #[error("Invalid castling right {0:?}: {1}")]
+    Castling(Castling, String),
+    #[error("Invalid en passant square: {0}")]
+    EnPassant(Square),
+    #[error("En passant pin mismatch, stored: {stored}, computed: {computed}")]
+    EpPin { stored: bool, computed: bool },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{LegalGen, MoveList, START_FEN, TRICKY_FEN};
+
+    // Plays every line up to the depth, validating each position on the way down and back up
+    fn validate_tree(board: &mut Board, depth: usize) {
+        assert_eq!(board.validate(), Ok(()), "{board}");
+
+        if depth == 0 {
+            return;
+        }
+
+        let mut move_list = MoveList::new();
+        board.generate_moves::<LegalGen>(&mut move_list);
+
+        for move_ in move_list.iter() {
+            board.make_move(*move_);
+            validate_tree(board, depth - 1);
+            board.undo_move(*move_);
+
+            assert_eq!(board.validate(), Ok(()), "{board}");
+        }
+    }
+
+    #[test]
+    fn test_valid_boards() {
+        for fen in [
+            START_FEN,
+            TRICKY_FEN,
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        ] {
+            validate_tree(&mut Board::from_fen(fen).unwrap(), 2);
+        }
+
+        let mut board = Board::from_fen(TRICKY_FEN).unwrap();
+        board.make_null_move();
+        assert_eq!(board.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_invalid_boards() {
+        let mut board = Board::from_fen(START_FEN).unwrap();
+        board.state.keys.key ^= 1;
+        assert!(matches!(
+            board.validate(),
+            Err(ValidationError::Key { name: "key", .. })
+        ));
+
+        let mut board = Board::from_fen(START_FEN).unwrap();
+        board.pieces[PieceType::Knight.index()].clear(Square::B1);
+        assert!(matches!(
+            board.validate(),
+            Err(ValidationError::Bitboard { diff, .. }) if diff == Square::B1.bb()
+        ));
+
+        // A castling right without its rook
+        let mut board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K3 w Qkq - 0 1").unwrap();
+        board.state.castle.set(Castling::WK);
+        assert!(matches!(
+            board.validate(),
+            Err(ValidationError::Castling(Castling::WK, _))
+        ));
+
+        // An en passant square with no pawn in front of it
+        let mut board = Board::from_fen(START_FEN).unwrap();
+        board.state.enpassant = Some(Square::E6);
+        assert_eq!(
+            board.validate(),
+            Err(ValidationError::EnPassant(Square::E6))
+        );
+
+        let mut board = Board::from_fen("4k3/8/8/8/1b6/8/3N4/4K3 w - - 0 1").unwrap();
+        board.state.diag_pin = Bitboard::EMPTY;
+        assert!(matches!(
+            board.validate(),
+            Err(ValidationError::Bitboard { .. })
+        ));
+    }
+}
@@ -0,0 +1,314 @@
+use std::{collections::HashMap, path::Path};
+
+use crate::{board::Key, core::*};
+
+use super::{
+    BookEntry, BookError, PolyglotBook, encode_move,
+    pgn::{GameResult, PgnGame, split_games},
+};
+
+/******************************************\
+|==========================================|
+|              Builder Config              |
+|==========================================|
+\******************************************/
+
+/// Settings used when building a book from PGN games.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BookBuilderConfig {
+    /// Score added to a move when the side that played it won the game
+    pub win_score: u32,
+    /// Score added to a move when the game was drawn
+    pub draw_score: u32,
+    /// Score added to a move when the side that played it lost the game
+    pub loss_score: u32,
+    /// Only the first `max_ply` half moves of each game are added
+    pub max_ply: usize,
+    /// A move must be played in at least this many games to be kept
+    pub min_games: u32,
+    /// Only add moves played by this colour, or by both sides if `None`
+    pub colour: Option<Colour>,
+}
+
+impl Default for BookBuilderConfig {
+    fn default() -> Self {
+        Self {
+            win_score: 2,
+            draw_score: 1,
+            loss_score: 0,
+            max_ply: 40,
+            min_games: 3,
+            colour: None,
+        }
+    }
+}
+
+/******************************************\
+|==========================================|
+|               Book Builder               |
+|==========================================|
+\******************************************/
+
+/// Accumulated statistics for a (position, move) pair.
+#[derive(Debug, Clone, Copy, Default)]
+struct MoveStats {
+    score: u64,
+    games: u32,
+}
+
+/// Summary of the games read from a PGN collection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PgnSummary {
+    /// Games added to the book
+    pub games: usize,
+    /// Games skipped because of an unknown result or invalid movetext
+    pub skipped: usize,
+}
+
+/// Builds Polyglot books by accumulating scores per (Polyglot key, move) over many games.
+#[derive(Debug, Clone, Default)]
+pub struct BookBuilder {
+    config: BookBuilderConfig,
+    stats: HashMap<(Key, u16), MoveStats>,
+}
+
+impl BookBuilder {
+    /// Creates an empty builder with the given settings
+    pub fn new(config: BookBuilderConfig) -> Self {
+        Self {
+            config,
+            stats: HashMap::new(),
+        }
+    }
+
+    /// # Add Game
+    ///
+    /// Adds the moves of a game to the book. Games with an unknown result are ignored
+    /// and `false` is returned.
+    pub fn add_game(&mut self, game: &PgnGame) -> bool {
+        if game.result == GameResult::Unknown {
+            return false;
+        }
+
+        let mut board = game.start.clone();
+
+        for &move_ in game.moves.iter().take(self.config.max_ply) {
+            let us = board.stm();
+
+            if self.config.colour.is_none_or(|colour| colour == us) {
+                let score = match game.result.winner() {
+                    Some(winner) if winner == us => self.config.win_score,
+                    Some(_) => self.config.loss_score,
+                    None => self.config.draw_score,
+                };
+
+                let stats = self
+                    .stats
+                    .entry((board.polyglot_key(), encode_move(&board, move_)))
+                    .or_default();
+
+                stats.score += score as u64;
+                stats.games += 1;
+            }
+
+            board.make_move(move_);
+        }
+
+        true
+    }
+
+    /// # Add PGN
+    ///
+    /// Adds every game of a PGN collection, skipping games that fail to parse.
+    pub fn add_pgn(&mut self, pgn: &str) -> PgnSummary {
+        let mut summary = PgnSummary::default();
+
+        for text in split_games(pgn) {
+            match PgnGame::parse(text) {
+                Ok(game) if self.add_game(&game) => summary.games += 1,
+                _ => summary.skipped += 1,
+            }
+        }
+
+        summary
+    }
+
+    /// # Book Entries
+    ///
+    /// Returns the book entries sorted by key, with the best moves of each position first.
+    /// Moves played in fewer than `min_games` games, or with a zero score, are dropped.
+    /// Scores are scaled down proportionally if they do not fit the 16 bit Polyglot weights.
+    pub fn entries(&self) -> Vec<BookEntry> {
+        let kept = || {
+            self.stats
+                .iter()
+                .filter(|(_, stats)| stats.games >= self.config.min_games && stats.score > 0)
+        };
+
+        let max_score = kept().map(|(_, stats)| stats.score).max().unwrap_or(0);
+        let scale = |score: u64| {
+            if max_score > u16::MAX as u64 {
+                // Round up so kept moves never end up with a zero weight
+                (score * u16::MAX as u64).div_ceil(max_score) as u16
+            } else {
+                score as u16
+            }
+        };
+
+        let mut entries: Vec<BookEntry> = kept()
+            .map(|(&(key, move_), stats)| BookEntry {
+                key,
+                move_,
+                weight: scale(stats.score),
+                learn: 0,
+            })
+            .collect();
+
+        entries.sort_by(|a, b| {
+            a.key
+                .cmp(&b.key)
+                .then(b.weight.cmp(&a.weight))
+                .then(a.move_.cmp(&b.move_))
+        });
+
+        entries
+    }
+
+    /// Builds an in memory book from the accumulated games
+    pub fn build(&self) -> PolyglotBook {
+        PolyglotBook::from_entries(self.entries())
+    }
+
+    /// # Write Book
+    ///
+    /// Writes the book in the Polyglot `.bin` format.
+    ///
+    /// ## Errors
+    /// Returns `BookError::Io` if the file can not be written.
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<(), BookError> {
+        let bytes: Vec<u8> = self
+            .entries()
+            .iter()
+            .flat_map(|entry| entry.to_bytes())
+            .collect();
+
+        std::fs::write(path, bytes)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+
+    const PGN: &str = r#"[Result "1-0"]
+
+1. e4 e5 2. Nf3 1-0
+
+[Result "1-0"]
+
+1. e4 c5 2. Nf3 1-0
+
+[Result "1/2-1/2"]
+
+1. d4 d5 1/2-1/2
+
+[Result "0-1"]
+
+1. d4 Nf6 0-1
+
+[Result "*"]
+
+1. c4 *
+"#;
+
+    fn config() -> BookBuilderConfig {
+        BookBuilderConfig {
+            min_games: 1,
+            ..Default::default()
+        }
+    }
+
+    fn uci_moves(book: &PolyglotBook, board: &Board) -> Vec<(String, u16)> {
+        book.moves(board)
+            .into_iter()
+            .map(|(move_, weight)| (move_.to_str(board), weight))
+            .collect()
+    }
+
+    #[test]
+    fn test_scores_and_summary() {
+        let mut builder = BookBuilder::new(config());
+        let summary = builder.add_pgn(PGN);
+
+        assert_eq!(
+            summary,
+            PgnSummary {
+                games: 4,
+                skipped: 1
+            }
+        );
+
+        let book = builder.build();
+        let board = Board::default();
+
+        // e4: two wins, d4: one draw and one loss
+        assert_eq!(
+            uci_moves(&book, &board),
+            [("e2e4".to_string(), 4), ("d2d4".to_string(), 1)]
+        );
+    }
+
+    #[test]
+    fn test_filters() {
+        let mut builder = BookBuilder::new(BookBuilderConfig {
+            max_ply: 1,
+            colour: Some(Colour::Black),
+            ..config()
+        });
+        builder.add_pgn(PGN);
+        assert!(builder.entries().is_empty());
+
+        let mut builder = BookBuilder::new(BookBuilderConfig {
+            min_games: 2,
+            ..config()
+        });
+        builder.add_pgn(PGN);
+
+        // Only e4 and d4 were played in two games
+        let book = builder.build();
+        assert_eq!(book.len(), 2);
+        assert_eq!(
+            book.best_move(&Board::default())
+                .unwrap()
+                .to_str(&Board::default()),
+            "e2e4"
+        );
+    }
+
+    #[test]
+    fn test_round_trip_through_reader() {
+        let mut builder = BookBuilder::new(config());
+        builder.add_pgn(PGN);
+
+        let path = std::env::temp_dir().join("celeris_book_round_trip.bin");
+        builder.write(&path).unwrap();
+        let book = PolyglotBook::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(book.len(), builder.entries().len());
+
+        assert_eq!(book.entries(0), []);
+
+        for entry in builder.entries() {
+            assert!(book.entries(entry.key).contains(&entry));
+        }
+
+        let mut board = Board::default();
+        for san in ["e4", "e5"] {
+            board.make_move(board.parse_san(san).unwrap());
+        }
+        assert_eq!(uci_moves(&book, &board), [("g1f3".to_string(), 2)]);
+    }
+}
@@ -0,0 +1,34 @@
+//! Polyglot opening book support.
+//!
+//! This module provides:
+//! - Polyglot compatible Zobrist hashing (`Board::polyglot_key`), separate from the engine's own keys.
+//! - A reader for Polyglot `.bin` books, with move decoding and weighted or best move selection.
+//! - A builder creating Polyglot books from PGN game collections.
+pub mod builder;
+pub mod pgn;
+pub mod polyglot;
+pub mod zobrist;
+
+pub use builder::{BookBuilder, BookBuilderConfig, PgnSummary};
+pub use pgn::{GameResult, PgnGame};
+pub use polyglot::{BookEntry, PolyglotBook, decode_move, encode_move};
+pub use zobrist::POLYGLOT_RANDOM;
+
+use thiserror::Error;
+
+/******************************************\
+|==========================================|
+|               Book Errors                |
+|==========================================|
+\******************************************/
+
+/// Represents errors that can occur while reading or writing opening books.
+#[derive(Error, Debug)]
+pub enum BookError {
+    #[error("Failed to access book file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Book size of {0} bytes is not a multiple of the entry size")]
+    InvalidLength(usize),
+    #[error("Invalid PGN: {0}")]
+    Pgn(String),
+}
@@ -0,0 +1,293 @@
+use crate::{board::Board, core::*};
+
+use super::BookError;
+
+/******************************************\
+|==========================================|
+|               Game Result                |
+|==========================================|
+\******************************************/
+
+/// The result of a game as recorded in PGN.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameResult {
+    WhiteWin,
+    BlackWin,
+    Draw,
+    Unknown,
+}
+
+impl GameResult {
+    /// Parses a PGN result token (`1-0`, `0-1`, `1/2-1/2` or `*`).
+    fn from_token(token: &str) -> Option<Self> {
+        match token {
+            "1-0" => Some(Self::WhiteWin),
+            "0-1" => Some(Self::BlackWin),
+            "1/2-1/2" => Some(Self::Draw),
+            "*" => Some(Self::Unknown),
+            _ => None,
+        }
+    }
+
+    /// Returns the winner of the game, if any
+    pub fn winner(&self) -> Option<Colour> {
+        match self {
+            Self::WhiteWin => Some(Colour::White),
+            Self::BlackWin => Some(Colour::Black),
+            _ => None,
+        }
+    }
+}
+
+/******************************************\
+|==========================================|
+|                PGN Game                  |
+|==========================================|
+\******************************************/
+
+/// A game read from PGN, holding the start position and the mainline moves.
+#[derive(Debug, Clone)]
+pub struct PgnGame {
+    /// Start position, from the `FEN` tag or the standard start position
+    pub start: Board,
+    /// Mainline moves, variations and comments are skipped
+    pub moves: Vec<Move>,
+    /// Result from the movetext, or the `Result` tag if the movetext has none
+    pub result: GameResult,
+}
+
+impl PgnGame {
+    /// # Parse Game
+    ///
+    /// Parses a single PGN game (tag pairs followed by movetext).
+    ///
+    /// ## Errors
+    /// Returns `BookError::Pgn` if the `FEN` tag or any mainline move is invalid.
+    pub fn parse(text: &str) -> Result<Self, BookError> {
+        let mut fen = None;
+        let mut chess960 = false;
+        let mut result = GameResult::Unknown;
+        let mut movetext = String::new();
+
+        for line in text.lines() {
+            let line = line.trim();
+
+            match Self::parse_tag(line) {
+                Some(("FEN", value)) => fen = Some(value),
+                Some(("Variant", value)) => chess960 = value.contains("960"),
+                Some(("Result", value)) => {
+                    result = GameResult::from_token(value).unwrap_or(GameResult::Unknown)
+                }
+                Some(_) => {}
+                None => {
+                    movetext.push_str(line);
+                    movetext.push('\n');
+                }
+            }
+        }
+
+        let mut board = match fen {
+            Some(fen) => Board::from_fen(fen)
+                .map_err(|e| BookError::Pgn(format!("Invalid FEN tag '{fen}': {e}")))?,
+            None => Board::default(),
+        };
+        board.set_chess960(chess960);
+
+        let start = board.clone();
+        let mut moves = Vec::new();
+
+        for token in Self::mainline_tokens(&movetext) {
+            if let Some(token_result) = GameResult::from_token(&token) {
+                result = token_result;
+                break;
+            }
+
+            let move_ = board
+                .parse_san(&token)
+                .map_err(|e| BookError::Pgn(format!("Move {}: {e}", moves.len() + 1)))?;
+
+            board.make_move(move_);
+            moves.push(move_);
+        }
+
+        Ok(Self {
+            start,
+            moves,
+            result,
+        })
+    }
+
+    /// Returns the `(name, value)` of a tag pair line such as `[Result "1-0"]`.
+    fn parse_tag(line: &str) -> Option<(&str, &str)> {
+        let inner = line.strip_prefix('[')?.strip_suffix(']')?;
+        let (name, value) = inner.split_once(char::is_whitespace)?;
+        Some((name, value.trim().trim_matches('"')))
+    }
+
+    /// Splits movetext into SAN moves and result tokens, skipping move numbers,
+    /// comments, variations and numeric annotation glyphs.
+    fn mainline_tokens(movetext: &str) -> Vec<String> {
+        let mut tokens = Vec::new();
+        let mut current = String::new();
+        let mut chars = movetext.chars();
+        let mut variation_depth = 0;
+
+        let mut flush = |current: &mut String, depth: usize| {
+            // Strip move numbers like "12." or "12..." which may be attached to the move.
+            // Only digits followed by a dot are a move number, so results and "0-0" are kept whole
+            let after_digits = current.trim_start_matches(|c: char| c.is_ascii_digit());
+            let token = match after_digits.strip_prefix('.') {
+                Some(rest) => rest.trim_start_matches('.'),
+                None => current.as_str(),
+            };
+
+            if depth == 0 && !token.is_empty() && !token.starts_with('$') {
+                tokens.push(token.to_string());
+            }
+            current.clear();
+        };
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' => {
+                    flush(&mut current, variation_depth);
+                    chars.by_ref().find(|&c| c == '}');
+                }
+                ';' => {
+                    flush(&mut current, variation_depth);
+                    chars.by_ref().find(|&c| c == '\n');
+                }
+                '(' => {
+                    flush(&mut current, variation_depth);
+                    variation_depth += 1;
+                }
+                ')' => {
+                    flush(&mut current, variation_depth);
+                    variation_depth = variation_depth.saturating_sub(1);
+                }
+                c if c.is_whitespace() => flush(&mut current, variation_depth),
+                c => current.push(c),
+            }
+        }
+        flush(&mut current, variation_depth);
+
+        tokens
+    }
+}
+
+/******************************************\
+|==========================================|
+|               PGN Reader                 |
+|==========================================|
+\******************************************/
+
+/// # Split PGN Games
+///
+/// Splits a PGN collection into the text of each game. A new game starts at the
+/// first tag pair following movetext.
+pub fn split_games(pgn: &str) -> Vec<&str> {
+    let mut games = Vec::new();
+    let mut start = 0;
+    let mut offset = 0;
+    let mut in_movetext = false;
+
+    for line in pgn.split_inclusive('\n') {
+        let trimmed = line.trim();
+
+        if trimmed.starts_with('[') {
+            if in_movetext {
+                games.push(&pgn[start..offset]);
+                start = offset;
+                in_movetext = false;
+            }
+        } else if !trimmed.is_empty() {
+            in_movetext = true;
+        }
+
+        offset += line.len();
+    }
+
+    if !pgn[start..].trim().is_empty() {
+        games.push(&pgn[start..]);
+    }
+
+    games
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PGN: &str = r#"[Event "Test"]
+[White "A"]
+[Black "B"]
+[Result "1-0"]
+
+1. e4 e5 2. Nf3 {a comment} Nc6 (2... d6 3. d4) 3. Bb5 $1 a6 ; line comment
+4.Ba4 Nf6 5. O-O 1-0
+
+[Event "Test 2"]
+[Result "1/2-1/2"]
+[FEN "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1"]
+[SetUp "1"]
+
+1. e4 Kd7 2. e5 1/2-1/2
+"#;
+
+    #[test]
+    fn test_split_games() {
+        let games = split_games(PGN);
+        assert_eq!(games.len(), 2);
+        assert!(games[0].contains("O-O"));
+        assert!(games[1].contains("Test 2"));
+    }
+
+    #[test]
+    fn test_parse_game() {
+        let games = split_games(PGN);
+
+        let game = PgnGame::parse(games[0]).unwrap();
+        assert_eq!(game.result, GameResult::WhiteWin);
+        assert_eq!(game.moves.len(), 9);
+
+        let mut board = game.start.clone();
+        let uci: Vec<String> = game
+            .moves
+            .iter()
+            .map(|&move_| {
+                let s = move_.to_str(&board);
+                board.make_move(move_);
+                s
+            })
+            .collect();
+        assert_eq!(
+            uci,
+            [
+                "e2e4", "e7e5", "g1f3", "b8c6", "f1b5", "a7a6", "b5a4", "g8f6", "e1g1"
+            ]
+        );
+
+        let game = PgnGame::parse(games[1]).unwrap();
+        assert_eq!(game.result, GameResult::Draw);
+        assert_eq!(game.moves.len(), 3);
+        assert_eq!(game.start.fen(), "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1");
+    }
+
+    #[test]
+    fn test_zero_castling() {
+        let game = PgnGame::parse(
+            "1. e4 e5 2. Nf3 Nc6 3. Bc4 d6 4. 0-0 Be6 5. d3 Qd7 6. Nc3 0-0-0 7.Be3 1/2-1/2",
+        )
+        .unwrap();
+        assert_eq!(game.result, GameResult::Draw);
+        assert_eq!(game.moves.len(), 13);
+        assert_eq!(game.moves[6].flag(), MoveFlag::KingCastle);
+        assert_eq!(game.moves[11].flag(), MoveFlag::QueenCastle);
+    }
+
+    #[test]
+    fn test_illegal_move() {
+        let result = PgnGame::parse("1. e4 e4 1-0");
+        assert!(matches!(result, Err(BookError::Pgn(_))));
+    }
+}
@@ -0,0 +1,347 @@
+use std::path::Path;
+
+use crate::{
+    board::{Board, Key, LegalGen, MoveList},
+    core::*,
+    utils::PRNG,
+};
+
+use super::BookError;
+
+/******************************************\
+|==========================================|
+|                Book Entry                |
+|==========================================|
+\******************************************/
+
+/// A single entry of a Polyglot book, stored as 16 big endian bytes on disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct BookEntry {
+    /// Polyglot key of the position
+    pub key: Key,
+    /// Polyglot encoded move
+    pub move_: u16,
+    /// Weight of the move, higher is better
+    pub weight: u16,
+    /// Learning data, unused by the reader
+    pub learn: u32,
+}
+
+impl BookEntry {
+    /// Size of an entry in bytes
+    pub const SIZE: usize = 16;
+
+    /// Decodes an entry from its on disk representation
+    pub fn from_bytes(bytes: &[u8; Self::SIZE]) -> Self {
+        Self {
+            key: Key::from_be_bytes(bytes[0..8].try_into().unwrap()),
+            move_: u16::from_be_bytes(bytes[8..10].try_into().unwrap()),
+            weight: u16::from_be_bytes(bytes[10..12].try_into().unwrap()),
+            learn: u32::from_be_bytes(bytes[12..16].try_into().unwrap()),
+        }
+    }
+
+    /// Encodes the entry to its on disk representation
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0; Self::SIZE];
+        bytes[0..8].copy_from_slice(&self.key.to_be_bytes());
+        bytes[8..10].copy_from_slice(&self.move_.to_be_bytes());
+        bytes[10..12].copy_from_slice(&self.weight.to_be_bytes());
+        bytes[12..16].copy_from_slice(&self.learn.to_be_bytes());
+        bytes
+    }
+}
+
+/******************************************\
+|==========================================|
+|              Move Encoding               |
+|==========================================|
+\******************************************/
+
+/// # Encode Polyglot Move
+///
+/// Converts a move into the Polyglot move format:
+/// - bits 0-5: destination square
+/// - bits 6-11: origin square
+/// - bits 12-14: promotion piece (0 none, 1 knight, 2 bishop, 3 rook, 4 queen)
+///
+/// Castling moves are encoded as the king capturing its own rook (e.g. e1h1).
+pub fn encode_move(board: &Board, move_: Move) -> u16 {
+    let to = if move_.is_castle() {
+        let side = match move_.flag() {
+            MoveFlag::KingCastle => Castling::king_side(board.stm()),
+            _ => Castling::queen_side(board.stm()),
+        };
+        board.rook_sq(side)
+    } else {
+        move_.to()
+    };
+
+    let promo = if move_.is_promotion() {
+        // Safety: the move is a promotion, and knight to queen map to 1 to 4
+        unsafe { move_.promotion_pt() }.index() as u16
+    } else {
+        0
+    };
+
+    to.index() as u16 | (move_.from().index() as u16) << 6 | promo << 12
+}
+
+/// # Decode Polyglot Move
+///
+/// Finds the legal move in the current position matching a Polyglot encoded move.
+/// Returns `None` if the move is not legal, which can happen with corrupted books or hash collisions.
+pub fn decode_move(board: &Board, raw: u16) -> Option<Move> {
+    let mut move_list = MoveList::new();
+    board.generate_moves::<LegalGen>(&mut move_list);
+
+    move_list
+        .iter()
+        .find(|&&move_| encode_move(board, move_) == raw)
+        .copied()
+}
+
+/******************************************\
+|==========================================|
+|              Polyglot Book               |
+|==========================================|
+\******************************************/
+
+/// An in memory Polyglot opening book, with entries sorted by key.
+#[derive(Debug, Clone, Default)]
+pub struct PolyglotBook {
+    entries: Vec<BookEntry>,
+}
+
+impl PolyglotBook {
+    /// # Open Book
+    ///
+    /// Reads a Polyglot `.bin` book from disk.
+    ///
+    /// ## Errors
+    /// Returns `BookError` if the file can not be read or is not a valid book.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, BookError> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+
+    /// # Book From Bytes
+    ///
+    /// Parses a Polyglot book from its raw bytes.
+    ///
+    /// ## Errors
+    /// Returns `BookError::InvalidLength` if the length is not a multiple of the entry size.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BookError> {
+        if !bytes.len().is_multiple_of(BookEntry::SIZE) {
+            return Err(BookError::InvalidLength(bytes.len()));
+        }
+
+        let entries = bytes
+            .chunks_exact(BookEntry::SIZE)
+            .map(|chunk| BookEntry::from_bytes(chunk.try_into().unwrap()))
+            .collect();
+
+        Ok(Self::from_entries(entries))
+    }
+
+    /// Creates a book from a list of entries, sorting them by key if needed.
+    pub fn from_entries(mut entries: Vec<BookEntry>) -> Self {
+        // Books should already be sorted, but binary search relies on it
+        if !entries.is_sorted_by_key(|entry| entry.key) {
+            entries.sort_by_key(|entry| entry.key);
+        }
+
+        Self { entries }
+    }
+
+    /// Returns the number of entries in the book
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether the book has no entries
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns all entries stored for a Polyglot key, using binary search
+    pub fn entries(&self, key: Key) -> &[BookEntry] {
+        let start = self.entries.partition_point(|entry| entry.key < key);
+        let end = self.entries.partition_point(|entry| entry.key <= key);
+        &self.entries[start..end]
+    }
+
+    /// Returns all legal book moves in the position along with their weights
+    pub fn moves(&self, board: &Board) -> Vec<(Move, u16)> {
+        self.entries(board.polyglot_key())
+            .iter()
+            .filter_map(|entry| Some((decode_move(board, entry.move_)?, entry.weight)))
+            .collect()
+    }
+
+    /// # Best Book Move
+    ///
+    /// Returns the legal book move with the highest weight, if any.
+    pub fn best_move(&self, board: &Board) -> Option<Move> {
+        self.moves(board)
+            .into_iter()
+            .filter(|&(_, weight)| weight > 0)
+            .max_by_key(|&(_, weight)| weight)
+            .map(|(move_, _)| move_)
+    }
+
+    /// # Weighted Random Book Move
+    ///
+    /// Picks a legal book move at random, with probability proportional to its weight.
+    /// Moves with a weight of zero are never picked.
+    pub fn weighted_move(&self, board: &Board, rng: &mut PRNG) -> Option<Move> {
+        let moves = self.moves(board);
+        let total: u64 = moves.iter().map(|&(_, weight)| weight as u64).sum();
+
+        if total == 0 {
+            return None;
+        }
+
+        let mut pick = rng.random_u64() % total;
+
+        for (move_, weight) in moves {
+            if pick < weight as u64 {
+                return Some(move_);
+            }
+            pick -= weight as u64;
+        }
+
+        unreachable!()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Helper to serialise a list of entries into a book
+    fn book_bytes(entries: &[BookEntry]) -> Vec<u8> {
+        entries.iter().flat_map(|entry| entry.to_bytes()).collect()
+    }
+
+    fn find_move(board: &Board, move_str: &str) -> Move {
+        let mut move_list = MoveList::new();
+        board.generate_moves::<LegalGen>(&mut move_list);
+        *move_list
+            .iter()
+            .find(|m| m.to_str(board) == move_str)
+            .unwrap()
+    }
+
+    // e2e4 in Polyglot encoding
+    const E2E4: u16 = (12 << 6) | 28;
+    // d2d4 in Polyglot encoding
+    const D2D4: u16 = (11 << 6) | 27;
+
+    fn start_book() -> PolyglotBook {
+        let key = Board::default().polyglot_key();
+        let entries = [
+            BookEntry {
+                key: 1,
+                move_: E2E4,
+                weight: 100,
+                learn: 0,
+            },
+            BookEntry {
+                key,
+                move_: E2E4,
+                weight: 30,
+                learn: 0,
+            },
+            BookEntry {
+                key,
+                move_: D2D4,
+                weight: 10,
+                learn: 0,
+            },
+            BookEntry {
+                key: Key::MAX,
+                move_: D2D4,
+                weight: 100,
+                learn: 0,
+            },
+        ];
+        PolyglotBook::from_bytes(&book_bytes(&entries)).unwrap()
+    }
+
+    #[test]
+    fn test_entry_round_trip() {
+        let entry = BookEntry {
+            key: 0x463B96181691FC9C,
+            move_: E2E4,
+            weight: 1234,
+            learn: 0xDEADBEEF,
+        };
+        assert_eq!(BookEntry::from_bytes(&entry.to_bytes()), entry);
+    }
+
+    #[test]
+    fn test_invalid_length() {
+        assert!(matches!(
+            PolyglotBook::from_bytes(&[0; 17]),
+            Err(BookError::InvalidLength(17))
+        ));
+    }
+
+    #[test]
+    fn test_lookup_and_selection() {
+        let book = start_book();
+        let board = Board::default();
+
+        assert_eq!(book.len(), 4);
+        assert_eq!(book.entries(board.polyglot_key()).len(), 2);
+        assert_eq!(book.moves(&board).len(), 2);
+        assert_eq!(book.best_move(&board), Some(find_move(&board, "e2e4")));
+
+        let mut rng = PRNG::default();
+        let mut counts = [0; 2];
+        for _ in 0..1000 {
+            let move_ = book.weighted_move(&board, &mut rng).unwrap();
+            counts[(move_ == find_move(&board, "d2d4")) as usize] += 1;
+        }
+        assert!(counts[0] > counts[1] && counts[1] > 0, "{counts:?}");
+    }
+
+    #[test]
+    fn test_missing_position() {
+        let book = start_book();
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+
+        assert!(book.entries(board.polyglot_key()).is_empty());
+        assert_eq!(book.best_move(&board), None);
+    }
+
+    #[test]
+    fn test_castling_encoding() {
+        let board = Board::from_fen("r3k2r/pppppppp/8/8/8/8/PPPPPPPP/R3K2R w KQkq - 0 1").unwrap();
+
+        // e1h1 and e1a1 in Polyglot encoding
+        let king_side = (4 << 6) | 7;
+        let queen_side = 4 << 6;
+
+        assert_eq!(
+            decode_move(&board, king_side),
+            Some(find_move(&board, "e1g1"))
+        );
+        assert_eq!(
+            decode_move(&board, queen_side),
+            Some(find_move(&board, "e1c1"))
+        );
+        assert_eq!(encode_move(&board, find_move(&board, "e1g1")), king_side);
+    }
+
+    #[test]
+    fn test_promotion_encoding() {
+        let board = Board::from_fen("8/P3k3/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+
+        // a7a8q in Polyglot encoding
+        let raw = (4 << 12) | (48 << 6) | 56;
+
+        assert_eq!(decode_move(&board, raw), Some(find_move(&board, "a7a8q")));
+        assert_eq!(decode_move(&board, (48 << 6) | 56), None);
+    }
+}
@@ -128,6 +128,15 @@ impl Move {
         Self { data }
     }
 
+    /// Create new move from row data, returning `None` if the flag bits are not a `MoveFlag`
+    #[inline]
+    pub const fn from_raw(data: u16) -> Option<Self> {
+        match (data >> Self::FLAG_SHIFT) & Self::FLAG_MASK {
+            0b0110 | 0b0111 => None,
+            _ => Some(Self { data }),
+        }
+    }
+
     /// Create new move from information like from to and flag
     #[inline(always)]
     pub const fn new(from: Square, to: Square, flag: MoveFlag) -> Self {
@@ -203,6 +212,25 @@ impl Move {
         self != Self::NONE && self != Self::NULL
     }
 
+    /// Returns the move played on the colour-flipped board, with its squares flipped vertically
+    pub fn flipped(self) -> Self {
+        if !self.is_valid() {
+            return self;
+        }
+
+        Self::new(self.from().flip_rank(), self.to().flip_rank(), self.flag())
+    }
+
+    /// Returns the move played on the mirrored board, with its squares flipped horizontally.
+    /// Castling moves have no mirrored counterpart, as the mirrored board has no castling rights.
+    pub fn mirrored(self) -> Self {
+        if !self.is_valid() {
+            return self;
+        }
+
+        Self::new(self.from().flip_file(), self.to().flip_file(), self.flag())
+    }
+
     /// Returns the string representation of the move (UCI format)
     pub fn to_str(&self, board: &Board) -> String {
         if !self.is_valid() {
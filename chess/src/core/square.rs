@@ -137,6 +137,21 @@ impl Square {
         unsafe { Self::from_unchecked((*self as u8) ^ Square::A8 as u8) }
     }
 
+    /// Flips the file of a square along the middle of the board, mirroring the a-file and the h-file
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use chess::core::Square;
+    ///
+    /// assert_eq!(Square::A1.flip_file(), Square::H1);
+    /// assert_eq!(Square::E4.flip_file(), Square::D4);
+    /// assert_eq!(Square::H8.flip_file(), Square::A8);
+    /// ```
+    pub const fn flip_file(&self) -> Self {
+        unsafe { Self::from_unchecked((*self as u8) ^ Square::H1 as u8) }
+    }
+
     /// Returns the square relative to the perspectives of `col: Colour`
     ///
     /// For White, the square remains the same.
@@ -444,6 +459,13 @@ mod tests {
         assert_eq!(Square::H8.flip_rank(), Square::H1);
     }
 
+    #[test]
+    fn test_flip_file() {
+        assert_eq!(Square::A1.flip_file(), Square::H1);
+        assert_eq!(Square::E4.flip_file(), Square::D4);
+        assert_eq!(Square::H8.flip_file(), Square::A8);
+    }
+
     #[test]
     fn test_square_conversions() {
         for file in 0..8 {
@@ -0,0 +1,411 @@
+use crate::{
+    board::{attacks, pawn_attack},
+    core::*,
+};
+
+use super::{
+    Bitbase, Bitbases, Dtm, MAX_PIECES, Material,
+    material::Squares,
+    table::{DRAW, INVALID, LOSS},
+};
+
+/// Code of positions not resolved yet, only used during generation
+const UNKNOWN: u8 = 4;
+
+/// Pieces and side to move of a position reached by a capture or promotion
+type Exit = ([(Piece, Square); MAX_PIECES], usize, Colour);
+
+/******************************************\
+|==========================================|
+|                 Position                 |
+|==========================================|
+\******************************************/
+
+/// A position of the table being generated, the squares being in table order
+#[derive(Debug, Clone, Copy)]
+struct Position {
+    squares: Squares,
+    stm: Colour,
+}
+
+/// A legal move of a position, staying in the table or leaving it
+enum Child {
+    Table(Position),
+    Exit(Exit),
+}
+
+/******************************************\
+|==========================================|
+|                Generator                 |
+|==========================================|
+\******************************************/
+
+/// # Generate Bitbase
+///
+/// Solves every position of an ending by retrograde analysis. The tables of the
+/// endings reached by captures and promotions must already be in `bitbases`.
+///
+/// Pass `n` resolves the positions mated or mating in `n` plies: a position is won
+/// if a move reaches a position lost in `n - 1` plies, and lost once every move reaches
+/// a won position. Only the predecessors of the positions resolved by the previous pass
+/// are visited, found by undoing moves. The positions left at the end are draws.
+///
+/// En passant rights are not part of the index, so double pawn pushes are assumed
+/// not to allow an en passant capture during generation. Probing a `Board` with
+/// en passant rights does consider the capture.
+pub(super) fn generate(material: &Material, bitbases: &Bitbases) -> Bitbase {
+    Generator::new(material, bitbases).run()
+}
+
+struct Generator<'a> {
+    material: &'a Material,
+    pieces: &'a [Piece],
+    bitbases: &'a Bitbases,
+    /// WDL code of each entry, `UNKNOWN` until resolved
+    codes: Vec<u8>,
+    /// Distance to mate in plies of resolved wins and losses
+    dtm: Vec<u8>,
+    /// Best result reached through captures and promotions, `INVALID` when there are none
+    exit_codes: Vec<u8>,
+    exit_dtm: Vec<u8>,
+    /// Last pass each entry was evaluated in, to evaluate it once per pass
+    visited: Vec<u16>,
+}
+
+impl<'a> Generator<'a> {
+    fn new(material: &'a Material, bitbases: &'a Bitbases) -> Self {
+        let size = material.size();
+
+        Self {
+            material,
+            pieces: material.pieces(),
+            bitbases,
+            codes: vec![UNKNOWN; size],
+            dtm: vec![0; size],
+            exit_codes: vec![INVALID; size],
+            exit_dtm: vec![0; size],
+            visited: vec![0; size],
+        }
+    }
+
+    fn run(mut self) -> Bitbase {
+        // Positions whose best capture or promotion resolves them at a given pass
+        let mut scheduled: Vec<Vec<usize>> = vec![Vec::new(); u8::MAX as usize + 1];
+        let mut resolved = Vec::new();
+
+        // --- Initialisation ---
+        // Mark illegal positions, mates and stalemates, and probe the smaller tables
+        for index in 0..self.material.size() {
+            let (squares, stm) = self.material.decode(index);
+            let pos = Position { squares, stm };
+
+            if !self.is_valid(&pos) {
+                self.codes[index] = INVALID;
+                continue;
+            }
+
+            let mut has_moves = false;
+            let mut best_exit: Option<Dtm> = None;
+
+            self.for_each_child(&pos, |child| {
+                has_moves = true;
+
+                if let Child::Exit((pieces, len, stm)) = child {
+                    let result = self
+                        .bitbases
+                        .probe_pieces(&pieces[..len], stm)
+                        .expect("Bitbases of captures and promotions are generated first")
+                        .parent();
+
+                    best_exit = best_exit.max(Some(result));
+                }
+            });
+
+            if !has_moves {
+                if self.is_attacked(&pos, pos.squares[pos.stm.index()], !pos.stm) {
+                    self.codes[index] = LOSS;
+                    resolved.push(index);
+                } else {
+                    self.codes[index] = DRAW;
+                }
+                continue;
+            }
+
+            if let Some(exit) = best_exit {
+                (self.exit_codes[index], self.exit_dtm[index]) = exit.encode();
+
+                if let Dtm::Win(ply) | Dtm::Loss(ply) = exit {
+                    scheduled[ply as usize].push(index);
+                }
+            }
+        }
+
+        // --- Retrograde Passes ---
+        let mut pass = 1;
+
+        while !resolved.is_empty() || scheduled[pass..].iter().any(|s| !s.is_empty()) {
+            let mut candidates = std::mem::take(&mut scheduled[pass]);
+
+            for &index in &resolved {
+                let (squares, stm) = self.material.decode(index);
+
+                self.for_each_predecessor(&Position { squares, stm }, |pred| {
+                    candidates.push(self.material.index(&pred.squares, pred.stm));
+                    candidates.extend(self.material.diagonal_twin(&pred.squares, pred.stm));
+                });
+            }
+
+            resolved.clear();
+
+            for index in candidates {
+                if self.codes[index] != UNKNOWN || self.visited[index] == pass as u16 {
+                    continue;
+                }
+
+                self.visited[index] = pass as u16;
+
+                if let Some(result) = self.evaluate(index, pass as u8) {
+                    (self.codes[index], self.dtm[index]) = result.encode();
+                    resolved.push(index);
+                }
+            }
+
+            pass += 1;
+        }
+
+        // --- Draws ---
+        for code in self.codes.iter_mut().filter(|code| **code == UNKNOWN) {
+            *code = DRAW;
+        }
+
+        Bitbase::from_entries(self.material.clone(), &self.codes, self.dtm)
+    }
+
+    /// Returns the result of a position if it is mating or mated in `pass` plies
+    fn evaluate(&self, index: usize, pass: u8) -> Option<Dtm> {
+        let (squares, stm) = self.material.decode(index);
+        let pos = Position { squares, stm };
+
+        let mut best = Dtm::decode(self.exit_codes[index], self.exit_dtm[index]);
+        let mut unknown = false;
+
+        self.for_each_child(&pos, |child| {
+            if let Child::Table(child) = child {
+                let child_index = self.material.index(&child.squares, child.stm);
+
+                match self.codes[child_index] {
+                    UNKNOWN => unknown = true,
+                    code => {
+                        let result = Dtm::decode(code, self.dtm[child_index]).map(Dtm::parent);
+                        best = best.max(result);
+                    }
+                }
+            }
+        });
+
+        match best? {
+            result @ Dtm::Win(ply) if ply <= pass => Some(result),
+            result @ Dtm::Loss(ply) if ply <= pass && !unknown => Some(result),
+            _ => None,
+        }
+    }
+
+    /******************************************\
+    |==========================================|
+    |             Move Generation              |
+    |==========================================|
+    \******************************************/
+
+    /// Returns all occupied squares, and the squares occupied by each colour
+    fn occupancy(&self, pos: &Position) -> (Bitboard, [Bitboard; Colour::NUM]) {
+        let mut colours = [Bitboard::EMPTY; Colour::NUM];
+
+        for (piece, sq) in self.pieces.iter().zip(pos.squares) {
+            colours[piece.colour().index()].set(sq);
+        }
+
+        (colours[0] | colours[1], colours)
+    }
+
+    /// Returns whether `sq` is attacked by the pieces of `by`, ignoring a captured piece
+    fn is_attacked_with(
+        &self,
+        pos: &Position,
+        sq: Square,
+        by: Colour,
+        occ: Bitboard,
+        captured: Option<usize>,
+    ) -> bool {
+        self.pieces.iter().enumerate().any(|(i, piece)| {
+            piece.colour() == by
+                && Some(i) != captured
+                && attacks(by, piece.pt(), pos.squares[i], occ).contains(sq)
+        })
+    }
+
+    fn is_attacked(&self, pos: &Position, sq: Square, by: Colour) -> bool {
+        self.is_attacked_with(pos, sq, by, self.occupancy(pos).0, None)
+    }
+
+    /// A position is valid if the pieces are on distinct squares, no pawn is on
+    /// the first or last rank, and the side not to move is not in check
+    fn is_valid(&self, pos: &Position) -> bool {
+        let (occ, _) = self.occupancy(pos);
+        let back_ranks = Rank::Rank1.bb() | Rank::Rank8.bb();
+
+        occ.count_bits() as usize == self.pieces.len()
+            && self
+                .pieces
+                .iter()
+                .zip(pos.squares)
+                .all(|(piece, sq)| piece.pt() != PieceType::Pawn || !back_ranks.contains(sq))
+            && !self.is_attacked(pos, pos.squares[(!pos.stm).index()], pos.stm)
+    }
+
+    /// Calls `f` with every legal move of the position
+    fn for_each_child(&self, pos: &Position, mut f: impl FnMut(Child)) {
+        let us = pos.stm;
+        let (occ, colours) = self.occupancy(pos);
+
+        for (i, piece) in self.pieces.iter().enumerate() {
+            if piece.colour() != us {
+                continue;
+            }
+
+            let from = pos.squares[i];
+            let mut targets = if piece.pt() == PieceType::Pawn {
+                let mut targets = pawn_attack(us, from) & colours[(!us).index()];
+                let push = unsafe { from.add_unchecked(us.forward()) };
+
+                if !occ.contains(push) {
+                    targets.set(push);
+
+                    if from.relative(us).rank() == Rank::Rank2 {
+                        let double = unsafe { push.add_unchecked(us.forward()) };
+                        if !occ.contains(double) {
+                            targets.set(double);
+                        }
+                    }
+                }
+
+                targets
+            } else {
+                attacks(us, piece.pt(), from, occ) & !colours[us.index()]
+            };
+
+            while let Some(to) = targets.pop_lsb() {
+                let captured = pos
+                    .squares
+                    .iter()
+                    .take(self.pieces.len())
+                    .position(|&sq| sq == to);
+
+                let mut child = *pos;
+                child.squares[i] = to;
+                child.stm = !us;
+
+                let child_occ = (occ ^ from.bb()) | to.bb();
+                if self.is_attacked_with(
+                    &child,
+                    child.squares[us.index()],
+                    !us,
+                    child_occ,
+                    captured,
+                ) {
+                    continue;
+                }
+
+                let promotion =
+                    piece.pt() == PieceType::Pawn && to.relative(us).rank() == Rank::Rank8;
+
+                if captured.is_none() && !promotion {
+                    f(Child::Table(child));
+                    continue;
+                }
+
+                let promotions: &[PieceType] = if promotion {
+                    &[
+                        PieceType::Queen,
+                        PieceType::Rook,
+                        PieceType::Bishop,
+                        PieceType::Knight,
+                    ]
+                } else {
+                    &[piece.pt()]
+                };
+
+                for &pt in promotions {
+                    let mut pieces = [(Piece::WhiteKing, Square::A1); MAX_PIECES];
+                    let mut len = 0;
+
+                    for (j, (&pc, &sq)) in self.pieces.iter().zip(&child.squares).enumerate() {
+                        if Some(j) == captured {
+                            continue;
+                        }
+
+                        pieces[len] = if j == i {
+                            (Piece::from_parts(us, pt), sq)
+                        } else {
+                            (pc, sq)
+                        };
+                        len += 1;
+                    }
+
+                    f(Child::Exit((pieces, len, child.stm)));
+                }
+            }
+        }
+    }
+
+    /// Calls `f` with every valid position that reaches this one by a move which
+    /// is neither a capture nor a promotion
+    fn for_each_predecessor(&self, pos: &Position, mut f: impl FnMut(Position)) {
+        let them = !pos.stm;
+        let (occ, _) = self.occupancy(pos);
+
+        for (i, piece) in self.pieces.iter().enumerate() {
+            if piece.colour() != them {
+                continue;
+            }
+
+            let to = pos.squares[i];
+            let mut origins = if piece.pt() == PieceType::Pawn {
+                let mut origins = Bitboard::EMPTY;
+                let rank = to.relative(them).rank();
+                let back = unsafe { to.add_unchecked(-them.forward()) };
+
+                if rank >= Rank::Rank3 && !occ.contains(back) {
+                    origins.set(back);
+
+                    if rank == Rank::Rank4 {
+                        let double = unsafe { back.add_unchecked(-them.forward()) };
+                        if !occ.contains(double) {
+                            origins.set(double);
+                        }
+                    }
+                }
+
+                origins
+            } else {
+                attacks(them, piece.pt(), to, occ) & !occ
+            };
+
+            while let Some(from) = origins.pop_lsb() {
+                let mut pred = *pos;
+                pred.squares[i] = from;
+                pred.stm = them;
+
+                let pred_occ = (occ ^ to.bb()) | from.bb();
+                if !self.is_attacked_with(
+                    &pred,
+                    pred.squares[pos.stm.index()],
+                    them,
+                    pred_occ,
+                    None,
+                ) {
+                    f(pred);
+                }
+            }
+        }
+    }
+}
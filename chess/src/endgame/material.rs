@@ -0,0 +1,450 @@
+use crate::core::*;
+
+use super::EndgameError;
+
+/******************************************\
+|==========================================|
+|                Constants                 |
+|==========================================|
+\******************************************/
+
+/// Maximum number of pieces, kings included, the generator supports
+pub const MAX_PIECES: usize = 4;
+
+/// Non-king piece types in the order they appear in table names, most valuable first
+const NAME_ORDER: [PieceType; 5] = [
+    PieceType::Queen,
+    PieceType::Rook,
+    PieceType::Bishop,
+    PieceType::Knight,
+    PieceType::Pawn,
+];
+
+/// Squares of the a1-d1-d4 triangle the white king is mapped to in pawnless tables
+#[rustfmt::skip]
+const TRIANGLE: [Square; 10] = [
+    Square::A1, Square::B1, Square::C1, Square::D1,
+    Square::B2, Square::C2, Square::D2,
+    Square::C3, Square::D3,
+    Square::D4,
+];
+
+/// Index of the white king in pawnless tables, for squares of the triangle
+const TRIANGLE_INDEX: [usize; Square::NUM] = {
+    let mut table = [0; Square::NUM];
+    let mut i = 0;
+    while i < TRIANGLE.len() {
+        table[TRIANGLE[i] as usize] = i;
+        i += 1;
+    }
+    table
+};
+
+/// Squares of the board, one per piece of a position in table order
+pub(super) type Squares = [Square; MAX_PIECES];
+
+/******************************************\
+|==========================================|
+|                 Material                 |
+|==========================================|
+\******************************************/
+
+/// The pieces of an ending, such as `KRKP`.
+///
+/// Tables store the first side of the name as white. Pieces are kept in table order:
+/// the white king, the black king, then the other white and black pieces, most valuable first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Material {
+    pieces: Vec<Piece>,
+}
+
+impl Material {
+    /// Creates the material of the given non-king pieces of each side
+    pub fn new(white: &[PieceType], black: &[PieceType]) -> Self {
+        let mut pieces = vec![Piece::WhiteKing, Piece::BlackKing];
+
+        for (colour, side) in [(Colour::White, white), (Colour::Black, black)] {
+            let mut side = side.to_vec();
+            side.sort_by_key(|&pt| name_rank(pt));
+            pieces.extend(side.iter().map(|&pt| Piece::from_parts(colour, pt)));
+        }
+
+        Self { pieces }
+    }
+
+    /// # Parse Material
+    ///
+    /// Parses an ending name such as `KPK` or `KRKP`, the first side being white.
+    ///
+    /// ## Errors
+    /// Returns `EndgameError::InvalidMaterial` if the name is malformed, contains kings
+    /// outside of the two side prefixes, or has more than `MAX_PIECES` pieces.
+    pub fn parse(name: &str) -> Result<Self, EndgameError> {
+        let invalid = || EndgameError::InvalidMaterial(name.to_string());
+
+        let rest = name.strip_prefix('K').ok_or_else(invalid)?;
+        let (white, black) = rest.split_once('K').ok_or_else(invalid)?;
+
+        let parse_side = |side: &str| {
+            side.chars()
+                .map(|c| match c {
+                    'Q' => Ok(PieceType::Queen),
+                    'R' => Ok(PieceType::Rook),
+                    'B' => Ok(PieceType::Bishop),
+                    'N' => Ok(PieceType::Knight),
+                    'P' => Ok(PieceType::Pawn),
+                    _ => Err(invalid()),
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        let material = Self::new(&parse_side(white)?, &parse_side(black)?);
+
+        if material.pieces.len() > MAX_PIECES {
+            return Err(invalid());
+        }
+
+        Ok(material)
+    }
+
+    /// Returns the name of the ending, such as `KRKP`
+    pub fn name(&self) -> String {
+        let mut name = String::from("K");
+
+        for colour in [Colour::White, Colour::Black] {
+            if colour == Colour::Black {
+                name.push('K');
+            }
+
+            for pt in self.side(colour) {
+                name.push_str(&pt.to_string().to_uppercase());
+            }
+        }
+
+        name
+    }
+
+    /// Returns the pieces in table order
+    pub fn pieces(&self) -> &[Piece] {
+        &self.pieces
+    }
+
+    /// Returns the non-king pieces of a side, most valuable first
+    pub fn side(&self, colour: Colour) -> Vec<PieceType> {
+        self.pieces[2..]
+            .iter()
+            .filter(|pc| pc.colour() == colour)
+            .map(|pc| pc.pt())
+            .collect()
+    }
+
+    /// Returns whether the ending has pawns
+    pub fn has_pawns(&self) -> bool {
+        self.pieces.iter().any(|pc| pc.pt() == PieceType::Pawn)
+    }
+
+    /// Returns the material with the colours swapped
+    pub fn flipped(&self) -> Self {
+        Self::new(&self.side(Colour::Black), &self.side(Colour::White))
+    }
+
+    /// Returns whether white is the stronger side, the orientation tables are stored in.
+    ///
+    /// The side with more pieces is stronger, then the side with the more valuable pieces.
+    pub fn is_canonical(&self) -> bool {
+        let ranks = |colour: Colour| -> Vec<usize> {
+            self.side(colour).into_iter().map(name_rank).collect()
+        };
+
+        let (white, black) = (ranks(Colour::White), ranks(Colour::Black));
+
+        white.len() > black.len() || (white.len() == black.len() && white <= black)
+    }
+
+    /// Returns the material in the orientation tables are stored in
+    pub fn canonical(&self) -> Self {
+        if self.is_canonical() {
+            self.clone()
+        } else {
+            self.flipped()
+        }
+    }
+
+    /// Returns a key identifying the material, independent of the order of the pieces
+    pub fn key(&self) -> u64 {
+        material_key(self.pieces.iter().copied())
+    }
+
+    /// Returns the number of entries of a table of this material
+    pub fn size(&self) -> usize {
+        Colour::NUM * self.king_squares() * Square::NUM.pow(self.pieces.len() as u32 - 1)
+    }
+
+    /// Returns the canonical materials reached by a capture or a promotion
+    pub fn successors(&self) -> Vec<Material> {
+        let mut successors: Vec<Material> = Vec::new();
+
+        for i in 2..self.pieces.len() {
+            let mut captured = self.pieces.clone();
+            captured.remove(i);
+            successors.push(Self { pieces: captured }.normalised());
+
+            if self.pieces[i].pt() == PieceType::Pawn {
+                for pt in NAME_ORDER[..4].iter() {
+                    let mut promoted = self.pieces.clone();
+                    promoted[i] = Piece::from_parts(self.pieces[i].colour(), *pt);
+                    successors.push(Self { pieces: promoted }.normalised());
+                }
+            }
+        }
+
+        successors.sort_by_key(|m| m.name());
+        successors.dedup();
+        successors
+    }
+
+    /// Restores the table order of the pieces, and orients the material like the tables
+    fn normalised(&self) -> Self {
+        Self::new(&self.side(Colour::White), &self.side(Colour::Black)).canonical()
+    }
+
+    /******************************************\
+    |==========================================|
+    |                 Indexing                 |
+    |==========================================|
+    \******************************************/
+
+    /// Number of squares the white king is indexed on
+    fn king_squares(&self) -> usize {
+        if self.has_pawns() { 32 } else { TRIANGLE.len() }
+    }
+
+    /// Returns the symmetry mapping the white king on `king` to its indexed squares:
+    /// files a to d with pawns, and the a1-d1-d4 triangle without.
+    fn symmetry(&self, king: Square) -> Symmetry {
+        let mut mirror = 0;
+
+        if king.file() > File::FileD {
+            mirror ^= 7;
+        }
+
+        if self.has_pawns() {
+            return Symmetry {
+                mirror,
+                transpose: false,
+            };
+        }
+
+        if king.rank() > Rank::Rank4 {
+            mirror ^= 56;
+        }
+
+        let king = king as u8 ^ mirror;
+
+        Symmetry {
+            mirror,
+            transpose: (king >> 3) > (king & 7),
+        }
+    }
+
+    /// # Table Index
+    ///
+    /// Returns the index of the position, the squares being in table order.
+    /// Symmetric positions share their index, except for pawnless positions
+    /// with the white king on the a1-h8 diagonal which are indexed twice.
+    pub(super) fn index(&self, squares: &[Square], stm: Colour) -> usize {
+        let symmetry = self.symmetry(squares[0]);
+        let king = symmetry.apply(squares[0]);
+
+        let king_index = if self.has_pawns() {
+            king.rank().index() * 4 + king.file().index()
+        } else {
+            TRIANGLE_INDEX[king.index()]
+        };
+
+        squares[1..self.pieces.len()].iter().fold(
+            stm.index() * self.king_squares() + king_index,
+            |index, &sq| index * Square::NUM + symmetry.apply(sq).index(),
+        )
+    }
+
+    /// Returns the index of the other representation of a pawnless position with
+    /// the white king on the a1-h8 diagonal, mirrored along that diagonal.
+    pub(super) fn diagonal_twin(&self, squares: &[Square], stm: Colour) -> Option<usize> {
+        if self.has_pawns() {
+            return None;
+        }
+
+        let symmetry = self.symmetry(squares[0]);
+        let king = symmetry.apply(squares[0]) as u8;
+
+        if king >> 3 != king & 7 {
+            return None;
+        }
+
+        let mut twin = [Square::A1; MAX_PIECES];
+        let transpose = Symmetry {
+            mirror: 0,
+            transpose: true,
+        };
+
+        for (i, &sq) in squares[..self.pieces.len()].iter().enumerate() {
+            twin[i] = transpose.apply(symmetry.apply(sq));
+        }
+
+        Some(self.index(&twin, stm))
+    }
+
+    /// Returns the squares and side to move of an index, the inverse of `index`
+    pub(super) fn decode(&self, mut index: usize) -> (Squares, Colour) {
+        let mut squares = [Square::A1; MAX_PIECES];
+
+        for i in (1..self.pieces.len()).rev() {
+            squares[i] = unsafe { Square::from_unchecked((index % Square::NUM) as u8) };
+            index /= Square::NUM;
+        }
+
+        let king_index = index % self.king_squares();
+        squares[0] = if self.has_pawns() {
+            unsafe { Square::from_unchecked(((king_index / 4) * 8 + king_index % 4) as u8) }
+        } else {
+            TRIANGLE[king_index]
+        };
+
+        let stm = unsafe { Colour::from_unchecked((index / self.king_squares()) as u8) };
+
+        (squares, stm)
+    }
+}
+
+/******************************************\
+|==========================================|
+|                 Helpers                  |
+|==========================================|
+\******************************************/
+
+/// A reflection of the board, applied to every square of a position
+#[derive(Debug, Clone, Copy)]
+struct Symmetry {
+    /// Xored with the square index: 7 mirrors the files, 56 the ranks
+    mirror: u8,
+    /// Whether to mirror along the a1-h8 diagonal after the other reflections
+    transpose: bool,
+}
+
+impl Symmetry {
+    fn apply(self, sq: Square) -> Square {
+        let sq = sq as u8 ^ self.mirror;
+        let sq = if self.transpose {
+            (sq & 7) << 3 | sq >> 3
+        } else {
+            sq
+        };
+
+        unsafe { Square::from_unchecked(sq) }
+    }
+}
+
+/// Position of a piece type in table names, lower is more valuable
+fn name_rank(pt: PieceType) -> usize {
+    NAME_ORDER.iter().position(|&p| p == pt).unwrap_or(0)
+}
+
+/// Returns a key identifying a set of pieces, 4 bits per piece
+pub(super) fn material_key(pieces: impl Iterator<Item = Piece>) -> u64 {
+    pieces.fold(0, |key, pc| key + (1 << (4 * pc.index())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_material() {
+        let material = Material::parse("KRKP").unwrap();
+        assert_eq!(material.name(), "KRKP");
+        assert_eq!(
+            material.pieces(),
+            &[
+                Piece::WhiteKing,
+                Piece::BlackKing,
+                Piece::WhiteRook,
+                Piece::BlackPawn
+            ]
+        );
+        assert!(material.is_canonical());
+        assert!(material.has_pawns());
+
+        let material = Material::parse("KPKR").unwrap();
+        assert!(!material.is_canonical());
+        assert_eq!(material.canonical().name(), "KRKP");
+        assert_eq!(material.key(), Material::parse("KPKR").unwrap().key());
+        assert_ne!(material.key(), material.flipped().key());
+
+        assert_eq!(Material::parse("KPRK").unwrap().name(), "KRPK");
+
+        for name in ["", "KP", "PKK", "KXK", "KQRKP", "KRKKP"] {
+            assert!(Material::parse(name).is_err(), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_successors() {
+        let names = |name: &str| -> Vec<String> {
+            Material::parse(name)
+                .unwrap()
+                .successors()
+                .iter()
+                .map(Material::name)
+                .collect()
+        };
+
+        assert_eq!(names("KK"), Vec::<String>::new());
+        assert_eq!(names("KQK"), ["KK"]);
+        assert_eq!(names("KPK"), ["KBK", "KK", "KNK", "KQK", "KRK"]);
+        assert_eq!(
+            names("KRKP"),
+            ["KPK", "KQKR", "KRK", "KRKB", "KRKN", "KRKR"]
+        );
+    }
+
+    #[test]
+    fn test_index_round_trip() {
+        for name in ["KQK", "KPK", "KRKP"] {
+            let material = Material::parse(name).unwrap();
+
+            for index in (0..material.size()).step_by(997) {
+                let (squares, stm) = material.decode(index);
+                assert_eq!(material.index(&squares, stm), index, "{name} {index}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_index_symmetry() {
+        use Square::*;
+
+        let material = Material::parse("KRK").unwrap();
+        let index = material.index(&[B3, E5, H1, A1], Colour::White);
+
+        // Mirrored files, ranks and along the diagonal
+        assert_eq!(material.index(&[G3, D5, A1, A1], Colour::White), index);
+        assert_eq!(material.index(&[B6, E4, H8, A1], Colour::White), index);
+        assert_eq!(material.index(&[C2, E5, A8, A1], Colour::White), index);
+        assert_ne!(material.index(&[B3, E5, H1, A1], Colour::Black), index);
+
+        let material = Material::parse("KPK").unwrap();
+        let index = material.index(&[B3, E5, C2, A1], Colour::White);
+        assert_eq!(material.index(&[G3, D5, F2, A1], Colour::White), index);
+        assert_ne!(material.index(&[B6, E4, C7, A1], Colour::White), index);
+
+        let material = Material::parse("KQK").unwrap();
+        let squares = [C3, E5, H1, A1];
+        let twin = material.diagonal_twin(&squares, Colour::White).unwrap();
+        assert_eq!(twin, material.index(&[C3, E5, A8, A1], Colour::White));
+        assert_eq!(
+            material.diagonal_twin(&[B3, E5, H1, A1], Colour::White),
+            None
+        );
+    }
+}
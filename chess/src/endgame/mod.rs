@@ -0,0 +1,454 @@
+//! Endgame bitbases generated by retrograde analysis.
+//!
+//! This module provides:
+//! - A generator for endings of up to 4 pieces (KPK, KRK, KQK, KRKP, ...), which first
+//!   generates the endings reached by captures and promotions.
+//! - Compact WDL (2 bits per position) and DTM (1 byte per position) bitbases on disk.
+//! - Probing of any `Board` whose material is covered, with exact results and distances to mate.
+mod generator;
+mod material;
+mod table;
+
+pub use material::{MAX_PIECES, Material};
+pub use table::{Bitbase, DTM_EXTENSION, WDL_EXTENSION};
+
+use std::{collections::HashMap, path::Path};
+
+use thiserror::Error;
+
+use crate::{
+    board::{Board, LegalGen, MoveList},
+    core::*,
+};
+use material::material_key;
+
+/******************************************\
+|==========================================|
+|                 Results                  |
+|==========================================|
+\******************************************/
+
+/// Result of a position with perfect play, from the side to move's point of view.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Wdl {
+    Loss,
+    Draw,
+    Win,
+}
+
+/// Result of a position with the distance to mate in plies, from the side to move's
+/// point of view. `Loss(0)` is a checkmate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dtm {
+    Loss(u8),
+    Draw,
+    Win(u8),
+}
+
+impl Dtm {
+    /// Returns the result without the distance to mate
+    pub fn wdl(self) -> Wdl {
+        match self {
+            Self::Loss(_) => Wdl::Loss,
+            Self::Draw => Wdl::Draw,
+            Self::Win(_) => Wdl::Win,
+        }
+    }
+
+    /// Returns the result of the side moving into a position with this result
+    pub fn parent(self) -> Self {
+        let next = |ply: u8| ply.checked_add(1).expect("Distance to mate overflow");
+
+        match self {
+            Self::Loss(ply) => Self::Win(next(ply)),
+            Self::Draw => Self::Draw,
+            Self::Win(ply) => Self::Loss(next(ply)),
+        }
+    }
+
+    /// Score ordering results from worst to best: slow losses are better than fast ones
+    fn score(self) -> i32 {
+        match self {
+            Self::Loss(ply) => ply as i32 - 1000,
+            Self::Draw => 0,
+            Self::Win(ply) => 1000 - ply as i32,
+        }
+    }
+
+    /// Returns the WDL code and distance to mate stored in the tables
+    fn encode(self) -> (u8, u8) {
+        match self {
+            Self::Loss(ply) => (table::LOSS, ply),
+            Self::Draw => (table::DRAW, 0),
+            Self::Win(ply) => (table::WIN, ply),
+        }
+    }
+
+    fn decode(code: u8, ply: u8) -> Option<Self> {
+        match code {
+            table::LOSS => Some(Self::Loss(ply)),
+            table::DRAW => Some(Self::Draw),
+            table::WIN => Some(Self::Win(ply)),
+            _ => None,
+        }
+    }
+}
+
+impl PartialOrd for Dtm {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Dtm {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.score().cmp(&other.score())
+    }
+}
+
+/******************************************\
+|==========================================|
+|                 Bitbases                 |
+|==========================================|
+\******************************************/
+
+/// A collection of endgame bitbases, keyed by material.
+#[derive(Debug, Default)]
+pub struct Bitbases {
+    tables: HashMap<u64, Bitbase>,
+}
+
+impl Bitbases {
+    /// Creates an empty collection
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of bitbases
+    pub fn len(&self) -> usize {
+        self.tables.len()
+    }
+
+    /// Returns whether there are no bitbases
+    pub fn is_empty(&self) -> bool {
+        self.tables.is_empty()
+    }
+
+    /// Returns the bitbase of an ending, in either colour orientation
+    pub fn get(&self, material: &Material) -> Option<&Bitbase> {
+        self.tables.get(&material.canonical().key())
+    }
+
+    /// Returns the bitbases, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = &Bitbase> {
+        self.tables.values()
+    }
+
+    /// # Generate Bitbase
+    ///
+    /// Generates the bitbase of an ending such as `KRKP`, along with any missing
+    /// bitbase of the endings reached by captures and promotions.
+    ///
+    /// ## Errors
+    /// Returns `EndgameError::InvalidMaterial` if the name is not a valid ending.
+    pub fn generate(&mut self, name: &str) -> Result<&Bitbase, EndgameError> {
+        let material = Material::parse(name)?.canonical();
+        self.generate_material(&material);
+
+        Ok(&self.tables[&material.key()])
+    }
+
+    fn generate_material(&mut self, material: &Material) {
+        if self.tables.contains_key(&material.key()) {
+            return;
+        }
+
+        for successor in material.successors() {
+            self.generate_material(&successor);
+        }
+
+        let bitbase = generator::generate(material, self);
+        self.tables.insert(material.key(), bitbase);
+    }
+
+    /// # Load Bitbases
+    ///
+    /// Loads every bitbase found in a directory. DTM tables are loaded when present.
+    ///
+    /// ## Errors
+    /// Returns `EndgameError::Io` if the directory or a table cannot be read, and
+    /// `EndgameError::InvalidFile` if a table does not match its material.
+    pub fn load(dir: &Path) -> Result<Self, EndgameError> {
+        let mut bitbases = Self::new();
+
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+
+            if path.extension().is_none_or(|ext| ext != WDL_EXTENSION) {
+                continue;
+            }
+
+            let Some(Ok(material)) = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .map(Material::parse)
+            else {
+                continue;
+            };
+
+            let bitbase = Bitbase::load(dir, &material)?;
+            bitbases.tables.insert(material.key(), bitbase);
+        }
+
+        Ok(bitbases)
+    }
+
+    /// # Save Bitbases
+    ///
+    /// Writes every bitbase to a directory, see `Bitbase::save`.
+    ///
+    /// ## Errors
+    /// Returns `EndgameError::Io` if the directory cannot be created or a file cannot be written.
+    pub fn save(&self, dir: &Path) -> Result<(), EndgameError> {
+        std::fs::create_dir_all(dir)?;
+
+        self.tables
+            .values()
+            .try_for_each(|bitbase| bitbase.save(dir))
+    }
+
+    /******************************************\
+    |==========================================|
+    |                 Probing                  |
+    |==========================================|
+    \******************************************/
+
+    /// # Probe WDL
+    ///
+    /// Returns the result of the position, or `None` if it has castling rights or
+    /// its material is not covered.
+    pub fn probe_wdl(&self, board: &Board) -> Option<Wdl> {
+        self.probe_dtm(board).map(Dtm::wdl)
+    }
+
+    /// # Probe DTM
+    ///
+    /// Returns the result of the position with its distance to mate, or `None` if it
+    /// has castling rights or its material is not covered. Distances are 0 for tables
+    /// loaded without their DTM file.
+    pub fn probe_dtm(&self, board: &Board) -> Option<Dtm> {
+        let occ = board.all_occupied_bb();
+
+        if occ.count_bits() as usize > MAX_PIECES || board.castling() != Castling::NONE {
+            return None;
+        }
+
+        let mut pieces = [(Piece::WhiteKing, Square::A1); MAX_PIECES];
+        let mut len = 0;
+        let mut occ = occ;
+
+        while let Some(sq) = occ.pop_lsb() {
+            pieces[len] = (board.on(sq)?, sq);
+            len += 1;
+        }
+
+        let result = self.probe_pieces(&pieces[..len], board.stm())?;
+
+        // En passant rights are not indexed, so look at the captures separately
+        if board.ep().is_none() {
+            return Some(result);
+        }
+
+        let mut move_list = MoveList::new();
+        board.generate_moves::<LegalGen>(&mut move_list);
+
+        move_list
+            .iter()
+            .filter(|move_| move_.flag() == MoveFlag::EPCapture)
+            .try_fold(result, |best, &move_| {
+                let mut child = board.clone();
+                child.make_move(move_);
+                Some(best.max(self.probe_dtm(&child)?.parent()))
+            })
+    }
+
+    /// Probes the position made of the given pieces, in any order
+    pub(super) fn probe_pieces(&self, pieces: &[(Piece, Square)], stm: Colour) -> Option<Dtm> {
+        let key = material_key(pieces.iter().map(|&(pc, _)| pc));
+        let flipped_key = material_key(pieces.iter().map(|&(pc, _)| flip_piece(pc)));
+
+        // Tables are stored with the stronger side as white
+        let (bitbase, flip) = match self.tables.get(&key) {
+            Some(bitbase) => (bitbase, false),
+            None => (self.tables.get(&flipped_key)?, true),
+        };
+
+        let mut squares = [Square::A1; MAX_PIECES];
+        let mut used = [false; MAX_PIECES];
+
+        for (slot, &table_piece) in bitbase.material().pieces().iter().enumerate() {
+            let i = (0..pieces.len()).find(|&i| {
+                !used[i]
+                    && if flip {
+                        flip_piece(pieces[i].0)
+                    } else {
+                        pieces[i].0
+                    } == table_piece
+            })?;
+
+            used[i] = true;
+            squares[slot] = if flip {
+                pieces[i].1.flip_rank()
+            } else {
+                pieces[i].1
+            };
+        }
+
+        let stm = if flip { !stm } else { stm };
+
+        bitbase.get(bitbase.material().index(&squares[..pieces.len()], stm))
+    }
+}
+
+/// Returns the same piece type of the other colour
+fn flip_piece(piece: Piece) -> Piece {
+    Piece::from_parts(!piece.colour(), piece.pt())
+}
+
+/******************************************\
+|==========================================|
+|              Endgame Errors              |
+|==========================================|
+\******************************************/
+
+/// Represents errors that can occur while generating, reading or writing bitbases.
+#[derive(Error, Debug)]
+pub enum EndgameError {
+    #[error("Failed to access bitbase file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid endgame material: {0}")]
+    InvalidMaterial(String),
+    #[error("Invalid bitbase file: {0}")]
+    InvalidFile(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn probe(bitbases: &Bitbases, fen: &str) -> Dtm {
+        let board = Board::from_fen(fen).unwrap();
+        bitbases.probe_dtm(&board).unwrap()
+    }
+
+    #[test]
+    fn test_kqk_krk() {
+        let mut bitbases = Bitbases::new();
+
+        // Longest mates are 10 and 16 moves with the strong side to move,
+        // and one more ply with the weak side to move
+        assert_eq!(bitbases.generate("KQK").unwrap().max_dtm(), 20);
+        assert_eq!(bitbases.generate("KRK").unwrap().max_dtm(), 32);
+
+        assert_eq!(
+            probe(&bitbases, "7k/8/6K1/8/8/8/8/1Q6 w - - 0 1"),
+            Dtm::Win(1)
+        );
+        assert_eq!(
+            probe(&bitbases, "1Q5k/8/6K1/8/8/8/8/8 b - - 0 1"),
+            Dtm::Loss(0)
+        );
+        assert_eq!(
+            probe(&bitbases, "8/8/8/8/8/1k6/1q6/K7 w - - 0 1"),
+            Dtm::Loss(0)
+        );
+        // Stalemate, and a hanging rook
+        assert_eq!(
+            probe(&bitbases, "k7/2Q5/1K6/8/8/8/8/8 b - - 0 1"),
+            Dtm::Draw
+        );
+        assert_eq!(probe(&bitbases, "8/8/8/8/8/8/1r6/K6k w - - 0 1"), Dtm::Draw);
+        assert_eq!(probe(&bitbases, "8/8/8/8/8/8/1k6/R6K b - - 0 1"), Dtm::Draw);
+    }
+
+    #[test]
+    fn test_kpk() {
+        let mut bitbases = Bitbases::new();
+        let kpk = bitbases.generate("KPK").unwrap();
+
+        // The longest win is a mate in 28 moves, one more ply with the pawnless side to move
+        assert_eq!(kpk.max_dtm(), 56);
+
+        // Every position is counted with the pawn on the a to d files, including the
+        // illegal ones with the pawn on the first or last rank. With white to move
+        // there are 62480 wins and 19184 draws, with black to move 48802 losses and
+        // 35210 draws.
+        let [invalid, loss, draw, win] = kpk.counts();
+        assert_eq!((invalid, loss, draw, win), (96_468, 48_802, 54_394, 62_480));
+
+        // Opposition decides the key squares
+        assert_eq!(
+            probe(&bitbases, "8/4k3/8/4K3/4P3/8/8/8 w - - 0 1").wdl(),
+            Wdl::Draw
+        );
+        assert_eq!(
+            probe(&bitbases, "8/4k3/8/4K3/4P3/8/8/8 b - - 0 1").wdl(),
+            Wdl::Loss
+        );
+        assert_eq!(
+            probe(&bitbases, "4k3/8/4K3/4P3/8/8/8/8 b - - 0 1").wdl(),
+            Wdl::Loss
+        );
+        // Stalemate, but Kd6 wins with the move
+        assert_eq!(
+            probe(&bitbases, "4k3/4P3/4K3/8/8/8/8/8 b - - 0 1"),
+            Dtm::Draw
+        );
+        assert_eq!(
+            probe(&bitbases, "4k3/4P3/4K3/8/8/8/8/8 w - - 0 1").wdl(),
+            Wdl::Win
+        );
+        // Rook pawn with the defending king in front
+        assert_eq!(
+            probe(&bitbases, "k7/8/K7/P7/8/8/8/8 w - - 0 1").wdl(),
+            Wdl::Draw
+        );
+        // The same positions with the colours swapped
+        assert_eq!(
+            probe(&bitbases, "8/8/8/4p3/4k3/8/4K3/8 b - - 0 1").wdl(),
+            Wdl::Draw
+        );
+        assert_eq!(
+            probe(&bitbases, "8/8/8/4p3/4k3/8/4K3/8 w - - 0 1").wdl(),
+            Wdl::Loss
+        );
+        assert_eq!(
+            probe(&bitbases, "8/8/8/8/4p3/4k3/8/4K3 b - - 0 1").wdl(),
+            Wdl::Win
+        );
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let mut bitbases = Bitbases::new();
+        bitbases.generate("KQK").unwrap();
+
+        let dir = std::env::temp_dir().join(format!("bitbases_{}", std::process::id()));
+        bitbases.save(&dir).unwrap();
+
+        let loaded = Bitbases::load(&dir).unwrap();
+        let material = Material::parse("KQK").unwrap();
+        assert_eq!(loaded.get(&material), bitbases.get(&material));
+
+        let fen = "7k/8/6K1/8/8/8/8/1Q6 w - - 0 1";
+        assert_eq!(probe(&loaded, fen), Dtm::Win(1));
+
+        std::fs::write(dir.join("KQK.wdl"), b"CWDL").unwrap();
+        assert!(matches!(
+            Bitbases::load(&dir),
+            Err(EndgameError::InvalidFile(_))
+        ));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,215 @@
+use std::{
+    fs,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use super::{Dtm, EndgameError, Material};
+
+/******************************************\
+|==========================================|
+|                Constants                 |
+|==========================================|
+\******************************************/
+
+/// Entry codes of the WDL bitbase, 2 bits per position from the side to move's point of view
+pub(super) const INVALID: u8 = 0;
+pub(super) const LOSS: u8 = 1;
+pub(super) const DRAW: u8 = 2;
+pub(super) const WIN: u8 = 3;
+
+/// Magic bytes starting WDL and DTM files
+const WDL_MAGIC: [u8; 4] = *b"CWDL";
+const DTM_MAGIC: [u8; 4] = *b"CDTM";
+
+/// File extensions of WDL and DTM bitbases
+pub const WDL_EXTENSION: &str = "wdl";
+pub const DTM_EXTENSION: &str = "dtm";
+
+/// Size of the file header: magic and number of entries
+const HEADER_SIZE: usize = 8;
+
+/******************************************\
+|==========================================|
+|                 Bitbase                  |
+|==========================================|
+\******************************************/
+
+/// The generated tables of one ending.
+///
+/// The WDL table packs the result of 4 positions per byte, and the optional DTM
+/// table stores the distance to mate in plies with one byte per position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bitbase {
+    material: Material,
+    wdl: Vec<u8>,
+    dtm: Option<Vec<u8>>,
+}
+
+impl Bitbase {
+    /// Creates a bitbase from the WDL codes and distances to mate of every entry
+    pub(super) fn from_entries(material: Material, codes: &[u8], dtm: Vec<u8>) -> Self {
+        let mut wdl = vec![0; codes.len().div_ceil(4)];
+
+        for (i, &code) in codes.iter().enumerate() {
+            wdl[i / 4] |= code << (2 * (i % 4));
+        }
+
+        Self {
+            material,
+            wdl,
+            dtm: Some(dtm),
+        }
+    }
+
+    /// Returns the material of the table
+    pub fn material(&self) -> &Material {
+        &self.material
+    }
+
+    /// Returns whether the distances to mate are available
+    pub fn has_dtm(&self) -> bool {
+        self.dtm.is_some()
+    }
+
+    /// Returns the WDL code of an entry
+    pub(super) fn code(&self, index: usize) -> u8 {
+        (self.wdl[index / 4] >> (2 * (index % 4))) & 3
+    }
+
+    /// Returns the result of an entry, or `None` for illegal positions.
+    /// Wins and losses have a distance of 0 when the DTM table is not loaded.
+    pub(super) fn get(&self, index: usize) -> Option<Dtm> {
+        let dtm = self.dtm.as_ref().map_or(0, |dtm| dtm[index]);
+
+        match self.code(index) {
+            LOSS => Some(Dtm::Loss(dtm)),
+            DRAW => Some(Dtm::Draw),
+            WIN => Some(Dtm::Win(dtm)),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of positions of each result, indexed by the WDL codes
+    pub fn counts(&self) -> [usize; 4] {
+        let mut counts = [0; 4];
+
+        for index in 0..self.material.size() {
+            counts[self.code(index) as usize] += 1;
+        }
+
+        counts
+    }
+
+    /// Returns the longest distance to mate in plies, 0 without a DTM table
+    pub fn max_dtm(&self) -> u8 {
+        self.dtm
+            .as_ref()
+            .and_then(|dtm| dtm.iter().max().copied())
+            .unwrap_or(0)
+    }
+
+    /******************************************\
+    |==========================================|
+    |               File Access                |
+    |==========================================|
+    \******************************************/
+
+    /// # Save Bitbase
+    ///
+    /// Writes `<name>.wdl`, and `<name>.dtm` when available, to the given directory.
+    ///
+    /// ## Errors
+    /// Returns `EndgameError::Io` if a file cannot be written.
+    pub fn save(&self, dir: &Path) -> Result<(), EndgameError> {
+        let name = self.material.name();
+        let entries = self.material.size() as u32;
+
+        write_table(
+            &dir.join(format!("{name}.{WDL_EXTENSION}")),
+            WDL_MAGIC,
+            entries,
+            &self.wdl,
+        )?;
+
+        if let Some(dtm) = &self.dtm {
+            write_table(
+                &dir.join(format!("{name}.{DTM_EXTENSION}")),
+                DTM_MAGIC,
+                entries,
+                dtm,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// # Load Bitbase
+    ///
+    /// Reads the WDL table of an ending from the given directory, and its DTM table if present.
+    ///
+    /// ## Errors
+    /// Returns `EndgameError::Io` if the WDL file cannot be read, and `EndgameError::InvalidFile`
+    /// if a file has the wrong magic or does not match the size of the ending.
+    pub fn load(dir: &Path, material: &Material) -> Result<Self, EndgameError> {
+        let name = material.name();
+        let entries = material.size();
+
+        let wdl = read_table(
+            &dir.join(format!("{name}.{WDL_EXTENSION}")),
+            WDL_MAGIC,
+            entries,
+            entries.div_ceil(4),
+        )?;
+
+        let dtm_path = dir.join(format!("{name}.{DTM_EXTENSION}"));
+        let dtm = if dtm_path.is_file() {
+            Some(read_table(&dtm_path, DTM_MAGIC, entries, entries)?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            material: material.clone(),
+            wdl,
+            dtm,
+        })
+    }
+}
+
+/// Writes a table file: magic, little endian number of entries, then the data
+fn write_table(path: &Path, magic: [u8; 4], entries: u32, data: &[u8]) -> Result<(), EndgameError> {
+    let mut writer = BufWriter::new(fs::File::create(path)?);
+
+    writer.write_all(&magic)?;
+    writer.write_all(&entries.to_le_bytes())?;
+    writer.write_all(data)?;
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// Reads a table file, checking its header and size
+fn read_table(
+    path: &Path,
+    magic: [u8; 4],
+    entries: usize,
+    len: usize,
+) -> Result<Vec<u8>, EndgameError> {
+    let mut bytes = fs::read(path)?;
+    let invalid = |reason: &str| EndgameError::InvalidFile(format!("{}: {reason}", path.display()));
+
+    if bytes.len() < HEADER_SIZE || bytes[..4] != magic {
+        return Err(invalid("bad magic"));
+    }
+
+    if u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize != entries
+        || bytes.len() != HEADER_SIZE + len
+    {
+        return Err(invalid("size does not match the material"));
+    }
+
+    bytes.drain(..HEADER_SIZE);
+
+    Ok(bytes)
+}
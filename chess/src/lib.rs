@@ -2,7 +2,11 @@
  
  
 pub mod board;
+pub mod book;
 pub mod core;
+pub mod endgame;
+#[cfg(feature = "serde")]
+pub mod serialize;
 pub mod utils;
 
 pub use core::*;
@@ -0,0 +1,285 @@
+//! `serde` support for the core types and `Board`, enabled by the `serde` feature.
+//!
+//! Types are serialised in the notation used by FEN and UCI, so they read naturally in JSON:
+//! squares as `"e4"`, pieces as `"N"`, colours as `"w"`, castling rights as `"KQkq"` and
+//! boards as their FEN. Bitboards are serialised as `u64` and moves as their raw `u16`, or as
+//! UCI strings with `UciMove` and `UciMoveSeed` when the board they are played on is known.
+use std::fmt::Display;
+use std::str::FromStr;
+
+use serde::de::{self, DeserializeSeed, Deserializer};
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::board::{Board, LegalGen, MoveList};
+use crate::core::*;
+
+/******************************************\
+|==========================================|
+|             String Helpers               |
+|==========================================|
+\******************************************/
+
+fn serialize_str<T: Display, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+fn deserialize_str<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: FromStr,
+    T::Err: Display,
+    D: Deserializer<'de>,
+{
+    String::deserialize(deserializer)?
+        .parse()
+        .map_err(de::Error::custom)
+}
+
+/// Implements `Serialize` and `Deserialize` through the `Display` and `FromStr` implementations
+macro_rules! impl_serde_str {
+    ($($type_name:ident),*) => {
+        $(
+            impl Serialize for $type_name {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serialize_str(self, serializer)
+                }
+            }
+
+            impl<'de> Deserialize<'de> for $type_name {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    deserialize_str(deserializer)
+                }
+            }
+        )*
+    };
+}
+
+impl_serde_str!(Square, Piece);
+
+/******************************************\
+|==========================================|
+|               Core Types                 |
+|==========================================|
+\******************************************/
+
+impl Serialize for PieceType {
+    /// Serialises the piece type as its lowercase letter
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_str(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for PieceType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let piece: Piece = deserialize_str(deserializer)?;
+
+        match piece.colour() {
+            Colour::Black => Ok(piece.pt()),
+            Colour::White => Err(de::Error::custom(format!(
+                "Piece type must be lowercase, found: {piece}"
+            ))),
+        }
+    }
+}
+
+impl Serialize for Colour {
+    /// Serialises the colour as in FEN, `"w"` or `"b"`
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(match self {
+            Colour::White => "w",
+            Colour::Black => "b",
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for Colour {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match String::deserialize(deserializer)?.as_str() {
+            "w" => Ok(Colour::White),
+            "b" => Ok(Colour::Black),
+            s => Err(de::Error::custom(format!("Invalid colour: {s}"))),
+        }
+    }
+}
+
+impl Serialize for Castling {
+    /// Serialises the castling rights in the `KQkq` format, `"-"` if there are none
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_str(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Castling {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+
+        if s == "-" {
+            return Ok(Castling::NONE);
+        }
+
+        s.chars().try_fold(Castling::NONE, |mut castling, c| {
+            let right = match c {
+                'K' => Castling::WK,
+                'Q' => Castling::WQ,
+                'k' => Castling::BK,
+                'q' => Castling::BQ,
+                _ => return Err(de::Error::custom(format!("Invalid castling char: {c}"))),
+            };
+
+            castling.set(right);
+            Ok(castling)
+        })
+    }
+}
+
+impl Serialize for Bitboard {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Bitboard {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        u64::deserialize(deserializer).map(Bitboard)
+    }
+}
+
+/******************************************\
+|==========================================|
+|                  Moves                   |
+|==========================================|
+\******************************************/
+
+impl Serialize for Move {
+    /// Serialises the move as its raw `u16`, use `UciMove` for the UCI notation
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u16(self.raw())
+    }
+}
+
+impl<'de> Deserialize<'de> for Move {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = u16::deserialize(deserializer)?;
+        Move::from_raw(raw).ok_or_else(|| de::Error::custom(format!("Invalid move: {raw:#06x}")))
+    }
+}
+
+/// A move serialised in UCI notation, which depends on the board it is played on.
+#[derive(Debug, Clone, Copy)]
+pub struct UciMove<'a> {
+    pub board: &'a Board,
+    pub move_: Move,
+}
+
+impl Serialize for UciMove<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.move_.to_str(self.board))
+    }
+}
+
+/// Deserialises a move in UCI notation into the matching legal move of the board.
+#[derive(Debug, Clone, Copy)]
+pub struct UciMoveSeed<'a>(pub &'a Board);
+
+impl<'de> DeserializeSeed<'de> for UciMoveSeed<'_> {
+    type Value = Move;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Move, D::Error> {
+        let uci = String::deserialize(deserializer)?;
+
+        let mut move_list = MoveList::new();
+        self.0.generate_moves::<LegalGen>(&mut move_list);
+
+        move_list
+            .iter()
+            .find(|move_| move_.to_str(self.0) == uci)
+            .copied()
+            .ok_or_else(|| de::Error::custom(format!("Illegal move: {uci}")))
+    }
+}
+
+/******************************************\
+|==========================================|
+|                  Board                   |
+|==========================================|
+\******************************************/
+
+impl Serialize for Board {
+    /// Serialises the board as its FEN. The move history and Chess960 flag are not stored.
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.fen())
+    }
+}
+
+impl<'de> Deserialize<'de> for Board {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let fen = String::deserialize(deserializer)?;
+        Board::from_fen(&fen).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{START_FEN, TRICKY_FEN};
+
+    fn round_trip<T>(value: T, json: &str)
+    where
+        T: Serialize + for<'de> Deserialize<'de> + PartialEq + std::fmt::Debug,
+    {
+        assert_eq!(serde_json::to_string(&value).unwrap(), json);
+        assert_eq!(serde_json::from_str::<T>(json).unwrap(), value);
+    }
+
+    #[test]
+    fn test_core_types() {
+        round_trip(Square::E4, r#""e4""#);
+        round_trip(Piece::WhiteKnight, r#""N""#);
+        round_trip(Piece::BlackQueen, r#""q""#);
+        round_trip(PieceType::Rook, r#""r""#);
+        round_trip(Colour::Black, r#""b""#);
+        round_trip(Castling::ALL, r#""KQkq""#);
+        round_trip(Castling::WK | Castling::BQ, r#""Kq""#);
+        round_trip(Castling::NONE, r#""-""#);
+        round_trip(Bitboard(0xFF00), "65280");
+        round_trip(vec![Square::A1, Square::H8], r#"["a1","h8"]"#);
+
+        assert!(serde_json::from_str::<Square>(r#""i9""#).is_err());
+        assert!(serde_json::from_str::<PieceType>(r#""R""#).is_err());
+        assert!(serde_json::from_str::<Colour>(r#""white""#).is_err());
+        assert!(serde_json::from_str::<Castling>(r#""KX""#).is_err());
+    }
+
+    #[test]
+    fn test_moves() {
+        let move_ = Move::new(Square::E7, Square::E8, MoveFlag::QueenPromo);
+        round_trip(move_, &move_.raw().to_string());
+        assert!(serde_json::from_str::<Move>(&(0b0110 << 12).to_string()).is_err());
+
+        let board = Board::from_fen(TRICKY_FEN).unwrap();
+        let castle = Move::new(Square::E1, Square::G1, MoveFlag::KingCastle);
+        let json = serde_json::to_string(&UciMove {
+            board: &board,
+            move_: castle,
+        })
+        .unwrap();
+        assert_eq!(json, r#""e1g1""#);
+
+        let mut deserializer = serde_json::Deserializer::from_str(&json);
+        assert_eq!(
+            UciMoveSeed(&board).deserialize(&mut deserializer).unwrap(),
+            castle
+        );
+
+        let mut deserializer = serde_json::Deserializer::from_str(r#""e1e3""#);
+        assert!(UciMoveSeed(&board).deserialize(&mut deserializer).is_err());
+    }
+
+    #[test]
+    fn test_board() {
+        for fen in [START_FEN, TRICKY_FEN] {
+            round_trip(Board::from_fen(fen).unwrap(), &format!("\"{fen}\""));
+        }
+
+        assert!(serde_json::from_str::<Board>(r#""8/8/8 w - - 0 1""#).is_err());
+    }
+}
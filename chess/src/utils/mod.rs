@@ -1,5 +1,12 @@
 mod perft;
+mod perft_suite;
+mod perft_table;
 mod prng;
 
-pub use perft::{perft_bench, perft_test};
+pub use perft::{
+    PerftStats, perft, perft_bench, perft_divide, perft_divide_parallel, perft_hashed,
+    perft_parallel, perft_stats, perft_stats_test, perft_test,
+};
+pub use perft_suite::{PerftSuiteEntry, PerftSuiteError, parse_perft_suite, run_perft_suite};
+pub use perft_table::{PERFT_HASH_SIZE, PerftTable};
 pub use prng::PRNG;
@@ -1,6 +1,22 @@
-use crate::board::{Board, LegalGen, MoveList};
+use std::{
+    ops::AddAssign,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+    time::Instant,
+};
+
+use super::perft_table::{PERFT_HASH_SIZE, PerftTable};
+use crate::{
+    board::{Board, LegalGen, MoveList},
+    core::*,
+};
+
+/// Counts the leaf nodes of the move generation tree at the given depth
+pub fn perft(board: &mut Board, depth: usize) -> usize {
+    if depth == 0 {
+        return 1;
+    }
 
-fn perft(board: &mut Board, depth: usize) -> usize {
     let mut move_list = MoveList::new();
 
     board.generate_moves::<LegalGen>(&mut move_list);
@@ -13,47 +29,148 @@ fn perft(board: &mut Board, depth: usize) -> usize {
 
     for move_ in move_list.iter() {
         board.make_move(*move_);
+        board.debug_validate();
         nodes += perft(board, depth - 1);
         board.undo_move(*move_);
+        board.debug_validate();
     }
 
     nodes
 }
 
-pub fn perft_test(board: &mut Board, depth: usize) {
-    use std::time::Instant;
+/// Returns the UCI string and leaf count of every legal move, the moves being played on the board
+pub fn perft_divide(board: &mut Board, depth: usize) -> Vec<(String, usize)> {
+    let mut move_list = MoveList::new();
+    board.generate_moves::<LegalGen>(&mut move_list);
+
+    move_list
+        .iter()
+        .map(|move_| {
+            let name = move_.to_str(board);
+
+            board.make_move(*move_);
+            let nodes = perft(board, depth.saturating_sub(1));
+            board.undo_move(*move_);
+
+            (name, nodes)
+        })
+        .collect()
+}
+
+/// Counts the leaf nodes like `perft`, reusing the counts of transposed subtrees stored in the table
+pub fn perft_hashed(board: &mut Board, depth: usize, table: &PerftTable) -> usize {
+    if depth <= 1 {
+        return perft(board, depth);
+    }
+
+    if let Some(nodes) = table.get(board.key(), depth) {
+        return nodes;
+    }
 
     let mut move_list = MoveList::new();
+    board.generate_moves::<LegalGen>(&mut move_list);
+
+    let mut nodes = 0;
 
+    for move_ in move_list.iter() {
+        board.make_move(*move_);
+        board.debug_validate();
+        nodes += perft_hashed(board, depth - 1, table);
+        board.undo_move(*move_);
+        board.debug_validate();
+    }
+
+    table.set(board.key(), depth, nodes);
+
+    nodes
+}
+
+/// # Parallel Perft Divide
+///
+/// Returns the same counts as `perft_divide`, the root moves being split across
+/// `threads` threads which share the hash table when one is given.
+pub fn perft_divide_parallel(
+    board: &Board,
+    depth: usize,
+    threads: usize,
+    table: Option<&PerftTable>,
+) -> Vec<(String, usize)> {
+    let mut move_list = MoveList::new();
     board.generate_moves::<LegalGen>(&mut move_list);
 
-    if depth == 1 {
-        println!("Total nodes: {}", move_list.len());
-        return;
+    let moves: Vec<Move> = move_list.iter().copied().collect();
+    let counts: Vec<AtomicUsize> = moves.iter().map(|_| AtomicUsize::new(0)).collect();
+    let next = AtomicUsize::new(0);
+
+    thread::scope(|s| {
+        for _ in 0..threads.clamp(1, moves.len().max(1)) {
+            s.spawn(|| {
+                let mut board = board.clone();
+
+                // Each thread takes the next root move until none are left
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(&move_) = moves.get(index) else {
+                        break;
+                    };
+
+                    board.make_move(move_);
+                    let nodes = match table {
+                        Some(table) => perft_hashed(&mut board, depth.saturating_sub(1), table),
+                        None => perft(&mut board, depth.saturating_sub(1)),
+                    };
+                    board.undo_move(move_);
+
+                    counts[index].store(nodes, Ordering::Relaxed);
+                }
+            });
+        }
+    });
+
+    moves
+        .iter()
+        .zip(counts)
+        .map(|(move_, nodes)| (move_.to_str(board), nodes.into_inner()))
+        .collect()
+}
+
+/// Counts the leaf nodes like `perft` using `threads` threads and an optional hash table
+pub fn perft_parallel(
+    board: &Board,
+    depth: usize,
+    threads: usize,
+    table: Option<&PerftTable>,
+) -> usize {
+    if depth == 0 {
+        return 1;
     }
 
+    perft_divide_parallel(board, depth, threads, table)
+        .iter()
+        .map(|(_, nodes)| nodes)
+        .sum()
+}
+
+/// # Perft Test
+///
+/// Prints the leaf count of every root move and the total, using `threads` threads
+/// and a hash table of `hash_mb` megabytes, 0 disabling it.
+pub fn perft_test(board: &Board, depth: usize, threads: usize, hash_mb: usize) {
     println!("=============== PERFT TEST ===============");
     println!("                 Depth: {depth}           ");
     println!("==========================================");
 
-    let mut total_nodes = 0;
+    let table = (hash_mb > 0).then(|| PerftTable::new(hash_mb));
 
     let start = Instant::now();
+    let divide = perft_divide_parallel(board, depth, threads, table.as_ref());
+    let time = start.elapsed().as_millis().max(1);
 
-    for move_ in move_list.iter() {
-        board.make_move(*move_);
-        if depth == 1 {
-            println!("              {}: 1", move_.to_str(&board));
-            continue;
-        }
-        let nodes = perft(board, depth - 1);
-        total_nodes += nodes;
-        board.undo_move(*move_);
-
-        println!("              {}: {nodes:?}", move_.to_str(&board));
+    for (move_, nodes) in &divide {
+        println!("              {move_}: {nodes}");
     }
 
-    let time = start.elapsed().as_millis();
+    let total_nodes: usize = divide.iter().map(|(_, nodes)| nodes).sum();
 
     println!("=========================================");
     println!("              Nodes: {total_nodes}       ");
@@ -65,6 +182,145 @@ pub fn perft_test(board: &mut Board, depth: usize) {
     println!("=========================================");
 }
 
+/******************************************\
+|==========================================|
+|             Perft Statistics             |
+|==========================================|
+\******************************************/
+
+/// Move counts of the leaf nodes of a perft tree, in the format of the
+/// Chess Programming Wiki perft result tables
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PerftStats {
+    pub nodes: usize,
+    pub captures: usize,
+    pub en_passants: usize,
+    pub castles: usize,
+    pub promotions: usize,
+    pub checks: usize,
+    pub discovered_checks: usize,
+    pub double_checks: usize,
+    pub checkmates: usize,
+}
+
+impl PerftStats {
+    /// Records the leaf reached by playing `move_` on the board
+    fn record(&mut self, board: &mut Board, move_: Move) {
+        self.nodes += 1;
+        self.captures += move_.is_capture() as usize;
+        self.en_passants += (move_.flag() == MoveFlag::EPCapture) as usize;
+        self.castles += move_.is_castle() as usize;
+        self.promotions += move_.is_promotion() as usize;
+
+        board.make_move(move_);
+
+        if board.in_check() {
+            let ksq = board.ksq(board.stm());
+            let checkers =
+                board.attackers_to(ksq, board.all_occupied_bb()) & board.occupied_bb(!board.stm());
+
+            // A check is discovered when the moved piece is not giving it,
+            // the rook being the checking piece of a castling move
+            let moved = match move_.flag() {
+                MoveFlag::KingCastle => Square::from_parts(File::FileF, move_.to().rank()),
+                MoveFlag::QueenCastle => Square::from_parts(File::FileD, move_.to().rank()),
+                _ => move_.to(),
+            };
+
+            let mut replies = MoveList::new();
+            board.generate_moves::<LegalGen>(&mut replies);
+
+            self.checks += 1;
+            self.discovered_checks += !checkers.contains(moved) as usize;
+            self.double_checks += (checkers.count_bits() > 1) as usize;
+            self.checkmates += replies.is_empty() as usize;
+        }
+
+        board.undo_move(move_);
+    }
+}
+
+impl AddAssign for PerftStats {
+    fn add_assign(&mut self, other: Self) {
+        self.nodes += other.nodes;
+        self.captures += other.captures;
+        self.en_passants += other.en_passants;
+        self.castles += other.castles;
+        self.promotions += other.promotions;
+        self.checks += other.checks;
+        self.discovered_checks += other.discovered_checks;
+        self.double_checks += other.double_checks;
+        self.checkmates += other.checkmates;
+    }
+}
+
+/// Counts the leaf nodes at the given depth along with their captures, castles,
+/// promotions, checks and checkmates
+pub fn perft_stats(board: &mut Board, depth: usize) -> PerftStats {
+    let mut stats = PerftStats::default();
+
+    if depth == 0 {
+        stats.nodes = 1;
+        return stats;
+    }
+
+    let mut move_list = MoveList::new();
+    board.generate_moves::<LegalGen>(&mut move_list);
+
+    for move_ in move_list.iter() {
+        if depth == 1 {
+            stats.record(board, *move_);
+            continue;
+        }
+
+        board.make_move(*move_);
+        stats += perft_stats(board, depth - 1);
+        board.undo_move(*move_);
+    }
+
+    stats
+}
+
+/// Prints the perft statistics of every depth up to `depth` as a table
+pub fn perft_stats_test(board: &mut Board, depth: usize) {
+    println!(
+        "{:>5} {:>14} {:>12} {:>8} {:>9} {:>10} {:>10} {:>10} {:>8} {:>10}",
+        "Depth",
+        "Nodes",
+        "Captures",
+        "E.p.",
+        "Castles",
+        "Promotions",
+        "Checks",
+        "Disc.",
+        "Double",
+        "Mates"
+    );
+
+    for d in 1..=depth {
+        let stats = perft_stats(board, d);
+
+        println!(
+            "{d:>5} {:>14} {:>12} {:>8} {:>9} {:>10} {:>10} {:>10} {:>8} {:>10}",
+            stats.nodes,
+            stats.captures,
+            stats.en_passants,
+            stats.castles,
+            stats.promotions,
+            stats.checks,
+            stats.discovered_checks,
+            stats.double_checks,
+            stats.checkmates
+        );
+    }
+}
+
+/******************************************\
+|==========================================|
+|                  Bench                   |
+|==========================================|
+\******************************************/
+
 #[rustfmt::skip]
 const BENCH_LIST: &[(&str, usize, usize)] = &[
     ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", 6, 119060324),
@@ -115,33 +371,43 @@ const BENCH_LIST: &[(&str, usize, usize)] = &[
     ("1b1r1krb/ppp1np2/qn1p2pp/3Bp3/2P1P1PP/1N1P4/PP3P2/1BNRQKR1 w KQkq - 0 9", 6, 1169912833)
 ];
 
+/// # Perft Bench
+///
+/// Runs every bench position with plain perft, then with a parallel hashed perft
+/// on all available threads, and returns whether both matched every expected count.
 pub fn perft_bench() -> bool {
-    use std::time::Instant;
+    let threads = thread::available_parallelism().map_or(1, usize::from);
 
     let mut passed = true;
     println!("=============  START BENCH  =============");
+    println!("Parallel perft: {threads} threads, {PERFT_HASH_SIZE} MB hash");
 
     for (fen, depth, expected_nodes) in BENCH_LIST.iter() {
         let mut board = Board::from_fen(fen).unwrap();
 
         let start = Instant::now();
         let nodes = perft(&mut board, *depth);
+        let time = start.elapsed().as_millis().max(1);
 
-        let time = start.elapsed().as_millis();
+        let table = PerftTable::new(PERFT_HASH_SIZE);
+        let start = Instant::now();
+        let parallel_nodes = perft_parallel(&board, *depth, threads, Some(&table));
+        let parallel_time = start.elapsed().as_millis().max(1);
 
-        let status: &str = if nodes == *expected_nodes {
+        let status: &str = if nodes == *expected_nodes && parallel_nodes == *expected_nodes {
             "PASSED"
         } else {
             "FAILED"
         };
 
-        if nodes != *expected_nodes {
+        if nodes != *expected_nodes || parallel_nodes != *expected_nodes {
             passed = false;
         }
 
         println!(
-            "status: {status}, time: {time:4}ms, Mnps: {:0.1}, Fen: {fen} )",
-            (nodes as f64 / time as f64 / 1000.0)
+            "status: {status}, time: {time:4}ms, Mnps: {:0.1}, parallel time: {parallel_time:4}ms, parallel Mnps: {:0.1}, Fen: {fen} )",
+            (nodes as f64 / time as f64 / 1000.0),
+            (nodes as f64 / parallel_time as f64 / 1000.0)
         )
     }
 
@@ -151,6 +417,7 @@ pub fn perft_bench() -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::board::START_FEN;
 
     fn perft_bench_with_key_check() {
         for (fen, depth, expected_nodes) in BENCH_LIST.iter() {
@@ -184,6 +451,72 @@ mod tests {
         nodes
     }
 
+    fn stats(fen: &str, depth: usize) -> [usize; 9] {
+        let s = perft_stats(&mut Board::from_fen(fen).unwrap(), depth);
+        [
+            s.nodes,
+            s.captures,
+            s.en_passants,
+            s.castles,
+            s.promotions,
+            s.checks,
+            s.discovered_checks,
+            s.double_checks,
+            s.checkmates,
+        ]
+    }
+
+    #[test]
+    fn test_perft_stats() {
+        // Results from the Chess Programming Wiki perft tables
+        let start = stats(START_FEN, 4);
+        assert_eq!(start, [197281, 1576, 0, 0, 0, 469, 0, 0, 8]);
+
+        let kiwipete = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+        assert_eq!(
+            stats(kiwipete, 3),
+            [97862, 17102, 45, 3162, 0, 993, 0, 0, 1]
+        );
+
+        let position_3 = "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1";
+        assert_eq!(
+            stats(position_3, 4),
+            [43238, 3348, 123, 0, 0, 1680, 106, 0, 17]
+        );
+        assert_eq!(
+            stats(position_3, 5),
+            [674624, 52051, 1165, 0, 0, 52950, 1292, 3, 0]
+        );
+
+        let position_4 = "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1";
+        assert_eq!(stats(position_4, 3), [9467, 1021, 4, 0, 120, 38, 2, 0, 22]);
+    }
+
+    #[test]
+    fn test_perft_parallel() {
+        let table = PerftTable::new(1);
+
+        for (fen, depth, expected_nodes) in BENCH_LIST.iter().filter(|(_, _, n)| *n < 700_000) {
+            let mut board = Board::from_fen(fen).unwrap();
+
+            assert_eq!(
+                perft_parallel(&board, *depth, 4, None),
+                *expected_nodes,
+                "{fen}"
+            );
+            assert_eq!(
+                perft_parallel(&board, *depth, 4, Some(&table)),
+                *expected_nodes,
+                "{fen}"
+            );
+            assert_eq!(
+                perft_hashed(&mut board, *depth, &table),
+                *expected_nodes,
+                "{fen}"
+            );
+        }
+    }
+
     #[test]
     fn test_perft_bench() {
         assert!(perft_bench());
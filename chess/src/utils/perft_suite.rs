@@ -0,0 +1,233 @@
+use std::{fs, path::Path, str::FromStr, time::Instant};
+
+use thiserror::Error;
+
+use super::perft::{perft, perft_divide};
+use crate::{
+    board::{Board, fen::FenParseError},
+    core::*,
+};
+
+/******************************************\
+|==========================================|
+|               Suite Entry                |
+|==========================================|
+\******************************************/
+
+/// A position of a perft suite with its expected leaf counts.
+///
+/// Suites use the EPD format, the FEN being followed by the counts of each depth:
+/// `rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - ;D1 20 ;D2 400 ;D3 8902`.
+/// The move counters may be omitted, and Chess960 positions may use Shredder or X-FEN castling rights.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PerftSuiteEntry {
+    pub fen: String,
+    pub depths: Vec<(usize, usize)>,
+}
+
+impl PerftSuiteEntry {
+    /// # Suite Board
+    ///
+    /// Creates the board of the entry, in Chess960 mode when the castling rights
+    /// use rook files or the king is not on the e-file.
+    ///
+    /// ## Errors
+    /// Returns `FenParseError` if the FEN is invalid.
+    pub fn board(&self) -> Result<Board, FenParseError> {
+        let fields = self.fen.split_whitespace().count();
+        let fen = if fields == 4 {
+            format!("{} 0 1", self.fen)
+        } else {
+            self.fen.clone()
+        };
+
+        let mut board = Board::from_fen(&fen)?;
+
+        let castling = fen.split_whitespace().nth(2).unwrap_or("-");
+        let shredder = castling.chars().any(|c| !"KQkq-".contains(c));
+        let moved_king = [
+            (Colour::White, Castling::WHITE_CASTLING),
+            (Colour::Black, Castling::BLACK_CASTLING),
+        ]
+        .into_iter()
+        .any(|(col, rights)| {
+            board.castling().has(rights) && board.ksq(col) != Square::E1.relative(col)
+        });
+
+        board.set_chess960(shredder || moved_king);
+
+        Ok(board)
+    }
+}
+
+impl FromStr for PerftSuiteEntry {
+    type Err = PerftSuiteError;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let mut sections = line.split(';');
+        let fen = sections.next().unwrap_or_default().trim().to_string();
+
+        if fen.is_empty() {
+            return Err(PerftSuiteError::InvalidEntry(line.to_string()));
+        }
+
+        let depths = sections
+            .map(|section| {
+                let mut tokens = section.split_whitespace();
+
+                let depth = tokens
+                    .next()
+                    .and_then(|token| token.strip_prefix('D'))
+                    .and_then(|depth| depth.parse().ok());
+                let nodes = tokens.next().and_then(|nodes| nodes.parse().ok());
+
+                depth
+                    .zip(nodes)
+                    .ok_or_else(|| PerftSuiteError::InvalidEntry(section.trim().to_string()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self { fen, depths })
+    }
+}
+
+/******************************************\
+|==========================================|
+|                  Runner                  |
+|==========================================|
+\******************************************/
+
+/// # Parse Perft Suite
+///
+/// Parses every position of a suite, skipping empty lines and `#` comments.
+///
+/// ## Errors
+/// Returns `PerftSuiteError::InvalidEntry` if a line is not a valid entry.
+pub fn parse_perft_suite(contents: &str) -> Result<Vec<PerftSuiteEntry>, PerftSuiteError> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::parse)
+        .collect()
+}
+
+/// # Run Perft Suite
+///
+/// Runs every position of the suite file up to `max_depth`, and stops at the first
+/// mismatch, printing the position with the leaf count of each of its moves.
+/// Returns whether every count matched.
+///
+/// ## Errors
+/// Returns `PerftSuiteError` if the file cannot be read or contains an invalid entry or FEN.
+pub fn run_perft_suite(path: &Path, max_depth: usize) -> Result<bool, PerftSuiteError> {
+    let entries = parse_perft_suite(&fs::read_to_string(path)?)?;
+
+    println!("=========  START PERFT SUITE  =========");
+
+    let start = Instant::now();
+    let mut total_nodes = 0;
+
+    for (i, entry) in entries.iter().enumerate() {
+        let mut board = entry
+            .board()
+            .map_err(|e| PerftSuiteError::InvalidFen(format!("{} -> {e}", entry.fen)))?;
+
+        for &(depth, expected_nodes) in entry.depths.iter().filter(|(d, _)| *d <= max_depth) {
+            let nodes = perft(&mut board, depth);
+            total_nodes += nodes;
+
+            if nodes != expected_nodes {
+                println!("status: FAILED, Depth: {depth}, Fen: {}", entry.fen);
+                println!("expected: {expected_nodes}, found: {nodes}");
+                println!("{board}");
+
+                for (move_, nodes) in perft_divide(&mut board, depth) {
+                    println!("{move_}: {nodes}");
+                }
+
+                return Ok(false);
+            }
+        }
+
+        println!("status: PASSED, Position: {:4}, Fen: {}", i + 1, entry.fen);
+    }
+
+    let time = start.elapsed().as_millis().max(1);
+
+    println!("=========================================");
+    println!("Positions: {}", entries.len());
+    println!("Nodes: {total_nodes}");
+    println!("Mnps: {:0.1}", total_nodes as f64 / time as f64 / 1000.0);
+
+    Ok(true)
+}
+
+/******************************************\
+|==========================================|
+|               Suite Errors               |
+|==========================================|
+\******************************************/
+
+#[derive(Error, Debug)]
+pub enum PerftSuiteError {
+    #[error("Failed to read perft suite: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid perft suite entry: {0}")]
+    InvalidEntry(String),
+    #[error("Invalid perft suite FEN: {0}")]
+    InvalidFen(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_entry() {
+        let entry: PerftSuiteEntry =
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - ;D1 20 ;D2 400"
+                .parse()
+                .unwrap();
+
+        assert_eq!(
+            entry.fen,
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -"
+        );
+        assert_eq!(entry.depths, vec![(1, 20), (2, 400)]);
+        assert!(!entry.board().unwrap().chess960());
+
+        assert!(
+            "8/8/8/8/8/8/8/8 w - - ;D1"
+                .parse::<PerftSuiteEntry>()
+                .is_err()
+        );
+        assert!(
+            "8/8/8/8/8/8/8/8 w - - ;X1 20"
+                .parse::<PerftSuiteEntry>()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_suite() {
+        let suite = "
+            # Chess Programming Wiki positions
+            r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - ;D1 48 ;D2 2039 ;D3 97862
+            8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1 ;D1 14 ;D2 191 ;D3 2812 ;D4 43238
+            bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9 ;D1 21 ;D2 528 ;D3 12189
+        ";
+
+        let entries = parse_perft_suite(suite).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert!(entries[2].board().unwrap().chess960());
+
+        for entry in entries {
+            let mut board = entry.board().unwrap();
+
+            for (depth, nodes) in entry.depths {
+                assert_eq!(perft(&mut board, depth), nodes, "{}", entry.fen);
+            }
+        }
+    }
+}
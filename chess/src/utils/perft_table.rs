@@ -0,0 +1,79 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Default size of the perft hash table in MB
+pub const PERFT_HASH_SIZE: usize = 64;
+
+/// Multiplier mixing the depth into the key, so each depth of a position has its own slot
+const DEPTH_MIX: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// Bits of the packed data holding the depth, the node count using the others
+const DEPTH_BITS: u64 = 8;
+const DEPTH_MASK: u64 = (1 << DEPTH_BITS) - 1;
+
+/******************************************\
+|==========================================|
+|               Perft Table                |
+|==========================================|
+\******************************************/
+
+/// Hash table of perft subtree counts keyed by the position key and depth,
+/// shared by the threads of a parallel perft.
+///
+/// Entries are written without locks. As in the transposition table, the key is stored
+/// xored with the data, so an entry torn by concurrent writes fails the key check instead
+/// of returning a wrong count.
+#[derive(Debug)]
+pub struct PerftTable {
+    entries: Vec<PerftEntry>,
+}
+
+#[derive(Debug, Default)]
+struct PerftEntry {
+    key: AtomicU64,
+    data: AtomicU64,
+}
+
+impl PerftTable {
+    /// Creates a table using `mb` megabytes, with at least one entry
+    pub fn new(mb: usize) -> Self {
+        let len = ((mb << 20) / size_of::<PerftEntry>()).max(1);
+        let mut entries = Vec::new();
+        entries.resize_with(len, PerftEntry::default);
+
+        Self { entries }
+    }
+
+    /// Returns the key of a position at a given depth
+    fn mixed_key(key: u64, depth: usize) -> u64 {
+        key ^ (depth as u64).wrapping_mul(DEPTH_MIX)
+    }
+
+    fn entry(&self, key: u64) -> &PerftEntry {
+        let index = ((key as u128 * self.entries.len() as u128) >> 64) as usize;
+        &self.entries[index]
+    }
+
+    /// Returns the stored leaf count of the position at the given depth
+    pub fn get(&self, key: u64, depth: usize) -> Option<usize> {
+        let key = Self::mixed_key(key, depth);
+        let entry = self.entry(key);
+
+        let data = entry.data.load(Ordering::Relaxed);
+        let stored_key = entry.key.load(Ordering::Relaxed);
+
+        (stored_key ^ data == key && data & DEPTH_MASK == depth as u64)
+            .then_some((data >> DEPTH_BITS) as usize)
+    }
+
+    /// Stores the leaf count of the position at the given depth, replacing the previous entry
+    pub fn set(&self, key: u64, depth: usize, nodes: usize) {
+        debug_assert!(depth as u64 <= DEPTH_MASK);
+
+        let key = Self::mixed_key(key, depth);
+        let entry = self.entry(key);
+        let data = ((nodes as u64) << DEPTH_BITS) | depth as u64;
+
+        entry.key.store(key ^ data, Ordering::Relaxed);
+        entry.data.store(data, Ordering::Relaxed);
+    }
+}
@@ -24,6 +24,7 @@ pub enum Command {
 
     // --- Custom/Non-UCI Commands ---
     Perft(usize),
+    PerftStats(usize),
     Print,
     Bench,
     Eval,
@@ -40,7 +41,7 @@ impl FromStr for Command {
             Some("uci") => Ok(Self::Uci),
             Some("debug") => Self::parse_debug(tokens),
             Some("isready") => Ok(Self::IsReady),
-            Some("setoption") => Self::parse_option(tokens),
+            Some("setoption") => Self::parse_option(s, tokens),
             Some("ucinewgame") => Ok(Self::NewGame),
             Some("position") => Self::parse_position(tokens),
             Some("go") => Self::parse_go(tokens),
@@ -68,7 +69,7 @@ impl Command {
     }
 
     /// Parses the "setoption" command, extracting the option name and value.
-    fn parse_option<'a>(mut tokens: SplitWhitespace) -> Result<Self, UCICommandError> {
+    fn parse_option<'a>(line: &str, mut tokens: SplitWhitespace) -> Result<Self, UCICommandError> {
         // Expect "name"
         if tokens.next() != Some("name") {
             return Err(UCICommandError(
@@ -78,13 +79,15 @@ impl Command {
 
         // Collect words until "value" is found (option names can have spaces)
         let mut name_parts = Vec::new();
-        let mut next_token = tokens.next();
-        while let Some(token) = next_token {
+        let mut value = "";
+        for token in tokens {
             if token == "value" {
-                break; // Found "value", stop collecting name parts
+                // Take the rest of the line as is, so values such as paths keep their spacing
+                let offset = token.as_ptr() as usize - line.as_ptr() as usize + token.len();
+                value = line[offset..].trim();
+                break;
             }
             name_parts.push(token);
-            next_token = tokens.next();
         }
 
         let option_name = name_parts.join(" ");
@@ -92,7 +95,7 @@ impl Command {
             return Err(UCICommandError("Missing option name".to_string()));
         }
 
-        let option = EngineOption::try_from((option_name, tokens))?;
+        let option = EngineOption::try_from((option_name, value))?;
 
         Ok(Command::SetOption(option))
     }
@@ -171,14 +174,18 @@ impl Command {
         Ok(Self::Go(tc))
     }
 
-    /// Parses the "perft" command and its depth argument.
+    /// Parses the "perft" command, its depth argument and the optional "stats" flag.
     fn parse_perft<'a>(mut tokens: SplitWhitespace) -> Result<Self, UCICommandError> {
-        match tokens.next() {
-            Some(depth) => {
+        match (tokens.next(), tokens.next()) {
+            (Some(depth), stats @ (None | Some("stats"))) => {
                 let depth_usize = depth
                     .parse::<usize>()
                     .map_err(|e| UCICommandError(format!("Invalid perft depth -> {}", e)))?;
-                Ok(Self::Perft(depth_usize))
+                if stats.is_some() {
+                    Ok(Self::PerftStats(depth_usize))
+                } else {
+                    Ok(Self::Perft(depth_usize))
+                }
             }
             _ => Err(UCICommandError(format!("Invalid perft command"))),
         }
@@ -196,3 +203,30 @@ impl std::fmt::Display for UCICommandError {
 }
 
 impl std::error::Error for UCICommandError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_option_keeps_value_spacing() {
+        let command = "setoption name BookFile value books/my  book.bin"
+            .parse::<Command>()
+            .unwrap();
+        assert_eq!(
+            command,
+            Command::SetOption(EngineOption::BookFile("books/my  book.bin".to_string()))
+        );
+
+        let command = "setoption name SyzygyPath value  /tb/3  4  5 "
+            .parse::<Command>()
+            .unwrap();
+        assert_eq!(
+            command,
+            Command::SetOption(EngineOption::SyzygyPath("/tb/3  4  5".to_string()))
+        );
+
+        let command = "setoption name Hash value 64".parse::<Command>().unwrap();
+        assert_eq!(command, Command::SetOption(EngineOption::ResizeHash(64)));
+    }
+}
@@ -11,6 +11,7 @@ use crate::EngineController;
 
 // Import necessary modules from the parent 'engine' module and standard library.
 use super::Command;
+use chess::board::cuckoo::init_cuckoo_tables;
 use chess::board::movegen::init_magic_tables;
 use std::{
     io::BufRead,
@@ -50,8 +51,9 @@ impl UCI {
     /// 4. Enters the main UCI command loop (`run`), reading from stdin.
     /// This function takes control of the main thread, running the UCI command loop.
     pub fn init() {
-        // initialise magic tables
+        // initialise magic and cuckoo tables
         init_magic_tables();
+        init_cuckoo_tables();
 
         let (tx, rx) = mpsc::channel();
 
@@ -1,12 +1,20 @@
 use std::sync::{Arc, atomic::AtomicBool, mpsc::Receiver};
 
 use chess::{
+    Move,
     board::Board,
-    utils::{perft_bench, perft_test},
+    book::PolyglotBook,
+    utils::{PERFT_HASH_SIZE, PRNG, perft_bench, perft_stats_test, perft_test},
 };
 
 // Import local modules (evaluation, threading, transposition table).
-use crate::{evaluate_nnue, search::TT, thread::ThreadPool, time::TimeControl};
+use crate::{
+    evaluate_nnue,
+    search::TT,
+    syzygy::{TB_PIECES, Tablebases},
+    thread::ThreadPool,
+    time::TimeControl,
+};
 
 use super::EngineOption;
 use super::constants::*;
@@ -26,6 +34,12 @@ pub(crate) struct EngineController {
     tt: TT,
     /// Manages the pool of search worker threads.
     thread_pool: ThreadPool,
+    /// Whether moves should be played from the opening book when available.
+    own_book: bool,
+    /// The loaded Polyglot opening book, if any.
+    book: Option<PolyglotBook>,
+    /// Random number generator used for weighted book move selection.
+    rng: PRNG,
 }
 
 impl EngineController {
@@ -36,12 +50,16 @@ impl EngineController {
         let mut thread_pool = ThreadPool::new(stop);
 
         thread_pool.resize(THREADS);
+        thread_pool.set_debug(DEBUG);
 
         Self {
             is_debug: DEBUG,
             board: Board::default(),
             tt,
             thread_pool,
+            own_book: false,
+            book: None,
+            rng: PRNG::new(book_seed()),
         }
     }
 
@@ -67,6 +85,7 @@ impl EngineController {
             Command::Position(board) => self.set_position(board),
             Command::Go(time_control) => self.go(time_control),
             Command::Perft(depth) => self.perft(depth),
+            Command::PerftStats(depth) => self.perft_stats(depth),
             Command::Bench => self.bench(),
             Command::Print => self.print_board(),
             Command::Eval => self.evaluate(),
@@ -84,6 +103,12 @@ impl EngineController {
         println!("option name ClearHash type button");
         println!("option name Hash type spin default 32 min 1 max 128");
         println!("option name Threads type spin default 1 min 1 max 12");
+        println!("option name OwnBook type check default false");
+        println!("option name BookFile type string default <empty>");
+        println!("option name SyzygyPath type string default <empty>");
+        println!(
+            "option name SyzygyProbeLimit type spin default {SYZYGY_PROBE_LIMIT} min 0 max {TB_PIECES}"
+        );
 
         #[cfg(feature = "tune")]
         println!("{}", spsa_output_opts());
@@ -93,6 +118,7 @@ impl EngineController {
 
     fn set_debug(&mut self, is_debug: bool) {
         self.is_debug = is_debug;
+        self.thread_pool.set_debug(is_debug);
     }
 
     /// Handles the "ucinewgame" command: Resets the board to the default starting position.
@@ -150,6 +176,54 @@ impl EngineController {
         }
     }
 
+    /// Loads the Polyglot opening book at the given path, `<empty>` unloads the current book.
+    fn set_book_file(&mut self, path: &str) {
+        if path.is_empty() || path == "<empty>" {
+            self.book = None;
+            return;
+        }
+
+        match PolyglotBook::open(path) {
+            Ok(book) => {
+                if self.is_debug {
+                    println!(
+                        "info string Loaded book {path} with {} entries.",
+                        book.len()
+                    );
+                }
+                self.book = Some(book);
+            }
+            Err(e) => {
+                println!("info string Failed to load book {path}: {e}");
+                self.book = None;
+            }
+        }
+    }
+
+    /// Loads the Syzygy tablebases found in the given directories, `<empty>` unloads them.
+    fn set_syzygy_path(&mut self, paths: &str) {
+        let tb = Tablebases::new(paths);
+
+        if !tb.is_empty() {
+            println!(
+                "info string Found {} tablebases with up to {} pieces.",
+                tb.len(),
+                tb.max_pieces()
+            );
+        }
+
+        self.thread_pool.set_tablebases(tb);
+    }
+
+    /// Sets the maximum number of pieces for tablebase probes during search.
+    fn set_syzygy_probe_limit(&mut self, limit: usize) {
+        if limit > TB_PIECES {
+            println!("info string SyzygyProbeLimit spin value out of bounds (0 to {TB_PIECES}).");
+        }
+
+        self.thread_pool.set_tb_probe_limit(limit.min(TB_PIECES));
+    }
+
     #[cfg(feature = "tune")]
     fn set_tunable(&mut self, tunable_name: &str, val: &str) {
         if let Err(e) = set_tunable(&tunable_name, &val) {
@@ -166,6 +240,10 @@ impl EngineController {
             EngineOption::ClearHash => self.clear_hash(),
             EngineOption::ResizeHash(size_mb) => self.resize_hash(size_mb),
             EngineOption::ResizeThreads(threads) => self.resize_threads(threads),
+            EngineOption::OwnBook(own_book) => self.own_book = own_book,
+            EngineOption::BookFile(path) => self.set_book_file(&path),
+            EngineOption::SyzygyPath(paths) => self.set_syzygy_path(&paths),
+            EngineOption::SyzygyProbeLimit(limit) => self.set_syzygy_probe_limit(limit),
             #[cfg(feature = "tune")]
             EngineOption::SetTunable(tunable_name, val) => self.set_tunable(&tunable_name, &val),
         }
@@ -177,20 +255,43 @@ impl EngineController {
     }
 
     /// Handles the "go" command: Starts the search process with the given time control.
+    /// The opening book is consulted first, unless searching infinitely where `bestmove` must wait for `stop`.
     fn go(&mut self, time_control: TimeControl) {
+        if let Some(book_move) = self.book_move(&time_control) {
+            println!("bestmove {}", book_move.to_str(&self.board));
+            return;
+        }
+
         self.tt.increment_age();
         self.thread_pool
             .start_search(time_control, &self.tt, &self.board);
     }
 
+    /// Picks a weighted random move from the opening book, if enabled and the position is in the book.
+    fn book_move(&mut self, time_control: &TimeControl) -> Option<Move> {
+        if !self.own_book || *time_control == TimeControl::Infinite {
+            return None;
+        }
+
+        self.book
+            .as_ref()?
+            .weighted_move(&self.board, &mut self.rng)
+    }
+
     /// Handles the "bench" command (custom): Runs a standard benchmark suite.
     fn bench(&self) {
         perft_bench();
     }
 
-    /// Handles the "perft" command (custom): Runs a performance test for move generation.
+    /// Handles the "perft" command (custom): Runs a performance test for move generation,
+    /// using as many threads as the search.
     fn perft(&mut self, depth: usize) {
-        perft_test(&mut self.board, depth);
+        perft_test(&self.board, depth, self.thread_pool.size(), PERFT_HASH_SIZE);
+    }
+
+    /// Handles the "perft <depth> stats" command (custom): Prints the perft statistics of each depth.
+    fn perft_stats(&mut self, depth: usize) {
+        perft_stats_test(&mut self.board, depth);
     }
 
     /// Handles the "print" or "b" command (custom): Prints the current board to the console.
@@ -202,7 +303,7 @@ impl EngineController {
     fn evaluate(&mut self) {
         println!(
             "NNUE Eval:{}",
-            evaluate_nnue(&self.board, &mut self.thread_pool.main_worker.nnue)
+            evaluate_nnue(&self.board, &mut self.thread_pool.main_worker().nnue)
         )
     }
 
@@ -212,3 +313,11 @@ impl EngineController {
         self.thread_pool.clear_hash_table(&self.tt);
     }
 }
+
+/// Seeds the book move selection from the system clock, so games do not repeat the same line.
+fn book_seed() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as u64)
+        | 1
+}
@@ -1,4 +1,4 @@
-use std::str::{FromStr, SplitWhitespace};
+use std::str::FromStr;
 
 use crate::cli::UCICommandError;
 
@@ -12,6 +12,14 @@ pub enum EngineOption {
     ResizeHash(usize),
     /// Command to change the number of search threads.
     ResizeThreads(usize),
+    /// Command to enable playing moves from the opening book.
+    OwnBook(bool),
+    /// Command to set the path of the Polyglot opening book.
+    BookFile(String),
+    /// Command to set the directories containing Syzygy tablebases.
+    SyzygyPath(String),
+    /// Command to set the maximum number of pieces probed in the tablebases during search.
+    SyzygyProbeLimit(usize),
     /// Temporary option for tunables.
     #[cfg(feature = "tune")]
     SetTunable(String, String),
@@ -19,13 +27,7 @@ pub enum EngineOption {
 
 impl EngineOption {
     /// Helper to parse the value part of a "setoption" command into a specific type.
-    fn parse_value<T: FromStr>(
-        option_name: &str,
-        tokens: SplitWhitespace,
-    ) -> Result<T, UCICommandError> {
-        // Collect the rest as value (values can also have spaces, though less common for standard UCI options)
-        let value_str = tokens.collect::<Vec<&str>>().join(" ");
-
+    fn parse_value<T: FromStr>(option_name: &str, value_str: &str) -> Result<T, UCICommandError> {
         if value_str.is_empty() {
             return Err(UCICommandError(format!(
                 "Missing value for option '{}'",
@@ -39,15 +41,20 @@ impl EngineOption {
     }
 }
 
-impl TryFrom<(String, SplitWhitespace<'_>)> for EngineOption {
+impl TryFrom<(String, &str)> for EngineOption {
     type Error = UCICommandError;
 
-    fn try_from((option_name, tokens): (String, SplitWhitespace)) -> Result<Self, UCICommandError> {
+    /// Builds the option from its name and the raw text after "value"
+    fn try_from((option_name, value): (String, &str)) -> Result<Self, UCICommandError> {
         let option = match option_name.to_ascii_lowercase().as_str() {
-            "uci_chess960" => Self::Chess960(Self::parse_value(&option_name, tokens)?),
+            "uci_chess960" => Self::Chess960(Self::parse_value(&option_name, value)?),
             "clear hash" => Self::ClearHash,
-            "hash" => Self::ResizeHash(Self::parse_value(&option_name, tokens)?),
-            "threads" => Self::ResizeThreads(Self::parse_value(&option_name, tokens)?),
+            "hash" => Self::ResizeHash(Self::parse_value(&option_name, value)?),
+            "threads" => Self::ResizeThreads(Self::parse_value(&option_name, value)?),
+            "ownbook" => Self::OwnBook(Self::parse_value(&option_name, value)?),
+            "bookfile" => Self::BookFile(value.to_string()),
+            "syzygypath" => Self::SyzygyPath(value.to_string()),
+            "syzygyprobelimit" => Self::SyzygyProbeLimit(Self::parse_value(&option_name, value)?),
 
             #[cfg(not(feature = "tune"))]
             _ => {
@@ -58,7 +65,7 @@ impl TryFrom<(String, SplitWhitespace<'_>)> for EngineOption {
             }
 
             #[cfg(feature = "tune")]
-            _ => Self::SetTunable(option_name, tokens.collect::<Vec<&str>>().join(" ")),
+            _ => Self::SetTunable(option_name, value.to_string()),
         };
 
         Ok(option)
@@ -17,16 +17,26 @@ pub mod constants {
     pub const THREADS: usize = 1;
     pub const DEBUG: bool = true;
     pub const TT_SIZE: usize = 32;
+    pub const SYZYGY_PROBE_LIMIT: usize = 7;
 
     // Search-related constants.
     pub const MAX_DEPTH: Depth = MAX_MOVES as Depth;
     pub const MIN_DEPTH: Depth = 4;
-    pub const SEARCH_STACK_OFFSET: usize = 2;
+    pub const SEARCH_STACK_OFFSET: usize = 6;
+
+    // Lazy SMP: each helper thread skips the iterations in which `(depth + phase) / size` is odd,
+    // with a different size and phase per thread
+    pub const SMP_SKIP_SIZE: [Depth; 20] =
+        [1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 3, 3, 4, 4, 4, 4, 4, 4, 4, 4];
+    pub const SMP_SKIP_PHASE: [Depth; 20] =
+        [0, 1, 0, 1, 2, 3, 0, 1, 2, 3, 4, 5, 0, 1, 2, 3, 4, 5, 6, 7];
 
     // Moveordering-related constants
     pub const MAX_MAIN_HISTORY: i16 = 16384;
     pub const MOVE_BUFFER_MAX: usize = 32;
-    pub const CONT_HIST_SIZE: usize = SEARCH_STACK_OFFSET;
+    /// How many plies back the moves used by the continuation history were played
+    pub const CONT_HIST_PLIES: [i8; 4] = [1, 2, 4, 6];
+    pub const CONT_HIST_SIZE: usize = CONT_HIST_PLIES.len();
     /// The value of the victim we are capturing
     pub const MVV: [i32; PieceType::NUM] = [0, 2400, 2400, 4800, 9600, 0];
 }
@@ -36,12 +46,54 @@ init_tunables! {
     // value: type = val, min, max, step;
 
     // Late Move Reduction
-    lmr_base: i16 = 768, 500, 2000, 100;
-    lmr_mult: i16 = 2048, 1500, 4000, 100;
+    lmr_base:      i16 = 768, 500, 2000, 100;
+    lmr_mult:      i16 = 2048, 1500, 4000, 100;
+    lmr_min_depth: i16 = 2, 1, 4, 1;
+    lmr_min_moves: i16 = 3, 1, 6, 1;
+
+    // History adjustments to Late Move Reduction
+    lmr_quiet_hist_div:   i32 = 8192, 4096, 16384, 256;
+    lmr_capture_hist_div: i32 = 4096, 2048, 8192, 256;
+
+    // History Pruning
+    hist_prune_depth:  i16 = 4, 2, 6, 1;
+    hist_prune_margin: i32 = 2048, 512, 4096, 128;
+
+    // Razoring
+    razor_depth:  i16 = 3, 1, 6, 1;
+    razor_margin: i32 = 250, 100, 400, 10;
+
+    // Reverse Futility Pruning
+    rfp_depth:     i16 = 8, 4, 12, 1;
+    rfp_margin:    i32 = 80, 40, 140, 5;
+    rfp_improving: i32 = 60, 20, 100, 5;
+    rfp_worsening: i32 = 15, 0, 40, 5;
+
+    // Late Move Pruning
+    lmp_depth: i16 = 8, 4, 12, 1;
+    lmp_base:  i16 = 5, 2, 8, 1;
+    lmp_mult:  i16 = 2, 1, 4, 1;
+
+    // SEE Pruning
+    see_depth:          i16 = 10, 6, 14, 1;
+    see_quiet_margin:   i32 = 70, 30, 120, 5;
+    see_capture_margin: i32 = 20, 5, 40, 2;
+
+    // Singular Extensions
+    se_min_depth:       i16 = 8, 5, 10, 1;
+    se_tt_depth_margin: i16 = 3, 1, 5, 1;
+
+    // History bonus
+    hist_bonus_mult: i32 = 350, 150, 500, 10;
+    hist_bonus_max:  i32 = 1600, 1000, 2500, 50;
 
     // Null Move Pruning
-    nmp_min: i16 = 4, 2, 6, 1;
-    nmp_div: i16 = 4, 2, 6, 1;
+    nmp_min_depth: i16 = 2, 1, 4, 1;
+    nmp_min:       i16 = 4, 2, 6, 1;
+    nmp_div:       i16 = 4, 2, 6, 1;
+
+    // Quiescence search futility and delta pruning
+    qs_futility_margin: i32 = 200, 50, 400, 10;
 
     // Piece values
     pawn_val:   i32 = 82, 60, 140, 5;
@@ -52,4 +104,20 @@ init_tunables! {
 
     // NNUE scaling value
     nnue_base: i32 = 700, 600, 800, 10;
+
+    // Continuation history weights, for the moves 1, 2, 4 and 6 plies back
+    cont1_weight: i32 = 1024, 512, 1536, 64;
+    cont2_weight: i32 = 1024, 512, 1536, 64;
+    cont4_weight: i32 = 512, 0, 1024, 64;
+    cont6_weight: i32 = 384, 0, 1024, 64;
+
+    // Pawn and low ply history weights
+    pawn_hist_weight:   i32 = 512, 0, 1024, 64;
+    low_ply_weight:     i32 = 2048, 0, 4096, 128;
+    low_ply_root_bonus: i32 = 800, 0, 1600, 50;
+
+    // Correction history weights
+    corr_pawn_weight:     i32 = 512, 256, 1024, 32;
+    corr_non_pawn_weight: i32 = 256, 128, 768, 32;
+    corr_cont_weight:     i32 = 256, 128, 768, 32;
 }
@@ -21,3 +21,28 @@ use nnue::accumulator::Accumulator;
  
      Eval(v as i32)
  }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chess::board::{KILLER_FEN, START_FEN, TRICKY_FEN};
+
+    #[test]
+    fn test_eval_colour_symmetry() {
+        for fen in [
+            START_FEN,
+            TRICKY_FEN,
+            KILLER_FEN,
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+        ] {
+            let board = Board::from_fen(fen).unwrap();
+
+            assert_eq!(
+                evaluate_nnue(&board, &mut Accumulator::default()),
+                evaluate_nnue(&board.flipped(), &mut Accumulator::default()),
+                "{fen}"
+            );
+        }
+    }
+}
@@ -20,6 +20,11 @@ impl Eval {
 
     pub const MATE_BOUND: Eval = Eval(Self::MATE.0 - MAX_DEPTH as i32);
 
+    /// Tablebase wins are scored just below mates
+    pub const TB_WIN: Eval = Eval(Self::MATE_BOUND.0 - 1);
+
+    pub const TB_WIN_BOUND: Eval = Eval(Self::TB_WIN.0 - MAX_DEPTH as i32);
+
     pub fn abs(&self) -> Eval {
         Eval(self.0.abs())
     }
@@ -40,12 +45,20 @@ impl Eval {
         Self::MATE - Eval(ply as i32)
     }
 
+    pub fn tb_win_in(ply: u16) -> Eval {
+        Self::TB_WIN - Eval(ply as i32)
+    }
+
+    pub fn tb_loss_in(ply: u16) -> Eval {
+        -Self::TB_WIN + Eval(ply as i32)
+    }
+
     pub fn from_tt(&self, ply: u16) -> Eval {
         let ply = Eval(ply as i32);
 
-        if *self >= Self::MATE_BOUND {
+        if *self >= Self::TB_WIN_BOUND {
             *self - ply
-        } else if *self <= -Self::MATE_BOUND {
+        } else if *self <= -Self::TB_WIN_BOUND {
             *self + ply
         } else {
             *self
@@ -55,9 +68,9 @@ impl Eval {
     pub fn to_tt(&self, ply: u16) -> Eval {
         let ply = Eval(ply as i32);
 
-        if *self >= Self::MATE_BOUND {
+        if *self >= Self::TB_WIN_BOUND {
             *self + ply
-        } else if *self <= -Self::MATE_BOUND {
+        } else if *self <= -Self::TB_WIN_BOUND {
             *self - ply
         } else {
             *self
@@ -3,6 +3,7 @@ mod engine;
 mod eval;
 mod movepick;
 mod search;
+mod syzygy;
 mod thread;
 pub mod time;
 mod utils;
@@ -11,4 +12,4 @@ pub use engine::*;
 pub use eval::*;
 pub use movepick::*;
 pub use search::*;
-pub use utils::run_bench;
+pub use utils::{run_bench, run_make_book, run_smp_bench};
@@ -1,9 +1,9 @@
-use chess::utils::perft_bench;
+use chess::utils::{perft_bench, run_perft_suite};
 #[cfg(feature = "tune")]
 use engine::tunables::spsa_output_txt;
 
 use engine::cli::UCI;
-use engine::{Depth, run_bench};
+use engine::{Depth, run_bench, run_make_book, run_smp_bench};
 use std::env::args;
 
 const DEFAULT_CMD_BENCH_DEPTH: Depth = 12;
@@ -36,9 +36,36 @@ fn main() {
             }
         }
 
+        Some("smpbench") => {
+            let depth = cli_args
+                .next()
+                .and_then(|s| s.parse::<Depth>().ok())
+                .filter(|&depth| depth > 0)
+                .unwrap_or(DEFAULT_CMD_BENCH_DEPTH);
+
+            run_smp_bench(depth);
+        }
+
         Some("test") => {
             perft_bench();
         }
+
+        Some("perftsuite") => {
+            let Some(path) = cli_args.next() else {
+                println!("info string usage: perftsuite <suite.epd> [max depth]");
+                return;
+            };
+            let max_depth = cli_args
+                .next()
+                .and_then(|s| s.parse::<usize>().ok())
+                .unwrap_or(usize::MAX);
+
+            if let Err(e) = run_perft_suite(path.as_ref(), max_depth) {
+                println!("info string {e}");
+            }
+        }
+
+        Some("makebook") => run_make_book(cli_args),
         _ => UCI::init(),
     }
 }
@@ -1,6 +1,9 @@
-use chess::{Colour, Move, Piece, Square, board::Board};
+use chess::{
+    Colour, Move, Piece, Square,
+    board::{Board, Key},
+};
 
-use crate::{constants::MAX_MAIN_HISTORY, eval::Eval};
+use crate::{Depth, constants::MAX_MAIN_HISTORY, eval::Eval, tunables::*};
 
 /******************************************\
 |==========================================|
@@ -226,6 +229,88 @@ impl CaptureHistory {
 
 impl Interface<CaptureHistoryEntry> for CaptureHistory {}
 
+/******************************************\
+|==========================================|
+|               Pawn History               |
+|==========================================|
+\******************************************/
+
+/// Number of pawn structure buckets of the pawn history
+pub const PAWN_HISTORY_SIZE: usize = 512;
+
+type PawnHistoryEntry = Entry<MAX_MAIN_HISTORY>;
+
+define_history!(
+    PawnHistory,
+    PawnHistoryEntry,
+    [PAWN_HISTORY_SIZE, Piece::NUM, Square::NUM]
+);
+
+impl_history_probe!(PawnHistory, PawnHistoryEntry, [bucket, piece, square]);
+
+impl PawnHistory {
+    /// Helper function to get the indices for the pawn history table.
+    #[inline(always)]
+    fn get_indices(board: &Board, move_: Move) -> (usize, usize, usize) {
+        // Safety: Assumes the 'from' square is occupied, which should be true for valid moves.
+        let moved_piece = unsafe { board.on_unchecked(move_.from()) };
+        (
+            board.pawn_key() as usize % PAWN_HISTORY_SIZE,
+            moved_piece.index(),
+            move_.to().index(),
+        )
+    }
+}
+
+impl Interface<PawnHistoryEntry> for PawnHistory {}
+
+/******************************************\
+|==========================================|
+|              Low Ply History             |
+|==========================================|
+\******************************************/
+
+/// Number of plies from the root covered by the low ply history
+pub const LOW_PLY_SIZE: usize = 4;
+
+type LowPlyHistoryEntry = Entry<MAX_MAIN_HISTORY>;
+
+define_history!(
+    LowPlyHistory,
+    LowPlyHistoryEntry,
+    [LOW_PLY_SIZE, Square::NUM, Square::NUM]
+);
+
+impl LowPlyHistory {
+    /// Returns the history of a move played `ply` plies from the root, or zero past the covered plies
+    pub fn get(&self, ply: u16, move_: Move) -> Eval {
+        self.probe(ply, move_)
+            .map_or(Eval::ZERO, |entry| entry.get())
+    }
+
+    pub fn update(&mut self, ply: u16, move_: Move, bonus: i16) {
+        if let Some(entry) = self.probe_mut(ply, move_) {
+            entry.update(bonus);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    fn probe(&self, ply: u16, move_: Move) -> Option<&LowPlyHistoryEntry> {
+        self.history
+            .get(ply as usize)
+            .map(|table| &table[move_.from().index()][move_.to().index()])
+    }
+
+    fn probe_mut(&mut self, ply: u16, move_: Move) -> Option<&mut LowPlyHistoryEntry> {
+        self.history
+            .get_mut(ply as usize)
+            .map(|table| &mut table[move_.from().index()][move_.to().index()])
+    }
+}
+
 /******************************************\
 |==========================================|
 |               Continuation               |
@@ -275,3 +360,234 @@ impl ContinuationTable {
         }
     }
 }
+
+/// Returns the weight of the continuation history of the move `CONT_HIST_PLIES[index]` plies back,
+/// out of 1024
+pub fn cont_hist_weight(index: usize) -> i32 {
+    [
+        cont1_weight(),
+        cont2_weight(),
+        cont4_weight(),
+        cont6_weight(),
+    ][index]
+}
+
+/******************************************\
+|==========================================|
+|              Counter Moves               |
+|==========================================|
+\******************************************/
+
+define_history!(CounterMoveTable, Move, [Piece::NUM, Square::NUM]);
+
+impl CounterMoveTable {
+    /// Returns the last quiet move that refuted the previous move
+    pub fn get(&self, piece: Piece, to: Square) -> Move {
+        self.history[piece.index()][to.index()]
+    }
+
+    pub fn update(&mut self, piece: Piece, to: Square, move_: Move) {
+        self.history[piece.index()][to.index()] = move_;
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/******************************************\
+|==========================================|
+|            Correction History            |
+|==========================================|
+\******************************************/
+
+/// Number of entries of each hashed correction history table
+const CORRECTION_SIZE: usize = 16384;
+
+/// Scale of the corrections stored in the entries, which keeps precision for small updates
+const CORRECTION_GRAIN: i32 = 256;
+
+/// Largest correction an entry can hold, in centipawns
+const MAX_CORRECTION: i32 = 64;
+
+/// Scale of the update weights
+const CORRECTION_WEIGHT_SCALE: i32 = 256;
+
+/// Largest update weight, reached at high depths
+const MAX_CORRECTION_WEIGHT: i32 = 16;
+
+/// Number of entries of the continuation correction table, one per pair of previous moves
+const CONT_CORRECTION_SIZE: usize = (Piece::NUM * Square::NUM).pow(2);
+
+/// The piece and destination square of the last two moves, oldest first
+pub type CorrectionKey = [(Piece, Square); 2];
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CorrectionEntry(i16);
+
+impl CorrectionEntry {
+    /// Returns the scaled correction
+    fn get(self) -> i32 {
+        self.0 as i32
+    }
+
+    /// # Updates correction
+    ///
+    /// - Moves the entry towards the difference between the search result and the static eval
+    /// - Deeper searches are more reliable, so they move the entry further
+    fn update(&mut self, diff: i32, depth: Depth) {
+        let weight = (depth as i32 + 1).min(MAX_CORRECTION_WEIGHT);
+        let target = diff.clamp(-MAX_CORRECTION, MAX_CORRECTION) * CORRECTION_GRAIN;
+        let entry = (self.get() * (CORRECTION_WEIGHT_SCALE - weight) + target * weight)
+            / CORRECTION_WEIGHT_SCALE;
+
+        self.0 = entry.clamp(
+            -MAX_CORRECTION * CORRECTION_GRAIN,
+            MAX_CORRECTION * CORRECTION_GRAIN,
+        ) as i16;
+    }
+}
+
+/// # Correction History
+///
+/// Learns how far the static eval is from the search result for similar positions,
+/// telling positions apart by their pawn structure, each side's non-pawn pieces and the
+/// last two moves played.
+#[derive(Debug, Clone)]
+pub struct CorrectionHistory {
+    /// Indexed by the side to move and the pawn key
+    pawn: Box<[CorrectionEntry]>,
+    /// Indexed by the side owning the pieces, the side to move and its non-pawn key
+    non_pawn: [Box<[CorrectionEntry]>; Colour::NUM],
+    /// Indexed by the last two moves
+    continuation: Box<[CorrectionEntry]>,
+}
+
+impl Default for CorrectionHistory {
+    fn default() -> Self {
+        let table = |size| vec![CorrectionEntry::default(); size].into_boxed_slice();
+
+        Self {
+            pawn: table(Colour::NUM * CORRECTION_SIZE),
+            non_pawn: [
+                table(Colour::NUM * CORRECTION_SIZE),
+                table(Colour::NUM * CORRECTION_SIZE),
+            ],
+            continuation: table(CONT_CORRECTION_SIZE),
+        }
+    }
+}
+
+impl CorrectionHistory {
+    /// Returns the correction to add to the static eval of the board
+    pub fn correction(&self, board: &Board, key: Option<CorrectionKey>) -> Eval {
+        let stm = board.stm();
+
+        let pawn = self.pawn[hashed_index(stm, board.pawn_key())].get();
+        let non_pawn = [Colour::White, Colour::Black]
+            .into_iter()
+            .map(|col| self.non_pawn[col.index()][hashed_index(stm, board.non_pawn_key(col))].get())
+            .sum::<i32>();
+        let continuation = key.map_or(0, |key| self.continuation[continuation_index(key)].get());
+
+        let correction = pawn * corr_pawn_weight()
+            + non_pawn * corr_non_pawn_weight()
+            + continuation * corr_cont_weight();
+
+        Eval(correction / (1024 * CORRECTION_GRAIN))
+    }
+
+    /// Updates every table with the difference between the search result and the static eval
+    pub fn update(&mut self, board: &Board, key: Option<CorrectionKey>, diff: Eval, depth: Depth) {
+        let stm = board.stm();
+
+        self.pawn[hashed_index(stm, board.pawn_key())].update(diff.0, depth);
+
+        for col in [Colour::White, Colour::Black] {
+            self.non_pawn[col.index()][hashed_index(stm, board.non_pawn_key(col))]
+                .update(diff.0, depth);
+        }
+
+        if let Some(key) = key {
+            self.continuation[continuation_index(key)].update(diff.0, depth);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[inline(always)]
+fn hashed_index(stm: Colour, key: Key) -> usize {
+    stm.index() * CORRECTION_SIZE + key as usize % CORRECTION_SIZE
+}
+
+#[inline(always)]
+fn continuation_index([(piece2, to2), (piece1, to1)]: CorrectionKey) -> usize {
+    let index2 = piece2.index() * Square::NUM + to2.index();
+    let index1 = piece1.index() * Square::NUM + to1.index();
+
+    index2 * Piece::NUM * Square::NUM + index1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pawn_and_low_ply_history() {
+        use chess::MoveFlag;
+
+        let board = Board::default();
+        let move_ = Move::new(Square::G1, Square::F3, MoveFlag::QuietMove);
+
+        // Pawn history is kept apart for each pawn structure
+        let mut ph = PawnHistory::default();
+        ph.update(&board, move_, 1000);
+        assert_eq!(ph.get(&board, move_), Eval(1000));
+
+        let other =
+            Board::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 1").unwrap();
+        assert_eq!(ph.get(&other, move_), Eval::ZERO);
+
+        // Low ply history only covers the first plies from the root
+        let mut lph = LowPlyHistory::default();
+        lph.update(1, move_, 1000);
+        lph.update(LOW_PLY_SIZE as u16, move_, 1000);
+
+        assert_eq!(lph.get(0, move_), Eval::ZERO);
+        assert_eq!(lph.get(1, move_), Eval(1000));
+        assert_eq!(lph.get(LOW_PLY_SIZE as u16, move_), Eval::ZERO);
+    }
+
+    #[test]
+    fn test_correction_history() {
+        let board = Board::default();
+        let mut corr = CorrectionHistory::default();
+
+        assert_eq!(corr.correction(&board, None), Eval::ZERO);
+
+        for _ in 0..200 {
+            corr.update(&board, None, Eval(40), 20);
+        }
+
+        // Without a continuation key, the pawn and both non-pawn tables converge to the difference
+        let expected = 40 * (corr_pawn_weight() + 2 * corr_non_pawn_weight()) / 1024;
+        assert!((corr.correction(&board, None).0 - expected).abs() <= 1);
+
+        // Corrections are learnt separately for each side to move
+        let flipped = board.flipped();
+        assert_eq!(corr.correction(&flipped, None), Eval::ZERO);
+
+        // Large differences are clamped
+        for _ in 0..200 {
+            corr.update(&board, None, Eval(-5000), 20);
+        }
+        let expected = -MAX_CORRECTION * (corr_pawn_weight() + 2 * corr_non_pawn_weight()) / 1024;
+        assert!((corr.correction(&board, None).0 - expected).abs() <= 1);
+
+        corr.clear();
+        assert_eq!(corr.correction(&board, None), Eval::ZERO);
+    }
+}
@@ -13,7 +13,12 @@ pub enum MoveStage {
     GoodCaptures,
     Killer1,
     Killer2,
+    CounterMove,
     GenQuiets,
     Quiets,
     BadCaptures,
+    GenQuietChecks,
+    QuietChecks,
+    GenEvasions,
+    Evasions,
 }
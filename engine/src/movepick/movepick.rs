@@ -1,6 +1,6 @@
 use chess::{
     Move, MoveFlag, PieceType,
-    board::{Board, CaptureGen, MoveList, QuietGen},
+    board::{Board, CaptureGen, EvasionGen, MoveList, QuietCheckGen, QuietGen},
 };
 
 use crate::{
@@ -15,10 +15,13 @@ use super::{MoveStage, see::see};
 
 pub struct MovePicker<const TACTICAL: bool> {
     pub stage: MoveStage,
+    ply: u16,
     skip_quiets: bool,
+    quiet_checks: bool,
 
     tt_move: Move,
     killers: [Move; 2],
+    counter: Move,
 
     // Scored move list items
     move_list: MoveList,
@@ -29,15 +32,25 @@ pub struct MovePicker<const TACTICAL: bool> {
     bad_cap_start: usize,
 }
 
+/// Puts every capture ahead of the quiet moves when evading a check
+const EVASION_CAPTURE_BONUS: i32 = 1 << 20;
+
 fn captured_value(captured: PieceType) -> i32 {
     MVV[captured.index()]
 }
 
 impl<const TACTICAL: bool> MovePicker<TACTICAL> {
-    pub fn new(board: &Board, mut tt_move: Move, mut killers: [Move; 2]) -> Self {
+    pub fn new(
+        board: &Board,
+        ply: u16,
+        mut tt_move: Move,
+        mut killers: [Move; 2],
+        mut counter: Move,
+    ) -> Self {
         let in_check = board.in_check();
 
-        if TACTICAL && !in_check && board.is_capture(tt_move) {
+        // Out of check, a tactical move picker only tries captures
+        if TACTICAL && !in_check && !tt_move.is_capture() {
             tt_move = Move::NONE;
         }
 
@@ -51,11 +64,23 @@ impl<const TACTICAL: bool> MovePicker<TACTICAL> {
             killers[1] = Move::NONE;
         }
 
+        // A valid counter move must be legal, a non capture and not already tried as a killer
+        if !counter.is_valid()
+            || !board.is_legal(counter)
+            || board.is_capture(counter)
+            || killers.contains(&counter)
+        {
+            counter = Move::NONE;
+        }
+
         Self {
             stage: MoveStage::TTMove,
+            ply,
             skip_quiets: !in_check && TACTICAL,
+            quiet_checks: false,
             tt_move,
             killers,
+            counter,
             move_list: MoveList::new(),
             scores: [0; 256],
             index: 0,
@@ -68,24 +93,33 @@ impl<const TACTICAL: bool> MovePicker<TACTICAL> {
         self.skip_quiets = true;
     }
 
+    /// Also tries the quiet moves giving check after the captures, when not in check
+    pub fn include_quiet_checks(&mut self) {
+        self.quiet_checks = true;
+    }
+
+    fn capture_score(board: &Board, stats: &SearchStats, move_: Move) -> i32 {
+        let captured = match move_.flag() {
+            MoveFlag::EPCapture => PieceType::Pawn,
+            _ => unsafe { board.on_unchecked(move_.to()).pt() },
+        };
+
+        let mut score = captured_value(captured) + stats.cht.get(board, move_).0 as i32;
+
+        if move_.is_promotion() {
+            score += captured_value(unsafe { move_.promotion_pt() });
+        }
+
+        score
+    }
+
     fn score_captures(&mut self, board: &Board, stats: &SearchStats) {
         let mut next_good_cap = 0;
 
         for i in 0..self.move_list.len() {
             let move_ = self.move_list[i];
 
-            let captured = match move_.flag() {
-                MoveFlag::EPCapture => PieceType::Pawn,
-                _ => unsafe { board.on_unchecked(move_.to()).pt() },
-            };
-
-            let mut score = captured_value(captured) + stats.cht.get(board, move_).0 as i32;
-
-            if move_.is_promotion() {
-                score += captured_value(unsafe { move_.promotion_pt() });
-            }
-
-            self.scores[i] = score;
+            self.scores[i] = Self::capture_score(board, stats, move_);
 
             if see(board, move_, Eval::ZERO) {
                 self.scores.swap(i, next_good_cap);
@@ -106,12 +140,7 @@ impl<const TACTICAL: bool> MovePicker<TACTICAL> {
         for i in self.quiet_start..self.move_list.len() {
             let move_ = self.move_list[i];
 
-            self.scores[i] = stats.ht.get(board, move_).0 as i32;
-
-            for i in 0..CONT_HIST_SIZE {
-                let (piece, to) = ss_buffer[i].piece_to();
-                self.scores[i] += stats.ct.get_entry_ref(piece, to).get(board, move_).0 as i32;
-            }
+            self.scores[i] = stats.quiet_history(board, self.ply, move_, ss_buffer);
         }
     }
 
@@ -127,6 +156,41 @@ impl<const TACTICAL: bool> MovePicker<TACTICAL> {
         self.partial_sort(self.quiet_start, self.move_list.len());
     }
 
+    fn gen_quiet_checks(
+        &mut self,
+        board: &Board,
+        stats: &SearchStats,
+        ss_buffer: &[SearchStackEntry; CONT_HIST_SIZE],
+    ) {
+        self.quiet_start = self.move_list.len();
+        board.generate_moves::<QuietCheckGen>(&mut self.move_list);
+
+        self.score_quiets(board, stats, ss_buffer);
+        self.partial_sort(self.quiet_start, self.move_list.len());
+    }
+
+    fn gen_evasions(
+        &mut self,
+        board: &Board,
+        stats: &SearchStats,
+        ss_buffer: &[SearchStackEntry; CONT_HIST_SIZE],
+    ) {
+        board.generate_moves::<EvasionGen>(&mut self.move_list);
+
+        // Captures of the checking piece come first, then the quiet moves by history
+        for i in 0..self.move_list.len() {
+            let move_ = self.move_list[i];
+
+            self.scores[i] = if move_.is_capture() {
+                EVASION_CAPTURE_BONUS + Self::capture_score(board, stats, move_)
+            } else {
+                stats.quiet_history(board, self.ply, move_, ss_buffer)
+            };
+        }
+
+        self.partial_sort(0, self.move_list.len());
+    }
+
     fn gen_captures(&mut self, board: &Board, stats: &SearchStats) {
         board.generate_moves::<CaptureGen>(&mut self.move_list);
         self.quiet_start = self.move_list.len();
@@ -180,13 +244,18 @@ impl<const TACTICAL: bool> MovePicker<TACTICAL> {
         stats: &SearchStats,
         ss_buffer: &[SearchStackEntry; CONT_HIST_SIZE],
     ) -> Option<Move> {
-        let killers = self.killers;
+        let (killers, counter) = (self.killers, self.counter);
         let cap_pred = |_| true;
-        let quiet_pred = |move_: Move| move_ != killers[0] && move_ != killers[1];
+        let quiet_pred =
+            |move_: Move| move_ != killers[0] && move_ != killers[1] && move_ != counter;
 
         match self.stage {
             MoveStage::TTMove => {
-                self.stage = MoveStage::GenCaptures;
+                self.stage = if TACTICAL && board.in_check() {
+                    MoveStage::GenEvasions
+                } else {
+                    MoveStage::GenCaptures
+                };
                 if self.tt_move.is_valid() {
                     Some(self.tt_move)
                 } else {
@@ -223,7 +292,7 @@ impl<const TACTICAL: bool> MovePicker<TACTICAL> {
                 }
             }
             MoveStage::Killer2 => {
-                self.stage = MoveStage::GenQuiets;
+                self.stage = MoveStage::CounterMove;
                 if !self.skip_quiets
                     && self.killers[1].is_valid()
                     && self.killers[1] != self.tt_move
@@ -233,6 +302,14 @@ impl<const TACTICAL: bool> MovePicker<TACTICAL> {
                     self.next(board, stats, ss_buffer)
                 }
             }
+            MoveStage::CounterMove => {
+                self.stage = MoveStage::GenQuiets;
+                if !self.skip_quiets && self.counter.is_valid() && self.counter != self.tt_move {
+                    Some(self.counter)
+                } else {
+                    self.next(board, stats, ss_buffer)
+                }
+            }
             MoveStage::GenQuiets => {
                 if !self.skip_quiets {
                     self.gen_quiets(board, stats, ss_buffer);
@@ -258,10 +335,27 @@ impl<const TACTICAL: bool> MovePicker<TACTICAL> {
             MoveStage::BadCaptures => {
                 if let Some(move_) = self.next_best(self.quiet_start, cap_pred) {
                     Some(move_)
+                } else if self.quiet_checks {
+                    self.stage = MoveStage::GenQuietChecks;
+                    self.next(board, stats, ss_buffer)
                 } else {
                     None
                 }
             }
+            MoveStage::GenQuietChecks => {
+                self.stage = MoveStage::QuietChecks;
+                self.gen_quiet_checks(board, stats, ss_buffer);
+                self.index = self.quiet_start;
+                self.next(board, stats, ss_buffer)
+            }
+            MoveStage::QuietChecks => self.next_best(self.move_list.len(), |_| true),
+            MoveStage::GenEvasions => {
+                self.stage = MoveStage::Evasions;
+                self.gen_evasions(board, stats, ss_buffer);
+                self.index = 0;
+                self.next(board, stats, ss_buffer)
+            }
+            MoveStage::Evasions => self.next_best(self.move_list.len(), |_| true),
         }
     }
 }
@@ -314,7 +408,7 @@ mod tests {
         ("qbrnnkbr/pppppppp/8/8/8/8/PPPPPPPP/QBRNNKBR w KQkq - 0 1", 6, 121613156),
     ];
 
-    fn perft(board: &mut Board, depth: usize) -> usize {
+    fn perft(board: &mut Board, depth: usize, search_stats: &SearchStats) -> usize {
         let mut move_list = MoveList::new();
 
         board.generate_moves::<LegalGen>(&mut move_list);
@@ -324,16 +418,15 @@ mod tests {
         }
 
         let mut move_picker =
-            MovePicker::<false>::new(&board, Move::NONE, [Move::NONE, Move::NONE]);
-        let search_stats = SearchStats::default();
+            MovePicker::<false>::new(&board, 0, Move::NONE, [Move::NONE, Move::NONE], Move::NONE);
 
         let ss_buffer = [SearchStackEntry::default(); CONT_HIST_SIZE];
 
         let mut nodes = 0;
 
-        while let Some(move_) = move_picker.next(board, &search_stats, &ss_buffer) {
+        while let Some(move_) = move_picker.next(board, search_stats, &ss_buffer) {
             board.make_move(move_);
-            nodes += perft(board, depth - 1);
+            nodes += perft(board, depth - 1, search_stats);
             board.undo_move(move_);
         }
 
@@ -345,10 +438,12 @@ mod tests {
 
         println!("=============  START BENCH  =============");
 
+        let search_stats = SearchStats::default();
+
         for (fen, depth, expected_nodes) in BENCH_LIST.iter() {
             let start = Instant::now();
             let mut board = Board::from_fen(fen).unwrap();
-            let nodes = perft(&mut board, *depth);
+            let nodes = perft(&mut board, *depth, &search_stats);
 
             let status: &str = if nodes == *expected_nodes {
                 "PASSED"
@@ -371,4 +466,78 @@ mod tests {
     fn test_perft() {
         perft_bench();
     }
+
+    fn picked_moves(board: &Board, mut mp: MovePicker<true>) -> Vec<Move> {
+        let stats = SearchStats::default();
+        let ss_buffer = [SearchStackEntry::default(); CONT_HIST_SIZE];
+
+        let mut moves = Vec::new();
+        while let Some(move_) = mp.next(board, &stats, &ss_buffer) {
+            moves.push(move_);
+        }
+        moves
+    }
+
+    #[test]
+    fn test_tactical_stages() {
+        use chess::{MoveFlag, Square};
+
+        // In check, every evasion is tried once, with the capture of the checker first
+        let board = Board::from_fen("4k3/8/8/8/8/8/3q4/4K3 w - - 0 1").unwrap();
+        let mp = MovePicker::<true>::new(&board, 0, Move::NONE, [Move::NONE; 2], Move::NONE);
+        let moves = picked_moves(&board, mp);
+
+        let capture = Move::new(Square::E1, Square::D2, MoveFlag::Capture);
+        let retreat = Move::new(Square::E1, Square::F1, MoveFlag::QuietMove);
+        assert_eq!(moves, vec![capture, retreat]);
+
+        // Out of check, the quiet checks follow the captures when requested
+        let board = Board::from_fen("4k3/8/8/8/8/8/1p6/R3K3 w - - 0 1").unwrap();
+        let mp = MovePicker::<true>::new(&board, 0, Move::NONE, [Move::NONE; 2], Move::NONE);
+        assert_eq!(picked_moves(&board, mp).len(), 0);
+
+        let mut mp = MovePicker::<true>::new(&board, 0, Move::NONE, [Move::NONE; 2], Move::NONE);
+        mp.include_quiet_checks();
+        let moves = picked_moves(&board, mp);
+
+        let check = Move::new(Square::A1, Square::A8, MoveFlag::QuietMove);
+        assert_eq!(moves, vec![check]);
+
+        // A quiet hash move is not tried out of check
+        let tt_move = Move::new(Square::E1, Square::F1, MoveFlag::QuietMove);
+        let mp = MovePicker::<true>::new(&board, 0, tt_move, [Move::NONE; 2], Move::NONE);
+        assert!(picked_moves(&board, mp).is_empty());
+    }
+
+    #[test]
+    fn test_counter_move_stage() {
+        use chess::{MoveFlag, Square};
+
+        let board = Board::default();
+        let stats = SearchStats::default();
+        let ss_buffer = [SearchStackEntry::default(); CONT_HIST_SIZE];
+
+        let killer = Move::new(Square::E2, Square::E4, MoveFlag::DoublePawnPush);
+        let counter = Move::new(Square::G1, Square::F3, MoveFlag::QuietMove);
+
+        let mut mp = MovePicker::<false>::new(&board, 0, Move::NONE, [killer, Move::NONE], counter);
+
+        // The counter move is tried right after the killers, and only once
+        assert_eq!(mp.next(&board, &stats, &ss_buffer), Some(killer));
+        assert_eq!(mp.next(&board, &stats, &ss_buffer), Some(counter));
+        assert_eq!(mp.stage, MoveStage::GenQuiets);
+
+        let mut rest = Vec::new();
+        while let Some(move_) = mp.next(&board, &stats, &ss_buffer) {
+            rest.push(move_);
+        }
+        assert_eq!(rest.len(), 18);
+        assert!(!rest.contains(&killer) && !rest.contains(&counter));
+
+        // A counter move that duplicates a killer is discarded
+        let mut mp =
+            MovePicker::<false>::new(&board, 0, Move::NONE, [counter, Move::NONE], counter);
+        assert_eq!(mp.next(&board, &stats, &ss_buffer), Some(counter));
+        assert_ne!(mp.next(&board, &stats, &ss_buffer), Some(counter));
+    }
 }
@@ -10,8 +10,14 @@ pub use pv::PVLine;
 pub(crate) use stack::{SearchStack, SearchStackEntry};
 pub use tt::TT;
 
-use crate::{CaptureHistory, ContinuationTable, Depth, Eval, MainHistory, time::Clock};
-use chess::board::Board;
+use std::sync::{Arc, atomic::AtomicU64};
+
+use crate::{
+    CaptureHistory, ContinuationTable, CorrectionHistory, CounterMoveTable, Depth, Eval, Interface,
+    LowPlyHistory, MainHistory, PawnHistory, constants::CONT_HIST_SIZE, cont_hist_weight,
+    syzygy::Tablebases, time::Clock, tunables::*,
+};
+use chess::{Move, board::Board};
 use nnue::accumulator::Accumulator;
 
 #[derive(Debug, Clone, Default)]
@@ -19,6 +25,55 @@ pub struct SearchStats {
     pub ht: MainHistory,
     pub cht: CaptureHistory,
     pub ct: Box<ContinuationTable>,
+    pub cm: CounterMoveTable,
+    pub ph: Box<PawnHistory>,
+    pub lph: LowPlyHistory,
+    pub corr: CorrectionHistory,
+}
+
+impl SearchStats {
+    /// Returns the main history plus the weighted pawn, low ply and continuation histories
+    /// of a quiet move played `ply` plies from the root
+    pub(crate) fn quiet_history(
+        &self,
+        board: &Board,
+        ply: u16,
+        move_: Move,
+        ss_buffer: &[SearchStackEntry; CONT_HIST_SIZE],
+    ) -> i32 {
+        let mut score = self.ht.get(board, move_).0;
+
+        score += self.ph.get(board, move_).0 * pawn_hist_weight() / 1024;
+        // The low ply history matters less the further the move is from the root
+        score += self.lph.get(ply, move_).0 * low_ply_weight() / (1024 * (1 + ply as i32));
+
+        for (i, entry) in ss_buffer.iter().enumerate() {
+            if !entry.curr_move.is_valid() {
+                continue;
+            }
+
+            let (piece, to) = entry.piece_to();
+            let cont = self.ct.get_entry_ref(piece, to).get(board, move_).0;
+            score += cont * cont_hist_weight(i) / 1024;
+        }
+
+        score
+    }
+
+    /// Returns the capture history of a capture, or the quiet history of any other move
+    pub(crate) fn history(
+        &self,
+        board: &Board,
+        ply: u16,
+        move_: Move,
+        ss_buffer: &[SearchStackEntry; CONT_HIST_SIZE],
+    ) -> i32 {
+        if move_.is_capture() {
+            self.cht.get(board, move_).0
+        } else {
+            self.quiet_history(board, ply, move_, ss_buffer)
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -36,6 +91,8 @@ pub(crate) struct SearchWorker {
     // Search Info
     nodes: u64,
     pub depth: Depth,
+    // Depth of the last fully searched iteration
+    completed_depth: Depth,
     seldepth: Depth,
     ply: u16,
     // Plies from previous null move
@@ -53,6 +110,14 @@ pub(crate) struct SearchWorker {
 
     // NNUE
     pub nnue: Accumulator,
+
+    // Syzygy tablebases
+    tb: Arc<Tablebases>,
+    tb_hits: Arc<AtomicU64>,
+    // Maximum number of pieces probed during search
+    tb_probe_limit: usize,
+    // Root moves preserving the tablebase result, empty if the root is not in the tablebases
+    root_moves: Vec<Move>,
 }
 
 pub trait NodeType {
@@ -2,22 +2,26 @@ use chess::Move;
 
 use crate::{
     Depth, SearchWorker,
-    constants::{CONT_HIST_SIZE, MAX_DEPTH},
+    constants::{CONT_HIST_PLIES, MAX_DEPTH},
     eval::Eval,
     movepick::MovePicker,
-    search::{PVLine, stack::SearchStackEntry},
+    search::PVLine,
     see,
+    tunables::*,
 };
 
 use super::{NodeType, TT, tt::TTBound, utils::*};
 
 impl SearchWorker {
+    /// Searches the captures, evasions and, at the first ply (`depth == 0`), the quiet checks
+    /// until the position is quiet. `depth` counts down from 0 as the quiescence search goes deeper.
     pub(super) fn quiescence<NT: NodeType>(
         &mut self,
         tt: &TT,
         pv: &mut PVLine,
         mut alpha: Eval,
         beta: Eval,
+        depth: Depth,
     ) -> Eval {
         self.seldepth = self.seldepth.max(self.ply as Depth);
 
@@ -44,6 +48,15 @@ impl SearchWorker {
             return Eval::DRAW;
         }
 
+        // Raise alpha to a draw if we can force a repetition
+        if alpha < Eval::DRAW && self.board.upcoming_repetition(self.ply) {
+            alpha = Eval::DRAW;
+
+            if alpha >= beta {
+                return alpha;
+            }
+        }
+
         // --- Hash table probe ---
         let tt_entry = tt.get(self.board.key());
         let mut tt_move = Move::NONE;
@@ -63,15 +76,25 @@ impl SearchWorker {
         // Get the static evaluation of the current position.
         // This score assumes no further captures are made (the "stand pat" score).
         let eval = self.static_eval(in_check, tt_entry);
-        let futility = if in_check {
-            -Eval::INFINITY
-        } else {
-            eval + Eval(-350)
-        };
+        // The best we can hope for from a move, before counting the material it wins
+        let futility_base = eval + Eval(qs_futility_margin());
         // --- Alpha-Beta Pruning based on Stand Pat ---
         // If the static evaluation is already >= beta, the opponent won't allow this position.
         // We can prune immediately, assuming the static eval is a reasonable lower bound.
         if eval >= beta {
+            // Save the static evaluation for positions not in the hash table yet
+            if tt_entry.is_none() {
+                tt.write(
+                    self.board.key(),
+                    TTBound::Lower,
+                    self.ply,
+                    0,
+                    Move::NONE,
+                    self.ss_at(0).raw_eval,
+                    eval,
+                );
+            }
+
             return beta; // Fail-High based on static eval
         }
         // If the static evaluation is better than alpha, update alpha.
@@ -83,21 +106,39 @@ impl SearchWorker {
         let mut child_pv = PVLine::default();
         let mut best_move = Move::NONE;
 
-        // --- Generate and Explore Captures Only ---
-        let ss_buffer = [SearchStackEntry::default(); CONT_HIST_SIZE];
+        // --- Generate and Explore Captures, Evasions and Quiet Checks ---
+        let ss_buffer = CONT_HIST_PLIES.map(|offset| self.ss_at(offset));
+
+        // The generic parameter 'true' tells MovePicker to only generate evasions when in check,
+        // and captures otherwise.
+        let mut move_picker =
+            MovePicker::<true>::new(&self.board, self.ply, tt_move, [Move::NONE; 2], Move::NONE);
 
-        // The generic parameter 'true' tells MovePicker to skip quiet moves.
-        let mut move_picker = MovePicker::<true>::new(&self.board, tt_move, [Move::NONE; 2]);
+        // Quiet checks are only tried at the first ply, to keep the quiescence search small
+        if depth == 0 && !in_check {
+            move_picker.include_quiet_checks();
+        }
 
         while let Some(move_) = move_picker.next(&self.board, &self.stats, &ss_buffer) {
             // --- QS Pruning ---
             if !best_value.is_terminal() {
-                // --- Futility Pruning ---
-                // If the move does not win material and the current static eval is pessimistic enough,
-                // then we ignore the move
-                if in_check && futility <= alpha && !see(&self.board, move_, Eval(1)) {
-                    best_value = best_value.max(futility);
-                    continue;
+                if !in_check && move_.is_capture() && !move_.is_promotion() {
+                    // --- Delta Pruning ---
+                    // If even winning the captured piece cannot raise alpha, then we ignore the move
+                    let futility_value = futility_base + captured_value(&self.board, move_);
+
+                    if futility_value <= alpha {
+                        best_value = best_value.max(futility_value);
+                        continue;
+                    }
+
+                    // --- Futility Pruning ---
+                    // If the move does not win material and the current static eval is pessimistic enough,
+                    // then we ignore the move
+                    if futility_base <= alpha && !see(&self.board, move_, Eval(1)) {
+                        best_value = best_value.max(futility_base);
+                        continue;
+                    }
                 }
 
                 // --- SEE Pruning ---
@@ -106,11 +147,11 @@ impl SearchWorker {
                 }
             }
 
-            // Make the capture
+            // Make the move
             self.make_move(tt, move_);
             // Recursive call
-            let value = -self.quiescence::<NT::Next>(tt, &mut child_pv, -beta, -alpha);
-            // Undo the capture
+            let value = -self.quiescence::<NT::Next>(tt, &mut child_pv, -beta, -alpha, depth - 1);
+            // Undo the move
             self.undo_move(move_);
 
             // Check for stop signal after recursive call
@@ -162,7 +203,7 @@ impl SearchWorker {
             self.ply,
             0,
             best_move,
-            eval,
+            self.ss_at(0).raw_eval,
             best_value,
         );
 
@@ -1,8 +1,15 @@
+use std::sync::atomic::Ordering;
+
 use chess::Move;
 
 use crate::{
-    Depth, PV, SearchWorker, constants::MAX_DEPTH, eval::Eval, movepick::MovePicker,
-    search::PVLine, utils::MoveBuffer,
+    Depth, PV, SearchWorker,
+    constants::{CONT_HIST_PLIES, MAX_DEPTH},
+    eval::Eval,
+    movepick::MovePicker,
+    search::PVLine,
+    syzygy::Wdl,
+    utils::MoveBuffer,
 };
 
 use super::{NodeType, NonPV, Root, TT, tt::TTBound, utils::*};
@@ -12,12 +19,21 @@ impl SearchWorker {
         self.depth = 0;
 
         while self.should_start_iteration() {
+            if self.should_skip_iteration() {
+                self.depth += 1;
+                continue;
+            }
+
             self.search_position(tt);
 
             if self.stop {
                 break;
             }
 
+            self.completed_depth = self.depth + 1;
+
+            self.update_root_low_ply();
+
             if self.thread_id == 0 {
                 self.print_info(tt);
             }
@@ -101,7 +117,7 @@ impl SearchWorker {
 
         // --- Quiescence search in base case ---
         if depth == 0 && !in_check {
-            return self.quiescence::<NT::Next>(tt, pv, alpha, beta);
+            return self.quiescence::<NT::Next>(tt, pv, alpha, beta, 0);
         }
 
         if !NT::ROOT {
@@ -115,6 +131,15 @@ impl SearchWorker {
                 return Eval::DRAW;
             }
 
+            // Raise alpha to a draw if we can force a repetition
+            if alpha < Eval::DRAW && self.board.upcoming_repetition(self.ply) {
+                alpha = Eval::DRAW;
+
+                if alpha >= beta {
+                    return alpha;
+                }
+            }
+
             alpha = alpha.max(Eval::mated_in(self.ply));
             beta = beta.min(Eval::mate_in(self.ply + 1));
 
@@ -164,6 +189,53 @@ impl SearchWorker {
             tt_depth = tt_entry.depth as Depth;
         }
 
+        // --- Tablebase Probe ---
+        // With few pieces left the endgame tablebases give the exact result,
+        // right after a capture or pawn move since they ignore the fifty-move counter
+        let mut min_value = -Eval::INFINITY;
+        let mut max_value = Eval::INFINITY;
+        if !NT::ROOT
+            && !singular
+            && self.can_probe_tb()
+            && let Some(wdl) = self.tb.probe_wdl(&mut self.board)
+        {
+            self.tb_hits.fetch_add(1, Ordering::Relaxed);
+
+            let (value, bound) = match wdl {
+                Wdl::Win => (Eval::tb_win_in(self.ply), TTBound::Lower),
+                Wdl::Loss => (Eval::tb_loss_in(self.ply), TTBound::Upper),
+                // Draws, including wins and losses spoiled by the fifty-move rule
+                _ => (Eval(2 * wdl as i32), TTBound::Exact),
+            };
+
+            if bound == TTBound::Exact
+                || (bound == TTBound::Lower && value >= beta)
+                || (bound == TTBound::Upper && value <= alpha)
+            {
+                tt.write(
+                    self.board.key(),
+                    bound,
+                    self.ply,
+                    (depth + 6) as u8,
+                    Move::NONE,
+                    -Eval::INFINITY,
+                    value,
+                );
+
+                return value;
+            }
+
+            // In PV nodes keep searching for the best line, within the proven bounds
+            if NT::PV {
+                if bound == TTBound::Lower {
+                    min_value = value;
+                    alpha = alpha.max(value);
+                } else {
+                    max_value = value;
+                }
+            }
+        }
+
         // Get the best static evaluation of the position
         let eval = self.static_eval(in_check, tt_entry);
         // Set up flags to record trends of the game
@@ -172,10 +244,21 @@ impl SearchWorker {
 
         // --- Pruning ---
         if !NT::PV && !in_check && !singular {
-            // --- Futility Pruning ---
+            // --- Razoring ---
+            // If the eval is far below alpha near the leafs, then only tactical moves
+            // could save the position, so we check it with a quiescence search
+            if self.can_do_razoring(depth, eval, alpha) {
+                let value = self.quiescence::<NonPV>(tt, pv, alpha, alpha + Eval(1), 0);
+
+                if value <= alpha {
+                    return value;
+                }
+            }
+
+            // --- Reverse Futility Pruning ---
             // If the eval is well above beta, then we assume the eval will hold above beta
-            if self.can_do_fp(depth, eval, beta, improving) {
-                return beta;
+            if self.can_do_fp(depth, eval, beta, improving, opp_worsening) {
+                return (eval + beta) / Eval(2);
             }
 
             // --- Null Move Pruning ---
@@ -205,7 +288,7 @@ impl SearchWorker {
 
         // --- Quiescence search ---
         if depth <= 0 {
-            return self.quiescence::<PV>(tt, pv, alpha, beta);
+            return self.quiescence::<PV>(tt, pv, alpha, beta, 0);
         }
 
         // // --- Cut Node Pruning ---
@@ -226,10 +309,17 @@ impl SearchWorker {
         self.ss_at_mut(-2).killers.clear();
         // Get killer moves
         let killers = self.ss_at(0).killers.get();
+        // Get the counter move to the previous move
+        let counter = if self.ss_at(1).curr_move.is_valid() {
+            let (piece, to) = self.piece_to_at(1);
+            self.stats.cm.get(piece, to)
+        } else {
+            Move::NONE
+        };
         // Create search stack buffer for continuation history lookup
-        let ss_buffer = [self.ss_at(1), self.ss_at(2)];
+        let ss_buffer = CONT_HIST_PLIES.map(|offset| self.ss_at(offset));
         // Initialise move picker
-        let mut mp = MovePicker::<false>::new(&self.board, tt_move, killers);
+        let mut mp = MovePicker::<false>::new(&self.board, self.ply, tt_move, killers, counter);
         // --- Main Loop ---
         while let Some(move_) = mp.next(&self.board, &self.stats, &ss_buffer) {
             // Skip excluded move
@@ -237,11 +327,18 @@ impl SearchWorker {
                 continue;
             }
 
+            // Only search root moves preserving the tablebase result
+            if NT::ROOT && !self.root_moves.is_empty() && !self.root_moves.contains(&move_) {
+                continue;
+            }
+
             // Update number of moves searched in this node
             move_count += 1;
             // Move flags
             let is_capture = move_.is_capture();
             let is_promotion = move_.is_promotion();
+            // Move ordering statistics of the move
+            let history = self.stats.history(&self.board, self.ply, move_, &ss_buffer);
             // New depth
             let mut new_depth = depth.max(1) - 1;
 
@@ -253,6 +350,17 @@ impl SearchWorker {
                 if self.can_do_lmp(depth, move_count, improving) {
                     mp.skip_quiets();
                 }
+
+                // --- History Pruning ---
+                // Near the leafs, skip the quiet moves that have consistently failed low,
+                // unless they give check
+                if !is_capture
+                    && !is_promotion
+                    && self.can_do_hist_prune(depth, history)
+                    && !self.board.gives_check(move_)
+                {
+                    continue;
+                }
             }
 
             // --- SEE Pruning ---
@@ -338,6 +446,8 @@ impl SearchWorker {
                 // Decrease reductions for moves we think might be good
                 r -= in_check as Depth;
                 r -= self.board.in_check() as Depth;
+                // Adjust reductions by how well the move has done in similar positions
+                r -= lmr_history_adjustment(history, is_capture);
                 // We don't want to extend or go into qsearch.
                 // Since we have checked for qsearch, depth is guaranteed to be >= 1.
                 r = r.clamp(1, depth - 1);
@@ -435,6 +545,22 @@ impl SearchWorker {
             self.update_search_stats(best_move, depth, &caps_tried, &quiets_tried);
         }
 
+        // Keep the score within the bounds proven by the tablebases
+        best_value = best_value.clamp(min_value, max_value);
+
+        // --- Correction History ---
+        // Learn how far the static eval was from the search result,
+        // unless the bound of the result says nothing about the static eval
+        let static_eval = self.ss_at(0).eval;
+        if !singular
+            && !in_check
+            && !(best_move.is_valid() && best_move.is_capture())
+            && !(best_value >= beta && best_value <= static_eval)
+            && !(!best_move.is_valid() && best_value >= static_eval)
+        {
+            self.update_correction(depth, best_value);
+        }
+
         if !singular {
             // Write to TT, save static eval
             let bound = if best_value >= beta {
@@ -453,7 +579,7 @@ impl SearchWorker {
                 self.ply,
                 depth as u8,
                 best_move,
-                eval,
+                self.ss_at(0).raw_eval,
                 best_value,
             );
         }
@@ -12,6 +12,7 @@ pub(crate) struct SearchStackEntry {
     pub(crate) excl_move: Move,
     pub(crate) moved: Option<Piece>,
     pub(crate) eval: Eval,
+    pub(crate) raw_eval: Eval,
     pub(crate) in_check: bool,
     pub(crate) ply_from_null: u16,
 }
@@ -37,13 +38,19 @@ impl SearchStackEntry {
 }
 
 impl SearchStack {
+    /// Index of the entry `offset` plies before `ply`, the first entries are kept
+    /// for the moves before the root
+    fn index(ply: u16, offset: i8) -> usize {
+        let idx = ply as isize + SEARCH_STACK_OFFSET as isize - offset as isize;
+        debug_assert!((0..MAX_DEPTH as isize + SEARCH_STACK_OFFSET as isize).contains(&idx));
+        idx as usize
+    }
+
     pub(crate) fn at(&self, ply: u16, offset: i8) -> SearchStackEntry {
-        assert!(ply as i8 + SEARCH_STACK_OFFSET as i8 - offset >= 0);
-        self.stack[(ply as i8 + SEARCH_STACK_OFFSET as i8 - offset) as usize]
+        self.stack[Self::index(ply, offset)]
     }
 
     pub(crate) fn at_mut(&mut self, ply: u16, offset: i8) -> &mut SearchStackEntry {
-        assert!(ply as i8 + SEARCH_STACK_OFFSET as i8 - offset >= 0);
-        &mut self.stack[(ply as i8 + SEARCH_STACK_OFFSET as i8 - offset) as usize]
+        &mut self.stack[Self::index(ply, offset)]
     }
 }
@@ -320,20 +320,18 @@ impl ThreadPool {
 
         let chunk_size = no_of_entries / nums_threads; // Floor division
 
-        std::thread::scope(|s| {
-            for i in 0..nums_threads {
-                let start = i * chunk_size;
-                let end = if i == nums_threads - 1 {
-                    no_of_entries
-                } else {
-                    (i + 1) * chunk_size
-                };
-
-                s.spawn(move || {
-                    for j in start..end {
-                        tt.table[j].clear();
-                    }
-                });
+        // Each search thread clears its own chunk
+        self.run(&|worker| {
+            let i = worker.thread_id();
+            let start = i * chunk_size;
+            let end = if i == nums_threads - 1 {
+                no_of_entries
+            } else {
+                (i + 1) * chunk_size
+            };
+
+            for j in start..end {
+                tt.table[j].clear();
             }
         });
     }
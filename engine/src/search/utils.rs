@@ -1,9 +1,12 @@
-use chess::Move;
+use chess::{
+    Castling, Move, MoveFlag, PieceType,
+    board::{Board, LegalGen, MoveList},
+};
 
 use super::tt::TTBound;
 use crate::{
-    Depth, Eval, Interface, MoveStage, SearchWorker, constants::CONT_HIST_SIZE, evaluate_nnue,
-    search::tt::TTEntry, see, tunables::*, utils::MoveBuffer,
+    CorrectionKey, Depth, Eval, Interface, MoveStage, SearchWorker, constants::CONT_HIST_PLIES,
+    evaluate_nnue, search::tt::TTEntry, see, tunables::*, utils::MoveBuffer,
 };
 
 pub(crate) fn lmr_base_reduction(depth: Depth, move_count: usize) -> Depth {
@@ -17,6 +20,39 @@ pub(crate) fn lmr_base_reduction(depth: Depth, move_count: usize) -> Depth {
     (lmr_base + (depth as f32).ln() * (move_count as f32).ln() / lmr_mult) as i16
 }
 
+pub(crate) fn lmr_history_adjustment(history: i32, is_capture: bool) -> Depth {
+    let div = if is_capture {
+        lmr_capture_hist_div()
+    } else {
+        lmr_quiet_hist_div()
+    };
+
+    (history / div) as Depth
+}
+
+/// Returns the value of a piece type, with the king valued at zero
+pub(crate) fn piece_value(pt: PieceType) -> Eval {
+    Eval(match pt {
+        PieceType::Pawn => pawn_val(),
+        PieceType::Knight => knight_val(),
+        PieceType::Bishop => bishop_val(),
+        PieceType::Rook => rook_val(),
+        PieceType::Queen => queen_val(),
+        PieceType::King => 0,
+    })
+}
+
+/// Returns the value of the piece captured by a move, or zero for a quiet move
+pub(crate) fn captured_value(board: &Board, move_: Move) -> Eval {
+    if move_.flag() == MoveFlag::EPCapture {
+        piece_value(PieceType::Pawn)
+    } else {
+        board
+            .on(move_.to())
+            .map_or(Eval::ZERO, |piece| piece_value(piece.pt()))
+    }
+}
+
 pub(crate) fn nmp_reduction(depth: Depth) -> Depth {
     (nmp_min() + depth / nmp_div()).min(depth)
 }
@@ -36,12 +72,12 @@ pub(crate) fn can_use_tt_value(tt_bound: TTBound, tt_value: Eval, alpha: Eval, b
 }
 
 pub(crate) fn calculate_bonus(depth: Depth) -> i16 {
-    (350 * (depth.saturating_sub(1))).min(1600) as i16
+    (hist_bonus_mult() * (depth as i32 - 1).max(0)).min(hist_bonus_max()) as i16
 }
 
 impl SearchWorker {
     fn update_continuations(&mut self, move_: Move, bonus: i16) {
-        for offset in 0..CONT_HIST_SIZE as i8 {
+        for offset in CONT_HIST_PLIES {
             if self.ss_at(offset).curr_move.is_valid() {
                 let (piece, to) = self.piece_to_at(offset);
 
@@ -65,11 +101,20 @@ impl SearchWorker {
         if !best_move.is_capture() {
             self.ss_at_mut(0).killers.update(best_move);
             self.stats.ht.update(&self.board, best_move, bonus);
+            self.stats.ph.update(&self.board, best_move, bonus);
+            self.stats.lph.update(self.ply, best_move, bonus);
+
+            if self.ss_at(1).curr_move.is_valid() {
+                let (piece, to) = self.piece_to_at(1);
+                self.stats.cm.update(piece, to, best_move);
+            }
 
             self.update_continuations(best_move, bonus);
 
             for &move_ in quiets_tried {
                 self.stats.ht.update(&self.board, move_, -bonus);
+                self.stats.ph.update(&self.board, move_, -bonus);
+                self.stats.lph.update(self.ply, move_, -bonus);
 
                 self.update_continuations(move_, -bonus);
             }
@@ -82,8 +127,36 @@ impl SearchWorker {
         }
     }
 
+    /// Rewards the quiet root moves which took more than their share of the root nodes so far,
+    /// and penalises the others, so that the next iteration tries the most promising ones first
+    pub(super) fn update_root_low_ply(&mut self) {
+        let mut move_list = MoveList::new();
+        self.board.generate_moves::<LegalGen>(&mut move_list);
+
+        let total = move_list
+            .iter()
+            .map(|&move_| self.clock.node_count(move_))
+            .sum::<u64>();
+
+        if total == 0 {
+            return;
+        }
+
+        let max_bonus = low_ply_root_bonus() as f64;
+
+        for &move_ in move_list.iter().filter(|move_| !move_.is_capture()) {
+            let share = self.clock.node_count(move_) as f64 / total as f64;
+            let bonus = max_bonus * (share * move_list.len() as f64 - 1.0);
+
+            self.stats
+                .lph
+                .update(0, move_, bonus.clamp(-max_bonus, max_bonus) as i16);
+        }
+    }
+
     pub(super) fn static_eval(&mut self, in_check: bool, tt_entry: Option<TTEntry>) -> Eval {
         if in_check {
+            self.ss_at_mut(0).raw_eval = -Eval::INFINITY;
             self.ss_at_mut(0).eval = -Eval::INFINITY;
             self.ss_at(0).eval
         } else if self.ss_at(0).excl_move.is_valid() {
@@ -92,12 +165,14 @@ impl SearchWorker {
             let tt_eval = tt_entry.eval;
             let tt_value = tt_entry.value.from_tt(self.ply);
 
-            let eval = if tt_eval.abs() >= Eval::INFINITY {
+            let raw_eval = if tt_eval.abs() >= Eval::INFINITY {
                 self.evaluate()
             } else {
                 tt_eval
             };
+            let eval = self.corrected_eval(raw_eval);
 
+            self.ss_at_mut(0).raw_eval = raw_eval;
             self.ss_at_mut(0).eval = eval;
 
             // If we probe the tt_entry and the tt_value is tighter than the eval, then we can use it
@@ -108,12 +183,42 @@ impl SearchWorker {
             }
         } else {
             // self.ss_at_mut(0).eval = evaluate_nnue(&self.board, &mut self.nnue);
-            self.ss_at_mut(0).eval = self.evaluate();
+            let raw_eval = self.evaluate();
+
+            self.ss_at_mut(0).raw_eval = raw_eval;
+            self.ss_at_mut(0).eval = self.corrected_eval(raw_eval);
 
             self.ss_at(0).eval
         }
     }
 
+    /// Returns the piece and destination square of the last two moves, if both are real moves
+    fn correction_key(&self) -> Option<CorrectionKey> {
+        let (prev, prev2) = (self.ss_at(1), self.ss_at(2));
+
+        (prev.curr_move.is_valid() && prev2.curr_move.is_valid())
+            .then(|| [prev2.piece_to(), prev.piece_to()])
+    }
+
+    /// Adds the correction history to the raw static eval, keeping it out of the tablebase range
+    fn corrected_eval(&self, raw_eval: Eval) -> Eval {
+        let correction = self
+            .stats
+            .corr
+            .correction(&self.board, self.correction_key());
+        let bound = Eval::TB_WIN_BOUND - Eval(1);
+
+        (raw_eval + correction).clamp(-bound, bound)
+    }
+
+    /// Learns the difference between the search result and the raw static eval
+    pub(super) fn update_correction(&mut self, depth: Depth, best_value: Eval) {
+        let diff = best_value - self.ss_at(0).raw_eval;
+        let key = self.correction_key();
+
+        self.stats.corr.update(&self.board, key, diff, depth);
+    }
+
     pub(super) fn evaluate(&mut self) -> Eval {
         evaluate_nnue(&self.board, &mut self.nnue)
     }
@@ -142,29 +247,64 @@ impl SearchWorker {
         }
     }
 
+    pub(super) fn can_probe_tb(&self) -> bool {
+        self.board.all_occupied_bb().count_bits() as usize <= self.tb_probe_limit
+            && self.board.fifty_move() == 0
+            && self.board.castling() == Castling::NONE
+    }
+
     pub(super) fn can_do_pruning(&self, best_value: Eval) -> bool {
         best_value.is_valid() && self.board.has_non_pawn_material(self.board.stm())
     }
 
+    pub(super) fn can_do_razoring(&self, depth: Depth, eval: Eval, alpha: Eval) -> bool {
+        depth <= razor_depth()
+            && alpha.abs() < Eval::TB_WIN_BOUND
+            && eval + Eval(razor_margin() * depth as i32) <= alpha
+    }
+
     pub(super) fn can_do_nmp(&self, depth: Depth, eval: Eval, beta: Eval) -> bool {
-        depth >= 2
+        depth >= nmp_min_depth()
             && self.ply_from_null > 0
             && eval >= beta
             && self.board.has_non_pawn_material(self.board.stm())
             && beta >= -Eval::MATE_BOUND
     }
 
-    pub(super) fn can_do_fp(&self, depth: Depth, eval: Eval, beta: Eval, improving: bool) -> bool {
-        let fp_margin = Eval(80 * depth as i32 - 60 * improving as i32);
-        depth <= 8 && eval - fp_margin >= beta
+    pub(super) fn can_do_fp(
+        &self,
+        depth: Depth,
+        eval: Eval,
+        beta: Eval,
+        improving: bool,
+        opp_worsening: bool,
+    ) -> bool {
+        let fp_margin = Eval(
+            rfp_margin() * depth as i32
+                - rfp_improving() * improving as i32
+                - rfp_worsening() * opp_worsening as i32,
+        );
+
+        depth <= rfp_depth() && eval.abs() < Eval::TB_WIN_BOUND && eval - fp_margin >= beta
     }
 
     pub(super) fn can_do_lmp(&self, depth: Depth, move_count: usize, improving: bool) -> bool {
-        depth <= 8 && move_count >= ((5 + 2 * depth * depth) / (2 - improving as i16)) as usize
+        if depth > lmp_depth() {
+            return false;
+        }
+
+        let d = depth as i32;
+        let threshold = (lmp_base() as i32 + lmp_mult() as i32 * d * d) / (2 - improving as i32);
+
+        move_count >= threshold as usize
     }
 
     pub(super) fn can_do_lmr(&self, depth: Depth, move_count: usize, is_pv: bool) -> bool {
-        depth >= 2 && move_count > 3 + is_pv as usize
+        depth >= lmr_min_depth() && move_count > lmr_min_moves() as usize + is_pv as usize
+    }
+
+    pub(super) fn can_do_hist_prune(&self, depth: Depth, history: i32) -> bool {
+        depth <= hist_prune_depth() && history < -hist_prune_margin() * depth as i32
     }
 
     pub(super) fn can_do_see_prune(
@@ -176,11 +316,14 @@ impl SearchWorker {
     ) -> bool {
         // Set up SEE margins
         let d = depth as i32;
-        let see_margins = [Eval(-70 * d), Eval(-20 * d * d)];
+        let see_margins = [
+            Eval(-see_quiet_margin() * d),
+            Eval(-see_capture_margin() * d * d),
+        ];
         let is_capture = move_.is_capture();
 
         !best_value.is_terminal()
-            && depth <= 10
+            && depth <= see_depth()
             && stage > MoveStage::GoodCaptures
             && !see(&self.board, move_, see_margins[is_capture as usize])
     }
@@ -194,11 +337,11 @@ impl SearchWorker {
         tt_value: Eval,
         tt_bound: TTBound,
     ) -> bool {
-        depth >= 8
+        depth >= se_min_depth()
             && move_ == tt_move
             && tt_value.is_valid()
             && !tt_value.is_terminal()
-            && tt_depth >= depth - 3
+            && tt_depth >= depth - se_tt_depth_margin()
             && matches!(tt_bound, TTBound::Lower | TTBound::Exact)
     }
 }
@@ -1,26 +1,32 @@
 use std::sync::{
     Arc,
-    atomic::{AtomicBool, AtomicU64},
+    atomic::{AtomicBool, AtomicU64, Ordering},
 };
 
 use chess::{Move, Piece, Square, board::Board};
 use nnue::accumulator::Accumulator;
 
 use crate::{
-    HistoryTable, SearchStats, SearchWorker,
-    constants::{MAX_DEPTH, MIN_DEPTH},
+    Depth, HistoryTable, SearchStats, SearchWorker,
+    constants::{MAX_DEPTH, MIN_DEPTH, SMP_SKIP_PHASE, SMP_SKIP_SIZE},
     eval::Eval,
     search::{
         PVLine,
         stack::{SearchStack, SearchStackEntry},
     },
+    syzygy::Tablebases,
     time::Clock,
 };
 
 use super::TT;
 
 impl SearchWorker {
-    pub fn new(thread_id: usize, stop: Arc<AtomicBool>, nodes: Arc<AtomicU64>) -> Self {
+    pub fn new(
+        thread_id: usize,
+        stop: Arc<AtomicBool>,
+        nodes: Arc<AtomicU64>,
+        tb_hits: Arc<AtomicU64>,
+    ) -> Self {
         Self {
             clock: Clock::default(stop, nodes),
             thread_id,
@@ -30,12 +36,17 @@ impl SearchWorker {
             nodes: 0,
             seldepth: 0,
             depth: 0,
+            completed_depth: 0,
             ply: 0,
             ply_from_null: 0,
             pv: PVLine::default(),
             stop: false,
             stats: SearchStats::default(),
             nnue: Accumulator::default(),
+            tb: Arc::default(),
+            tb_hits,
+            tb_probe_limit: 0,
+            root_moves: Vec::new(),
         }
     }
 
@@ -47,11 +58,20 @@ impl SearchWorker {
         self.stats.ht.clear();
         self.stats.cht.clear();
         self.stats.ct.clear();
+        self.stats.cm.clear();
+        self.stats.ph.clear();
+        self.stats.lph.clear();
+        self.stats.corr.clear();
     }
 
     pub fn prepare_search(&mut self) {
         self.clock.last_nodes = 0;
+        // Helper threads keep their clock between searches, and the low ply history
+        // is relative to the root, so neither can carry over from the previous search
+        self.clock.reset_node_counts();
+        self.stats.lph.clear();
         self.nodes = 0;
+        self.completed_depth = 0;
         self.seldepth = 0;
         self.ply = 0;
         self.ply_from_null = 0;
@@ -64,10 +84,46 @@ impl SearchWorker {
         self.board = board;
     }
 
+    pub fn setup_tablebases(
+        &mut self,
+        tb: Arc<Tablebases>,
+        root_moves: Vec<Move>,
+        tb_probe_limit: usize,
+    ) {
+        self.tb = tb;
+        self.root_moves = root_moves;
+        self.tb_probe_limit = tb_probe_limit;
+    }
+
     pub fn best_move(&self) -> Move {
         self.pv[0]
     }
 
+    /// Returns the score of the last fully searched iteration
+    pub fn eval(&self) -> Eval {
+        self.eval
+    }
+
+    pub fn completed_depth(&self) -> Depth {
+        self.completed_depth
+    }
+
+    pub fn nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    /// Helper threads skip some iterations, in a different pattern for each thread,
+    /// so that they diverge from the main thread instead of repeating its search
+    pub fn should_skip_iteration(&self) -> bool {
+        if self.thread_id == 0 {
+            return false;
+        }
+
+        let i = (self.thread_id - 1) % SMP_SKIP_SIZE.len();
+
+        ((self.depth + 1 + SMP_SKIP_PHASE[i]) / SMP_SKIP_SIZE[i]) % 2 != 0
+    }
+
     pub fn should_start_iteration(&mut self) -> bool {
         self.depth < MAX_DEPTH
             && self
@@ -102,7 +158,7 @@ impl SearchWorker {
         let nodes_per_second = (self.clock.global_nodes() * 1000) as u128 / time.max(1);
 
         println!(
-            "info depth {} seldepth {} score {} time {} nodes {} nps {} hashfull {} {}",
+            "info depth {} seldepth {} score {} time {} nodes {} nps {} hashfull {} tbhits {} {}",
             self.depth + 1,
             self.seldepth + 1,
             self.eval,
@@ -110,12 +166,14 @@ impl SearchWorker {
             self.clock.global_nodes(),
             nodes_per_second,
             tt.hashfull(),
+            self.tb_hits.load(Ordering::Relaxed),
             self.pv.to_str(&self.board)
         );
     }
 
     pub(super) fn make_move(&mut self, tt: &TT, move_: Move) {
         self.board.make_move(move_);
+        self.board.debug_validate();
 
         tt.prefetch(self.board.key());
 
@@ -130,6 +188,7 @@ impl SearchWorker {
 
     pub(super) fn make_null_move(&mut self, tt: &TT) {
         self.board.make_null_move();
+        self.board.debug_validate();
 
         tt.prefetch(self.board.key());
 
@@ -144,6 +203,7 @@ impl SearchWorker {
 
     pub(super) fn undo_move(&mut self, move_: Move) {
         self.board.undo_move(move_);
+        self.board.debug_validate();
 
         self.ply -= 1;
 
@@ -152,9 +212,42 @@ impl SearchWorker {
 
     pub(super) fn undo_null_move(&mut self) {
         self.board.undo_null_move();
+        self.board.debug_validate();
 
         self.ply -= 1;
 
         self.ply_from_null = self.ss_at(0).ply_from_null;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chess::{MoveFlag, Square};
+
+    #[test]
+    fn test_prepare_search_clears_root_stats() {
+        let mut worker = SearchWorker::new(1, Arc::default(), Arc::default(), Arc::default());
+        let e4 = Move::new(Square::E2, Square::E4, MoveFlag::DoublePawnPush);
+
+        // Root statistics left over from a previous search
+        worker.clock.update_node_counts(e4, 1000);
+        worker.stats.lph.update(0, e4, 500);
+        assert_ne!(worker.stats.lph.get(0, e4), Eval::ZERO);
+
+        worker.prepare_search();
+
+        assert_eq!(worker.clock.node_count(e4), 0);
+        assert_eq!(worker.stats.lph.get(0, e4), Eval::ZERO);
+    }
+
+    #[test]
+    fn test_lmp_at_large_depths() {
+        let worker = SearchWorker::new(0, Arc::default(), Arc::default(), Arc::default());
+
+        // The move count threshold must not overflow past the late move pruning depth
+        assert!(!worker.can_do_lmp(128, usize::MAX, true));
+        assert!(!worker.can_do_lmp(Depth::MAX, usize::MAX, false));
+        assert!(worker.can_do_lmp(1, usize::MAX, false));
+    }
+}
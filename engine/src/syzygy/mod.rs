@@ -0,0 +1,162 @@
+//! Syzygy endgame tablebase probing.
+//!
+//! This module provides:
+//! - Discovery of `.rtbw` (win/draw/loss) and `.rtbz` (distance to zeroing) files in the `SyzygyPath` directories.
+//! - Lazily memory mapped tables, decoded on demand.
+//! - WDL probes used during search, DTZ probes, and ranking of the root moves.
+mod probe;
+mod table;
+
+pub use probe::Wdl;
+pub use table::TB_PIECES;
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use chess::board::Board;
+use table::{Table, TableInfo, TableKind, board_material_key};
+
+/// Separator between directories in the `SyzygyPath` option
+#[cfg(windows)]
+const PATH_SEPARATOR: char = ';';
+#[cfg(not(windows))]
+const PATH_SEPARATOR: char = ':';
+
+/// A table found on disk, mapped the first time it is probed.
+#[derive(Debug)]
+struct TableEntry {
+    info: TableInfo,
+    wdl: OnceLock<Option<Table>>,
+    dtz: OnceLock<Option<Table>>,
+}
+
+/// The set of Syzygy tables available to the engine.
+#[derive(Debug, Default)]
+pub struct Tablebases {
+    dirs: Vec<PathBuf>,
+    tables: Vec<TableEntry>,
+    /// Material key (for both colour assignments) to table index
+    keys: HashMap<u64, usize>,
+    max_pieces: usize,
+}
+
+impl Tablebases {
+    /// # Load Tablebases
+    ///
+    /// Scans the directories of a `SyzygyPath` value (separated by `:`, or `;` on Windows)
+    /// for WDL tables. Tables are only mapped when first probed, and unreadable
+    /// directories are ignored.
+    pub fn new(paths: &str) -> Self {
+        let mut tb = Self::default();
+
+        if paths.is_empty() || paths == "<empty>" {
+            return tb;
+        }
+
+        tb.dirs = paths
+            .split(PATH_SEPARATOR)
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .collect();
+
+        for dir in tb.dirs.clone() {
+            let Ok(files) = std::fs::read_dir(&dir) else {
+                continue;
+            };
+
+            for file in files.flatten() {
+                let path = file.path();
+                if path
+                    .extension()
+                    .is_some_and(|ext| ext == TableKind::Wdl.extension())
+                    && let Some(info) = path
+                        .file_stem()
+                        .and_then(|s| s.to_str())
+                        .and_then(TableInfo::parse)
+                {
+                    tb.add(info);
+                }
+            }
+        }
+
+        tb
+    }
+
+    fn add(&mut self, info: TableInfo) {
+        if self.keys.contains_key(&info.key) {
+            return;
+        }
+
+        self.max_pieces = self.max_pieces.max(info.piece_count);
+        self.keys.insert(info.key, self.tables.len());
+        self.keys.insert(info.key2, self.tables.len());
+        self.tables.push(TableEntry {
+            info,
+            wdl: OnceLock::new(),
+            dtz: OnceLock::new(),
+        });
+    }
+
+    /// Returns the number of WDL tables found
+    pub fn len(&self) -> usize {
+        self.tables.len()
+    }
+
+    /// Returns whether no tables were found
+    pub fn is_empty(&self) -> bool {
+        self.tables.is_empty()
+    }
+
+    /// Returns the largest number of pieces covered by the tables, 0 if there are none
+    pub fn max_pieces(&self) -> usize {
+        self.max_pieces
+    }
+
+    fn find_file(&self, name: &str, kind: TableKind) -> Option<PathBuf> {
+        self.dirs
+            .iter()
+            .map(|dir| dir.join(format!("{name}.{}", kind.extension())))
+            .find(|path| Path::is_file(path))
+    }
+
+    /// Returns the mapped table of the board's material along with the board's material key
+    fn table(&self, board: &Board, kind: TableKind) -> Option<(&TableInfo, &Table, u64)> {
+        let key = board_material_key(board);
+        let entry = &self.tables[*self.keys.get(&key)?];
+
+        let cell = match kind {
+            TableKind::Wdl => &entry.wdl,
+            TableKind::Dtz => &entry.dtz,
+        };
+
+        let table = cell
+            .get_or_init(|| {
+                Table::open(&self.find_file(&entry.info.name, kind)?, &entry.info, kind)
+            })
+            .as_ref()?;
+
+        Some((&entry.info, table, key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_tables() {
+        let tb = Tablebases::new("");
+        assert!(tb.is_empty());
+        assert_eq!(tb.max_pieces(), 0);
+
+        let tb = Tablebases::new("/this/path/does/not/exist");
+        assert!(tb.is_empty());
+
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+        assert_eq!(tb.probe_wdl(&mut board), None);
+        assert_eq!(tb.probe_dtz(&mut board), None);
+    }
+}
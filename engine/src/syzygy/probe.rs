@@ -0,0 +1,440 @@
+use chess::{
+    Move, PieceType,
+    board::{Board, LegalGen, MoveList},
+};
+
+use super::{Tablebases, table::TableKind};
+
+/******************************************\
+|==========================================|
+|                   WDL                    |
+|==========================================|
+\******************************************/
+
+/// Result of a position from the side to move's point of view.
+/// Cursed wins and blessed losses are wins and losses that are drawn by the fifty-move rule.
+#[repr(i8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Wdl {
+    Loss = -2,
+    BlessedLoss = -1,
+    Draw = 0,
+    CursedWin = 1,
+    Win = 2,
+}
+
+impl Wdl {
+    fn from_value(value: i32) -> Self {
+        match value {
+            ..=-2 => Self::Loss,
+            -1 => Self::BlessedLoss,
+            0 => Self::Draw,
+            1 => Self::CursedWin,
+            _ => Self::Win,
+        }
+    }
+
+    fn value(self) -> i32 {
+        self as i32
+    }
+}
+
+impl std::ops::Neg for Wdl {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::from_value(-self.value())
+    }
+}
+
+/// DTZ of a position whose best move is a zeroing move with the given result
+fn dtz_before_zeroing(wdl: Wdl) -> i32 {
+    match wdl {
+        Wdl::Win => 1,
+        Wdl::CursedWin => 101,
+        Wdl::BlessedLoss => -101,
+        Wdl::Loss => -1,
+        Wdl::Draw => 0,
+    }
+}
+
+fn legal_moves(board: &Board) -> MoveList {
+    let mut move_list = MoveList::new();
+    board.generate_moves::<LegalGen>(&mut move_list);
+    move_list
+}
+
+fn is_pawn_move(board: &Board, move_: Move) -> bool {
+    board
+        .on(move_.from())
+        .is_some_and(|piece| piece.pt() == PieceType::Pawn)
+}
+
+fn is_mate(board: &Board) -> bool {
+    board.in_check() && legal_moves(board).is_empty()
+}
+
+/******************************************\
+|==========================================|
+|               Root Probe                 |
+|==========================================|
+\******************************************/
+
+/// Ranking used for root moves, wins without fifty-move rule issues are ranked `MAX_DTZ`
+const MAX_DTZ: i32 = 1 << 18;
+
+/// Root moves ranked by their tablebase result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RootProbe {
+    /// Legal root moves with their rank, higher is better
+    pub moves: Vec<(Move, i32)>,
+    /// Whether the ranks come from DTZ tables, or only from WDL tables
+    pub dtz: bool,
+}
+
+impl RootProbe {
+    /// Returns the highest rank of the root moves
+    pub fn best_rank(&self) -> i32 {
+        self.moves.iter().map(|&(_, rank)| rank).max().unwrap_or(0)
+    }
+
+    /// Returns the moves sharing the highest rank, the only ones worth searching
+    pub fn best_moves(&self) -> Vec<Move> {
+        let best = self.best_rank();
+        self.moves
+            .iter()
+            .filter(|&&(_, rank)| rank == best)
+            .map(|&(move_, _)| move_)
+            .collect()
+    }
+}
+
+/******************************************\
+|==========================================|
+|                 Probing                  |
+|==========================================|
+\******************************************/
+
+impl Tablebases {
+    /// Probes the WDL table of the position, without looking at captures
+    fn probe_wdl_table(&self, board: &Board) -> Option<Wdl> {
+        if board.all_occupied_bb().count_bits() == 2 {
+            return Some(Wdl::Draw);
+        }
+
+        let (info, table, key) = self.table(board, TableKind::Wdl)?;
+        table.probe(info, board, key, 0).map(Wdl::from_value)
+    }
+
+    /// Probes the DTZ table of the position, the inner `None` means the table only stores the other side to move
+    fn probe_dtz_table(&self, board: &Board, wdl: Wdl) -> Option<Option<i32>> {
+        let (info, table, key) = self.table(board, TableKind::Dtz)?;
+        Some(table.probe(info, board, key, wdl.value()))
+    }
+
+    /// # WDL Search
+    ///
+    /// Positions where the side to move wins with a capture, or draws with one, may store
+    /// "don't care" values to improve compression, so captures are searched before the
+    /// table is probed. With `check_zeroing`, pawn moves are searched as well, since DTZ
+    /// tables are not valid when the best move is zeroing.
+    ///
+    /// Returns the result and whether the best move is a zeroing move.
+    fn search(&self, board: &mut Board, check_zeroing: bool) -> Option<(Wdl, bool)> {
+        let move_list = legal_moves(board);
+        let mut best = Wdl::Loss;
+        let mut move_count = 0;
+
+        for &move_ in move_list.iter() {
+            if !move_.is_capture() && (!check_zeroing || !is_pawn_move(board, move_)) {
+                continue;
+            }
+
+            move_count += 1;
+
+            board.make_move(move_);
+            let value = self.search(board, false).map(|(wdl, _)| -wdl);
+            board.undo_move(move_);
+
+            let value = value?;
+            if value > best {
+                best = value;
+
+                if value == Wdl::Win {
+                    return Some((value, true));
+                }
+            }
+        }
+
+        // If every legal move was searched the stored value can not be trusted, e.g. with en passant
+        let no_more_moves = move_count > 0 && move_count == move_list.len();
+
+        let value = if no_more_moves {
+            best
+        } else {
+            self.probe_wdl_table(board)?
+        };
+
+        if best >= value {
+            Some((best, best > Wdl::Draw || no_more_moves))
+        } else {
+            Some((value, false))
+        }
+    }
+
+    /// # Probe WDL
+    ///
+    /// Returns the result of the position, or `None` if the needed tables are missing.
+    /// The result assumes no castling rights, and that the fifty-move counter was just reset.
+    pub fn probe_wdl(&self, board: &mut Board) -> Option<Wdl> {
+        self.search(board, false).map(|(wdl, _)| wdl)
+    }
+
+    /// # Probe DTZ
+    ///
+    /// Returns the distance in plies to the next zeroing move (capture or pawn move) with
+    /// optimal play, positive when winning and negative when losing. Cursed wins and blessed
+    /// losses are offset by 100. Returns 0 for draws, and `None` if the needed tables are missing.
+    pub fn probe_dtz(&self, board: &mut Board) -> Option<i32> {
+        let (wdl, zeroing) = self.search(board, true)?;
+
+        if wdl == Wdl::Draw {
+            return Some(0);
+        }
+
+        if zeroing {
+            return Some(dtz_before_zeroing(wdl));
+        }
+
+        if let Some(dtz) = self.probe_dtz_table(board, wdl)? {
+            let cursed = matches!(wdl, Wdl::CursedWin | Wdl::BlessedLoss);
+            return Some((dtz + 100 * cursed as i32) * wdl.value().signum());
+        }
+
+        // The table stores the other side to move, so find the best DTZ with a one ply search
+        let mut min_dtz = i32::MAX;
+
+        for &move_ in legal_moves(board).iter() {
+            let zeroing = move_.is_capture() || is_pawn_move(board, move_);
+
+            board.make_move(move_);
+            // For zeroing moves the DTZ before the move is wanted, the sign comes from the result after it
+            let dtz = if zeroing {
+                self.search(board, false)
+                    .map(|(wdl, _)| -dtz_before_zeroing(wdl))
+            } else {
+                self.probe_dtz(board).map(|dtz| -dtz)
+            };
+            let mate = is_mate(board);
+            board.undo_move(move_);
+
+            let mut dtz = dtz?;
+
+            if dtz == 1 && mate {
+                min_dtz = 1;
+            }
+
+            if !zeroing {
+                dtz += dtz.signum();
+            }
+
+            if dtz < min_dtz && dtz.signum() == wdl.value().signum() {
+                min_dtz = dtz;
+            }
+        }
+
+        // Without legal moves the position is mate
+        Some(if min_dtz == i32::MAX { -1 } else { min_dtz })
+    }
+
+    /// # Probe Root
+    ///
+    /// Ranks the legal root moves using the DTZ tables, or the WDL tables when DTZ tables
+    /// are missing. Winning moves are ranked equally unless the fifty-move rule gets close,
+    /// so the search still picks among them. Returns `None` if the tables are missing.
+    pub fn probe_root(&self, board: &mut Board) -> Option<RootProbe> {
+        self.rank_root_dtz(board)
+            .map(|moves| RootProbe { moves, dtz: true })
+            .or_else(|| {
+                self.rank_root_wdl(board)
+                    .map(|moves| RootProbe { moves, dtz: false })
+            })
+    }
+
+    fn rank_root_dtz(&self, board: &mut Board) -> Option<Vec<(Move, i32)>> {
+        let fifty = board.fifty_move() as i32;
+        let repeated = board.has_repeated();
+        let mut ranked = Vec::new();
+
+        for &move_ in legal_moves(board).iter() {
+            board.make_move(move_);
+
+            // DTZ counted from the root position
+            let dtz = if board.fifty_move() == 0 {
+                self.probe_wdl(board).map(|wdl| dtz_before_zeroing(-wdl))
+            } else if board.is_draw(1) {
+                Some(0)
+            } else {
+                self.probe_dtz(board)
+                    .map(|dtz| -dtz)
+                    .map(|dtz| dtz + dtz.signum())
+            };
+            let mate = is_mate(board);
+            board.undo_move(move_);
+
+            let dtz = match dtz? {
+                2 if mate => 1,
+                dtz => dtz,
+            };
+
+            // Wins are ranked equally, losses too unless a fifty-move draw is in sight
+            let rank = if dtz > 0 {
+                if dtz + fifty <= 99 && !repeated {
+                    MAX_DTZ
+                } else {
+                    MAX_DTZ - (dtz + fifty)
+                }
+            } else if dtz < 0 {
+                if -dtz * 2 + fifty < 100 {
+                    -MAX_DTZ
+                } else {
+                    -MAX_DTZ + (-dtz + fifty)
+                }
+            } else {
+                0
+            };
+
+            ranked.push((move_, rank));
+        }
+
+        Some(ranked)
+    }
+
+    fn rank_root_wdl(&self, board: &mut Board) -> Option<Vec<(Move, i32)>> {
+        const WDL_TO_RANK: [i32; 5] = [-MAX_DTZ, -MAX_DTZ + 101, 0, MAX_DTZ - 101, MAX_DTZ];
+
+        let mut ranked = Vec::new();
+
+        for &move_ in legal_moves(board).iter() {
+            board.make_move(move_);
+            let wdl = self.probe_wdl(board).map(|wdl| -wdl);
+            board.undo_move(move_);
+
+            ranked.push((move_, WDL_TO_RANK[(wdl?.value() + 2) as usize]));
+        }
+
+        Some(ranked)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Loads the tables in `SYZYGY_PATH`, which the ignored tests below need.
+    /// Run them with `SYZYGY_PATH=<dir> cargo test -- --ignored`.
+    fn tablebases() -> Tablebases {
+        let path = std::env::var("SYZYGY_PATH").expect("SYZYGY_PATH should be set");
+        let tb = Tablebases::new(&path);
+        assert!(
+            tb.max_pieces() >= 5,
+            "SYZYGY_PATH should hold the 3-5 piece tables"
+        );
+        tb
+    }
+
+    /// Mirrors a FEN vertically and swaps the colours, which keeps the result for the side to move
+    fn flip_fen(fen: &str) -> String {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        let swap = |c: char| {
+            if c.is_ascii_uppercase() {
+                c.to_ascii_lowercase()
+            } else {
+                c.to_ascii_uppercase()
+            }
+        };
+
+        let placement: Vec<String> = fields[0]
+            .split('/')
+            .rev()
+            .map(|rank| rank.chars().map(swap).collect())
+            .collect();
+        let stm = if fields[1] == "w" { "b" } else { "w" };
+
+        format!("{} {stm} - - 0 1", placement.join("/"))
+    }
+
+    fn wdl(tb: &Tablebases, fen: &str) -> Wdl {
+        let mut board = Board::from_fen(fen).unwrap();
+        let wdl = tb.probe_wdl(&mut board).unwrap();
+
+        let mut flipped = Board::from_fen(&flip_fen(fen)).unwrap();
+        assert_eq!(tb.probe_wdl(&mut flipped), Some(wdl), "Flipped {fen}");
+
+        wdl
+    }
+
+    #[test]
+    #[ignore = "requires SYZYGY_PATH with 3-5 piece tables"]
+    fn test_probe_wdl() {
+        let tb = tablebases();
+
+        // KQvK and KRvK, with either side to move
+        assert_eq!(wdl(&tb, "4k3/8/8/8/8/8/8/4K2Q w - - 0 1"), Wdl::Win);
+        assert_eq!(wdl(&tb, "4k3/8/8/8/8/8/8/4K2Q b - - 0 1"), Wdl::Loss);
+        assert_eq!(wdl(&tb, "4k3/8/8/8/8/8/8/R3K3 b - - 0 1"), Wdl::Loss);
+        // Bare kings, and two knights can not force mate
+        assert_eq!(wdl(&tb, "4k3/8/8/8/8/8/8/4K3 w - - 0 1"), Wdl::Draw);
+        assert_eq!(wdl(&tb, "4k3/8/8/8/8/8/8/1NN1K3 w - - 0 1"), Wdl::Draw);
+        // KPvK: the defender holds with the opposition, and loses without it
+        assert_eq!(wdl(&tb, "8/4k3/8/4K3/4P3/8/8/8 w - - 0 1"), Wdl::Draw);
+        assert_eq!(wdl(&tb, "8/4k3/8/4K3/4P3/8/8/8 b - - 0 1"), Wdl::Loss);
+        // The queen hangs to the king
+        assert_eq!(wdl(&tb, "8/8/8/8/8/1k6/Q7/4K3 b - - 0 1"), Wdl::Draw);
+        // Pawns on both sides, and five pieces
+        assert_eq!(wdl(&tb, "7k/P7/8/8/8/7p/8/K7 w - - 0 1"), Wdl::Win);
+        assert_eq!(wdl(&tb, "4k3/8/8/8/8/8/P7/RR2K3 w - - 0 1"), Wdl::Win);
+    }
+
+    #[test]
+    #[ignore = "requires SYZYGY_PATH with 3-5 piece tables"]
+    fn test_probe_dtz() {
+        let tb = tablebases();
+
+        // Mate in one, and mated
+        let mut board = Board::from_fen("7k/8/6K1/8/8/8/8/1Q6 w - - 0 1").unwrap();
+        assert_eq!(tb.probe_dtz(&mut board), Some(1));
+
+        let mut board = Board::from_fen("1Q5k/8/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(tb.probe_dtz(&mut board), Some(-1));
+
+        // Draws and zeroing wins
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/8/1NN1K3 w - - 0 1").unwrap();
+        assert_eq!(tb.probe_dtz(&mut board), Some(0));
+
+        let mut board = Board::from_fen("8/4k3/8/4K3/4P3/8/8/8 b - - 0 1").unwrap();
+        assert!(tb.probe_dtz(&mut board).unwrap() < 0);
+    }
+
+    #[test]
+    #[ignore = "requires SYZYGY_PATH with 3-5 piece tables"]
+    fn test_probe_root() {
+        let tb = tablebases();
+
+        // Qb3 and Qa4 hang the queen to the king, every other move wins
+        let mut board = Board::from_fen("8/8/8/8/1k6/8/8/3QK3 w - - 0 1").unwrap();
+        let root = tb.probe_root(&mut board).unwrap();
+        let best: Vec<String> = root
+            .best_moves()
+            .iter()
+            .map(|move_| move_.to_str(&board))
+            .collect();
+
+        assert!(root.dtz);
+        assert_eq!(root.best_rank(), MAX_DTZ);
+        assert_eq!(root.moves.len(), legal_moves(&board).len());
+        assert!(best.contains(&"d1d4".to_string()));
+        assert!(!best.contains(&"d1b3".to_string()));
+        assert!(!best.contains(&"d1a4".to_string()));
+    }
+}
@@ -0,0 +1,1096 @@
+use std::{fs::File, path::Path, sync::LazyLock};
+
+use chess::{
+    Bitboard, Colour, Piece, PieceType, Square,
+    board::{Board, king_attack},
+};
+use memmap2::Mmap;
+
+/******************************************\
+|==========================================|
+|                Constants                 |
+|==========================================|
+\******************************************/
+
+/// Maximum number of pieces supported by the Syzygy format
+pub const TB_PIECES: usize = 7;
+
+const WDL_MAGIC: [u8; 4] = [0x71, 0xE8, 0x23, 0x5D];
+const DTZ_MAGIC: [u8; 4] = [0xD7, 0x66, 0x0C, 0xA5];
+
+// Per table flags
+const FLAG_STM: u8 = 1;
+const FLAG_MAPPED: u8 = 2;
+const FLAG_WIN_PLIES: u8 = 4;
+const FLAG_LOSS_PLIES: u8 = 8;
+const FLAG_WIDE: u8 = 16;
+const FLAG_SINGLE_VALUE: u8 = 128;
+
+/// Syzygy tables come in two kinds, win/draw/loss tables and distance to zeroing tables
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableKind {
+    Wdl,
+    Dtz,
+}
+
+impl TableKind {
+    /// File extension of the table kind
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Wdl => "rtbw",
+            Self::Dtz => "rtbz",
+        }
+    }
+
+    /// WDL tables store both sides to move, DTZ tables only one
+    fn sides(self) -> usize {
+        match self {
+            Self::Wdl => 2,
+            Self::Dtz => 1,
+        }
+    }
+
+    fn magic(self) -> [u8; 4] {
+        match self {
+            Self::Wdl => WDL_MAGIC,
+            Self::Dtz => DTZ_MAGIC,
+        }
+    }
+}
+
+/******************************************\
+|==========================================|
+|             Index Encoding               |
+|==========================================|
+\******************************************/
+
+/// Lookup tables used to turn a position into a table index, as defined by the Syzygy format.
+struct Encoding {
+    /// Encodes squares a2-h7 to 0..47, the highest value belongs to the leading pawn
+    map_pawns: [usize; Square::NUM],
+    /// Encodes a square below the a1-h8 diagonal to 0..27
+    map_b1h1h7: [usize; Square::NUM],
+    /// Encodes a square in the a1-d1-d4 triangle to 0..9
+    map_a1d1d4: [usize; Square::NUM],
+    /// Encodes the 462 legal placements of two kings, the first in the a1-d1-d4 triangle
+    map_kk: [[usize; Square::NUM]; 10],
+    /// `binomial[k][n]` ways to choose k elements from n
+    binomial: [[u64; Square::NUM]; 6],
+    /// Index of the leading pawn group for each leading pawn square
+    lead_pawn_idx: [[u64; Square::NUM]; 6],
+    /// Number of leading pawn group placements per file
+    lead_pawns_size: [[u64; 4]; 6],
+}
+
+static ENCODING: LazyLock<Encoding> = LazyLock::new(Encoding::new);
+
+/// Rank minus file, negative below the a1-h8 diagonal, zero on it
+fn off_a1h8(sq: usize) -> i32 {
+    (sq >> 3) as i32 - (sq & 7) as i32
+}
+
+fn square(index: usize) -> Square {
+    // Safety: callers only pass indices in 0..64
+    unsafe { Square::from_unchecked(index as u8) }
+}
+
+impl Encoding {
+    fn new() -> Self {
+        let mut enc = Self {
+            map_pawns: [0; Square::NUM],
+            map_b1h1h7: [0; Square::NUM],
+            map_a1d1d4: [0; Square::NUM],
+            map_kk: [[0; Square::NUM]; 10],
+            binomial: [[0; Square::NUM]; 6],
+            lead_pawn_idx: [[0; Square::NUM]; 6],
+            lead_pawns_size: [[0; 4]; 6],
+        };
+
+        let mut code = 0;
+        for sq in 0..Square::NUM {
+            if off_a1h8(sq) < 0 {
+                enc.map_b1h1h7[sq] = code;
+                code += 1;
+            }
+        }
+
+        // Diagonal squares are encoded last
+        let mut diagonal = Vec::new();
+        code = 0;
+        for sq in 0..=Square::D4.index() {
+            if off_a1h8(sq) < 0 && sq & 7 <= 3 {
+                enc.map_a1d1d4[sq] = code;
+                code += 1;
+            } else if off_a1h8(sq) == 0 && sq & 7 <= 3 {
+                diagonal.push(sq);
+            }
+        }
+        for sq in diagonal {
+            enc.map_a1d1d4[sq] = code;
+            code += 1;
+        }
+
+        // If the first king is on the a1-d4 diagonal, the other one can not be above the a1-h8 diagonal.
+        // Placements with both kings on the diagonal are encoded last.
+        let mut both_on_diagonal = Vec::new();
+        code = 0;
+        for idx in 0..10 {
+            for s1 in 0..=Square::D4.index() {
+                // Squares outside the triangle are also mapped to 0, b1 is the real one
+                if enc.map_a1d1d4[s1] != idx || (idx == 0 && s1 != Square::B1.index()) {
+                    continue;
+                }
+
+                for s2 in 0..Square::NUM {
+                    // Skip illegal king pairs, and the mirror images of the a1-h8 diagonal
+                    if s1 == s2
+                        || king_attack(square(s1)).contains(square(s2))
+                        || (off_a1h8(s1) == 0 && off_a1h8(s2) > 0)
+                    {
+                        continue;
+                    } else if off_a1h8(s1) == 0 && off_a1h8(s2) == 0 {
+                        both_on_diagonal.push((idx, s2));
+                    } else {
+                        enc.map_kk[idx][s2] = code;
+                        code += 1;
+                    }
+                }
+            }
+        }
+        for (idx, s2) in both_on_diagonal {
+            enc.map_kk[idx][s2] = code;
+            code += 1;
+        }
+
+        enc.binomial[0][0] = 1;
+        for n in 1..Square::NUM {
+            for k in 0..6.min(n + 1) {
+                enc.binomial[k][n] = if k > 0 { enc.binomial[k - 1][n - 1] } else { 0 }
+                    + if k < n { enc.binomial[k][n - 1] } else { 0 };
+            }
+        }
+
+        // Pawns nearer the edge and lower ranks get higher values, making them the leading pawn
+        let mut available = 47;
+        for lead_count in 1..=5 {
+            for file in 0..4 {
+                let mut idx = 0;
+
+                for rank in 1..=6 {
+                    let sq = rank * 8 + file;
+
+                    if lead_count == 1 {
+                        enc.map_pawns[sq] = available;
+                        enc.map_pawns[sq ^ 7] = available.saturating_sub(1);
+                        available = available.saturating_sub(2);
+                    }
+
+                    enc.lead_pawn_idx[lead_count][sq] = idx;
+                    idx += enc.binomial[lead_count - 1][enc.map_pawns[sq]];
+                }
+
+                enc.lead_pawns_size[lead_count][file] = idx;
+            }
+        }
+
+        enc
+    }
+}
+
+/******************************************\
+|==========================================|
+|               Table Info                 |
+|==========================================|
+\******************************************/
+
+/// # Material Key
+///
+/// Packs the piece counts of both sides into a key, four bits per piece type excluding kings.
+/// `counts[colour][piece type]` is the number of pieces.
+pub fn material_key(counts: &[[u8; PieceType::NUM]; Colour::NUM]) -> u64 {
+    let mut key = 0;
+    for (colour, pieces) in counts.iter().enumerate() {
+        for (pt, &count) in pieces[..PieceType::King.index()].iter().enumerate() {
+            key |= (count as u64) << (4 * (colour * 5 + pt));
+        }
+    }
+    key
+}
+
+/// Returns the material key of a board, see `material_key`
+pub fn board_material_key(board: &Board) -> u64 {
+    let mut counts = [[0; PieceType::NUM]; Colour::NUM];
+    for colour in [Colour::White, Colour::Black] {
+        for pt in PieceType::iter() {
+            counts[colour.index()][pt.index()] = board.piece_bb(colour, pt).count_bits() as u8;
+        }
+    }
+    material_key(&counts)
+}
+
+/// Material configuration of a table, parsed from its file name (e.g. `KRPvKR`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableInfo {
+    /// File name without extension
+    pub name: String,
+    /// Material key with the first side of the name as white
+    pub key: u64,
+    /// Material key with the first side of the name as black
+    pub key2: u64,
+    pub piece_count: usize,
+    pub has_pawns: bool,
+    /// Whether any side has a piece type other than the king exactly once
+    pub has_unique_pieces: bool,
+    /// Pawns of the leading colour, then of the other colour
+    pub pawn_count: [usize; 2],
+}
+
+impl TableInfo {
+    /// Parses a table name such as `KRvK`, returns `None` if it is not a valid Syzygy name
+    pub fn parse(name: &str) -> Option<Self> {
+        let (white, black) = name.split_once('v')?;
+        let mut counts = [[0u8; PieceType::NUM]; Colour::NUM];
+
+        for (colour, side) in [white, black].into_iter().enumerate() {
+            if !side.starts_with('K') || side[1..].contains('K') {
+                return None;
+            }
+
+            for c in side.chars() {
+                let pt = match c {
+                    'P' => PieceType::Pawn,
+                    'N' => PieceType::Knight,
+                    'B' => PieceType::Bishop,
+                    'R' => PieceType::Rook,
+                    'Q' => PieceType::Queen,
+                    'K' => PieceType::King,
+                    _ => return None,
+                };
+                counts[colour][pt.index()] += 1;
+            }
+        }
+
+        let piece_count = white.len() + black.len();
+        if piece_count > TB_PIECES {
+            return None;
+        }
+
+        let pawns = |colour: usize| counts[colour][PieceType::Pawn.index()] as usize;
+        let has_unique_pieces = counts
+            .iter()
+            .any(|pieces| pieces[..PieceType::King.index()].contains(&1));
+
+        // The leading colour is the one with fewer pawns, as it compresses better
+        let white_leads = pawns(1) == 0 || (pawns(0) > 0 && pawns(1) >= pawns(0));
+        let pawn_count = if white_leads {
+            [pawns(0), pawns(1)]
+        } else {
+            [pawns(1), pawns(0)]
+        };
+
+        Some(Self {
+            name: name.to_string(),
+            key: material_key(&counts),
+            key2: material_key(&[counts[1], counts[0]]),
+            piece_count,
+            has_pawns: pawns(0) + pawns(1) > 0,
+            has_unique_pieces,
+            pawn_count,
+        })
+    }
+
+    fn symmetric(&self) -> bool {
+        self.key == self.key2
+    }
+}
+
+/******************************************\
+|==========================================|
+|               Pairs Data                 |
+|==========================================|
+\******************************************/
+
+/// Decoding information of one sub table, for one side to move and one leading pawn file.
+/// Offsets are relative to the start of the mapped file.
+#[derive(Debug, Clone, Default)]
+struct PairsData {
+    flags: u8,
+    /// Block size in bytes
+    block_size: usize,
+    /// There is a sparse index entry about every `span` values
+    span: u64,
+    num_blocks: usize,
+    /// Minimum Huffman symbol length, or the value itself for single value tables
+    min_sym_len: u8,
+    /// `lowest_sym[l]` is the symbol of length l with the lowest value
+    lowest_sym: usize,
+    /// `btree[sym]` stores the left and right symbols that expand sym
+    btree: usize,
+    /// Number of values (minus one) stored in each block
+    block_length: usize,
+    block_length_size: usize,
+    sparse_index: usize,
+    sparse_index_size: usize,
+    /// Start of the Huffman compressed data
+    data: usize,
+    /// `base64[l - min_sym_len]` is the 64 bit padded lowest symbol of length l
+    base64: Vec<u64>,
+    /// Number of values (minus one) represented by each symbol
+    sym_len: Vec<u8>,
+    /// Piece order used by the table, which defines the groups
+    pieces: [u8; TB_PIECES],
+    /// Start index used for the encoding of each group
+    group_idx: [u64; TB_PIECES + 1],
+    /// Number of pieces of each group, zero terminated
+    group_len: [usize; TB_PIECES + 1],
+    /// Offsets of the DTZ value maps for wins, losses, cursed wins and blessed losses
+    map_idx: [u16; 4],
+}
+
+fn read_u16_le(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn read_u32_le(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u32_be(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64_be(bytes: &[u8], offset: usize) -> u64 {
+    u64::from_be_bytes(bytes[offset..offset + 8].try_into().unwrap())
+}
+
+/// Returns the (left, right) symbols of a symbol pair, each stored on 12 bits
+fn btree_pair(bytes: &[u8], btree: usize, sym: usize) -> (usize, usize) {
+    let lr = &bytes[btree + 3 * sym..btree + 3 * sym + 3];
+    let left = ((lr[1] as usize & 0xF) << 8) | lr[0] as usize;
+    let right = ((lr[2] as usize) << 4) | (lr[1] as usize >> 4);
+    (left, right)
+}
+
+impl PairsData {
+    /// Sets up the piece groups, and the index multiplier of each group.
+    /// `order` gives the position of the leading group and the remaining pawns in the encoding.
+    fn set_groups(&mut self, info: &TableInfo, order: [u8; 2], file: usize) {
+        let enc = &*ENCODING;
+        let mut n = 0;
+        let mut first_len: i32 = if info.has_pawns {
+            0
+        } else if info.has_unique_pieces {
+            3
+        } else {
+            2
+        };
+
+        self.group_len[0] = 1;
+        for i in 1..info.piece_count {
+            first_len -= 1;
+            // The leading pieces form the first group, then equal pieces are grouped together
+            if first_len > 0 || self.pieces[i] == self.pieces[i - 1] {
+                self.group_len[n] += 1;
+            } else {
+                n += 1;
+                self.group_len[n] = 1;
+            }
+        }
+        n += 1;
+        self.group_len[n] = 0;
+
+        let pp = info.has_pawns && info.pawn_count[1] > 0;
+        let mut next = if pp { 2 } else { 1 };
+        let mut free_squares = 64 - self.group_len[0] - if pp { self.group_len[1] } else { 0 };
+        let mut idx = 1;
+
+        let mut k = 0;
+        while next < n || k == order[0] as usize || k == order[1] as usize {
+            if k == order[0] as usize {
+                self.group_idx[0] = idx;
+                idx *= if info.has_pawns {
+                    enc.lead_pawns_size[self.group_len[0]][file]
+                } else if info.has_unique_pieces {
+                    31332
+                } else {
+                    462
+                };
+            } else if k == order[1] as usize {
+                self.group_idx[1] = idx;
+                idx *= enc.binomial[self.group_len[1]][48 - self.group_len[0]];
+            } else {
+                self.group_idx[next] = idx;
+                idx *= enc.binomial[self.group_len[next]][free_squares];
+                free_squares -= self.group_len[next];
+                next += 1;
+            }
+            k += 1;
+        }
+
+        self.group_idx[n] = idx;
+    }
+
+    /// Reads the block sizes and the canonical Huffman code, returns the offset after them
+    fn set_sizes(&mut self, bytes: &[u8], mut data: usize) -> Option<usize> {
+        self.flags = *bytes.get(data)?;
+        data += 1;
+
+        if self.flags & FLAG_SINGLE_VALUE != 0 {
+            self.min_sym_len = *bytes.get(data)?;
+            return Some(data + 1);
+        }
+
+        let groups = self.group_len.iter().position(|&len| len == 0)?;
+        let tb_size = self.group_idx[groups];
+
+        self.block_size = 1 << bytes[data];
+        self.span = 1 << bytes[data + 1];
+        self.sparse_index_size = tb_size.div_ceil(self.span) as usize;
+        let padding = bytes[data + 2] as usize;
+        self.num_blocks = read_u32_le(bytes, data + 3) as usize;
+        self.block_length_size = self.num_blocks + padding;
+        let max_sym_len = bytes[data + 7] as usize;
+        self.min_sym_len = bytes[data + 8];
+        data += 9;
+
+        if max_sym_len < self.min_sym_len as usize {
+            return None;
+        }
+
+        // Longer symbols have lower values in the canonical code, so base64[i] >= base64[i + 1]
+        self.lowest_sym = data;
+        let lengths = max_sym_len - self.min_sym_len as usize + 1;
+        self.base64 = vec![0; lengths];
+        for i in (0..lengths - 1).rev() {
+            let lowest = read_u16_le(bytes, self.lowest_sym + 2 * i) as u64;
+            let next_lowest = read_u16_le(bytes, self.lowest_sym + 2 * (i + 1)) as u64;
+            self.base64[i] = self.base64[i + 1]
+                .wrapping_add(lowest)
+                .wrapping_sub(next_lowest)
+                / 2;
+        }
+
+        // Right pad to 64 bits, so a symbol of length l lies between base64[l - 1] and base64[l]
+        for (i, base) in self.base64.iter_mut().enumerate() {
+            *base = base
+                .checked_shl((64 - i - self.min_sym_len as usize) as u32)
+                .unwrap_or(0);
+        }
+
+        data += lengths * 2;
+        let sym_count = read_u16_le(bytes, data) as usize;
+        data += 2;
+        self.btree = data;
+
+        if bytes.len() < self.btree + 3 * sym_count {
+            return None;
+        }
+
+        self.sym_len = vec![0; sym_count];
+        let mut visited = vec![false; sym_count];
+        for sym in 0..sym_count {
+            if !visited[sym] {
+                self.sym_len[sym] = self.set_sym_len(bytes, sym, &mut visited);
+            }
+        }
+
+        Some(data + sym_count * 3 + (sym_count & 1))
+    }
+
+    /// Computes the number of values (minus one) a symbol expands to, the tree is acyclic
+    fn set_sym_len(&mut self, bytes: &[u8], sym: usize, visited: &mut [bool]) -> u8 {
+        visited[sym] = true;
+        let (left, right) = btree_pair(bytes, self.btree, sym);
+
+        if right == 0xFFF {
+            return 0;
+        }
+
+        for child in [left, right] {
+            if !visited[child] {
+                self.sym_len[child] = self.set_sym_len(bytes, child, visited);
+            }
+        }
+
+        self.sym_len[left]
+            .wrapping_add(self.sym_len[right])
+            .wrapping_add(1)
+    }
+
+    /// # Decompress Value
+    ///
+    /// Finds the block holding the value at `idx` through the sparse index, then decodes
+    /// Huffman symbols until reaching the one that expands to it.
+    fn decompress(&self, bytes: &[u8], idx: u64) -> i32 {
+        if self.flags & FLAG_SINGLE_VALUE != 0 {
+            return self.min_sym_len as i32;
+        }
+
+        let block_length = |block: usize| read_u16_le(bytes, self.block_length + 2 * block) as i64;
+
+        // Each sparse entry points at the value k * span + span / 2
+        let k = (idx / self.span) as usize;
+        let entry = self.sparse_index + 6 * k;
+        let mut block = read_u32_le(bytes, entry) as usize;
+        let mut offset = read_u16_le(bytes, entry + 4) as i64;
+        offset += (idx % self.span) as i64 - (self.span / 2) as i64;
+
+        while offset < 0 {
+            block -= 1;
+            offset += block_length(block) + 1;
+        }
+
+        while offset > block_length(block) {
+            offset -= block_length(block) + 1;
+            block += 1;
+        }
+
+        let mut ptr = self.data + block * self.block_size;
+        let mut buf = read_u64_be(bytes, ptr);
+        let mut buf_size = 64;
+        ptr += 8;
+
+        let min_sym_len = self.min_sym_len as usize;
+        let mut sym;
+
+        loop {
+            let mut len = 0;
+            while buf < self.base64[len] {
+                len += 1;
+            }
+
+            sym = ((buf - self.base64[len]) >> (64 - len - min_sym_len)) as usize;
+            sym += read_u16_le(bytes, self.lowest_sym + 2 * len) as usize;
+
+            if offset < self.sym_len[sym] as i64 + 1 {
+                break;
+            }
+
+            offset -= self.sym_len[sym] as i64 + 1;
+            len += min_sym_len;
+            buf <<= len;
+            buf_size -= len;
+
+            // Refill the buffer
+            if buf_size <= 32 {
+                buf_size += 32;
+                buf |= (read_u32_be(bytes, ptr) as u64) << (64 - buf_size);
+                ptr += 4;
+            }
+        }
+
+        // Expand the symbol pairs until reaching a leaf
+        while self.sym_len[sym] != 0 {
+            let (left, right) = btree_pair(bytes, self.btree, sym);
+
+            if offset < self.sym_len[left] as i64 + 1 {
+                sym = left;
+            } else {
+                offset -= self.sym_len[left] as i64 + 1;
+                sym = right;
+            }
+        }
+
+        btree_pair(bytes, self.btree, sym).0 as i32
+    }
+}
+
+/******************************************\
+|==========================================|
+|                  Table                   |
+|==========================================|
+\******************************************/
+
+/// A memory mapped WDL or DTZ table.
+#[derive(Debug)]
+pub struct Table {
+    mmap: Mmap,
+    kind: TableKind,
+    /// Sub tables indexed by `side * 4 + file`
+    items: Vec<PairsData>,
+    /// Start of the DTZ value maps
+    map: usize,
+}
+
+/// Piece encoding used by the table files, white pieces are 1 to 6 and black ones 9 to 14
+fn piece_code(piece: Piece) -> u8 {
+    (piece.pt().index() as u8 + 1) | ((piece.colour() == Colour::Black) as u8) << 3
+}
+
+impl Table {
+    /// # Open Table
+    ///
+    /// Maps the table file and reads its headers. Returns `None` if the file can not be
+    /// mapped or does not match the expected table.
+    pub fn open(path: &Path, info: &TableInfo, kind: TableKind) -> Option<Self> {
+        let file = File::open(path).ok()?;
+        // Safety: tablebase files are read only and not expected to change while mapped
+        let mmap = unsafe { Mmap::map(&file) }.ok()?;
+
+        if mmap.len() % 64 != 16 || mmap[..4] != kind.magic() {
+            return None;
+        }
+
+        let (items, map) = Self::parse(&mmap, info, kind)?;
+
+        Some(Self {
+            mmap,
+            kind,
+            items,
+            map,
+        })
+    }
+
+    fn parse(bytes: &[u8], info: &TableInfo, kind: TableKind) -> Option<(Vec<PairsData>, usize)> {
+        let mut data = 4;
+        let flags = *bytes.get(data)?;
+
+        // Split tables store both sides to move, pawn tables are split by leading pawn file
+        if (flags & 2 != 0) != info.has_pawns || (flags & 1 != 0) != !info.symmetric() {
+            return None;
+        }
+        data += 1;
+
+        let sides = if kind == TableKind::Wdl && !info.symmetric() {
+            2
+        } else {
+            1
+        };
+        let files = if info.has_pawns { 4 } else { 1 };
+        let pp = info.has_pawns && info.pawn_count[1] > 0;
+        let mut items = vec![PairsData::default(); 2 * 4];
+
+        for file in 0..files {
+            let first = *bytes.get(data)?;
+            let second = if pp { *bytes.get(data + 1)? } else { 0xFF };
+            let order = [[first & 0xF, second & 0xF], [first >> 4, second >> 4]];
+            data += 1 + pp as usize;
+
+            for k in 0..info.piece_count {
+                let byte = *bytes.get(data)?;
+                for side in 0..sides {
+                    items[side * 4 + file].pieces[k] =
+                        if side == 1 { byte >> 4 } else { byte & 0xF };
+                }
+                data += 1;
+            }
+
+            for side in 0..sides {
+                items[side * 4 + file].set_groups(info, order[side], file);
+            }
+        }
+
+        data += data & 1;
+
+        for file in 0..files {
+            for side in 0..sides {
+                data = items[side * 4 + file].set_sizes(bytes, data)?;
+            }
+        }
+
+        let map = data;
+        if kind == TableKind::Dtz {
+            data = Self::set_dtz_map(bytes, &mut items, data, files)?;
+        }
+
+        for file in 0..files {
+            for side in 0..sides {
+                let d = &mut items[side * 4 + file];
+                d.sparse_index = data;
+                data += d.sparse_index_size * 6;
+            }
+        }
+
+        for file in 0..files {
+            for side in 0..sides {
+                let d = &mut items[side * 4 + file];
+                d.block_length = data;
+                data += d.block_length_size * 2;
+            }
+        }
+
+        for file in 0..files {
+            for side in 0..sides {
+                let d = &mut items[side * 4 + file];
+                data = (data + 0x3F) & !0x3F;
+                d.data = data;
+                data += d.num_blocks * d.block_size;
+            }
+        }
+
+        (data <= bytes.len()).then_some((items, map))
+    }
+
+    /// Reads the offsets of the maps from stored DTZ values to real ones
+    fn set_dtz_map(
+        bytes: &[u8],
+        items: &mut [PairsData],
+        mut data: usize,
+        files: usize,
+    ) -> Option<usize> {
+        let map = data;
+
+        for d in items.iter_mut().take(files) {
+            if d.flags & FLAG_MAPPED == 0 {
+                continue;
+            }
+
+            if d.flags & FLAG_WIDE != 0 {
+                data += data & 1;
+                for i in 0..4 {
+                    d.map_idx[i] = ((data - map) / 2 + 1) as u16;
+                    data += 2 * read_u16_le(bytes, data) as usize + 2;
+                }
+            } else {
+                for i in 0..4 {
+                    d.map_idx[i] = (data - map + 1) as u16;
+                    data += *bytes.get(data)? as usize + 1;
+                }
+            }
+        }
+
+        Some(data + (data & 1))
+    }
+
+    fn get(&self, stm: usize, file: usize) -> &PairsData {
+        &self.items[(stm % self.kind.sides()) * 4 + file]
+    }
+
+    /// # Probe Table
+    ///
+    /// Computes the index of the position and returns the stored value: the WDL score
+    /// (-2 to 2) for WDL tables, or the DTZ in plies for DTZ tables, where `wdl` is the
+    /// result of the position.
+    ///
+    /// Returns `None` if this is a DTZ table storing the other side to move.
+    pub fn probe(
+        &self,
+        info: &TableInfo,
+        board: &Board,
+        material_key: u64,
+        wdl: i32,
+    ) -> Option<i32> {
+        let enc = &*ENCODING;
+
+        // Tables are stored with white as the stronger side, and symmetric tables
+        // only store white to move, otherwise colours and squares are flipped
+        let black_to_move = board.stm() == Colour::Black;
+        let flip = (info.symmetric() && black_to_move) || material_key != info.key;
+        let flip_colour = if flip { 8 } else { 0 };
+        let flip_squares = if flip { 56 } else { 0 };
+        let stm = flip as usize ^ black_to_move as usize;
+
+        let mut squares = [0; TB_PIECES];
+        let mut pieces = [0; TB_PIECES];
+        let mut size = 0;
+        let mut lead_pawns = Bitboard::EMPTY;
+        let mut lead_count = 0;
+        let mut tb_file = 0;
+
+        // Pawn tables are split by the file of the leading pawn, the one with the highest `map_pawns`
+        if info.has_pawns {
+            let lead = self.get(0, 0).pieces[0] ^ flip_colour;
+            let colour = if lead & 8 != 0 {
+                Colour::Black
+            } else {
+                Colour::White
+            };
+
+            lead_pawns = board.piece_bb(colour, PieceType::Pawn);
+            let mut bb = lead_pawns;
+            while let Some(sq) = bb.pop_lsb() {
+                squares[size] = sq.index() ^ flip_squares;
+                size += 1;
+            }
+            lead_count = size;
+
+            let mut best = 0;
+            for i in 1..lead_count {
+                if enc.map_pawns[squares[i]] > enc.map_pawns[squares[best]] {
+                    best = i;
+                }
+            }
+            squares.swap(0, best);
+
+            let file = squares[0] & 7;
+            tb_file = file.min(7 - file);
+        }
+
+        if self.kind == TableKind::Dtz {
+            let flags = self.get(stm, tb_file).flags;
+            if (flags & FLAG_STM) as usize != stm && !(info.symmetric() && !info.has_pawns) {
+                return None;
+            }
+        }
+
+        let mut bb = board.all_occupied_bb() ^ lead_pawns;
+        while let Some(sq) = bb.pop_lsb() {
+            squares[size] = sq.index() ^ flip_squares;
+            // Safety: the square is taken from the occupied bitboard
+            pieces[size] = piece_code(unsafe { board.on_unchecked(sq) }) ^ flip_colour;
+            size += 1;
+        }
+
+        let d = self.get(stm, tb_file);
+
+        // Reorder the pieces to the sequence used by the table
+        for i in lead_count..size - 1 {
+            if let Some(j) = (i + 1..size).find(|&j| d.pieces[i] == pieces[j]) {
+                pieces.swap(i, j);
+                squares.swap(i, j);
+            }
+        }
+
+        // Map the leading piece to the a-d files
+        if squares[0] & 7 > 3 {
+            squares[..size].iter_mut().for_each(|sq| *sq ^= 7);
+        }
+
+        let mut idx = if info.has_pawns {
+            let mut idx = enc.lead_pawn_idx[lead_count][squares[0]];
+
+            squares[1..lead_count].sort_by_key(|&sq| enc.map_pawns[sq]);
+            for (i, &sq) in squares.iter().enumerate().take(lead_count).skip(1) {
+                idx += enc.binomial[i][enc.map_pawns[sq]];
+            }
+
+            idx
+        } else {
+            // Map the leading piece to ranks 1-4
+            if squares[0] >> 3 > 3 {
+                squares[..size].iter_mut().for_each(|sq| *sq ^= 56);
+            }
+
+            // The first piece of the leading group not on the a1-h8 diagonal goes below it
+            for i in 0..d.group_len[0] {
+                let off = off_a1h8(squares[i]);
+                if off == 0 {
+                    continue;
+                }
+                if off > 0 {
+                    squares[i..size]
+                        .iter_mut()
+                        .for_each(|sq| *sq = ((*sq >> 3) | (*sq << 3)) & 63);
+                }
+                break;
+            }
+
+            if info.has_unique_pieces {
+                Self::encode_unique(&squares)
+            } else {
+                enc.map_kk[enc.map_a1d1d4[squares[0]]][squares[1]] as u64
+            }
+        };
+
+        // Encode the remaining groups, squares already used by earlier groups are skipped
+        idx *= d.group_idx[0];
+        let mut start = d.group_len[0];
+        let mut remaining_pawns = info.has_pawns && info.pawn_count[1] > 0;
+        let mut next = 1;
+
+        while d.group_len[next] != 0 {
+            let len = d.group_len[next];
+            squares[start..start + len].sort_unstable();
+
+            let mut n = 0;
+            for i in 0..len {
+                let sq = squares[start + i];
+                let adjust = squares[..start].iter().filter(|&&s| sq > s).count();
+                n += enc.binomial[i + 1][sq - adjust - 8 * remaining_pawns as usize];
+            }
+
+            remaining_pawns = false;
+            idx += n * d.group_idx[next];
+            start += len;
+            next += 1;
+        }
+
+        let value = d.decompress(&self.mmap, idx);
+        Some(self.map_score(tb_file, value, wdl))
+    }
+
+    /// Encodes the first three unique pieces together, with the first one below the a1-h8 diagonal
+    fn encode_unique(squares: &[usize; TB_PIECES]) -> u64 {
+        let enc = &*ENCODING;
+        let [s0, s1, s2] = [squares[0], squares[1], squares[2]];
+        let adjust1 = (s1 > s0) as usize;
+        let adjust2 = (s2 > s0) as usize + (s2 > s1) as usize;
+        let rank = |sq: usize| sq >> 3;
+
+        let idx = if off_a1h8(s0) != 0 {
+            (enc.map_a1d1d4[s0] * 63 + (s1 - adjust1)) * 62 + s2 - adjust2
+        } else if off_a1h8(s1) != 0 {
+            (6 * 63 + rank(s0) * 28 + enc.map_b1h1h7[s1]) * 62 + s2 - adjust2
+        } else if off_a1h8(s2) != 0 {
+            6 * 63 * 62
+                + 4 * 28 * 62
+                + rank(s0) * 7 * 28
+                + (rank(s1) - adjust1) * 28
+                + enc.map_b1h1h7[s2]
+        } else {
+            6 * 63 * 62
+                + 4 * 28 * 62
+                + 4 * 7 * 28
+                + rank(s0) * 7 * 6
+                + (rank(s1) - adjust1) * 6
+                + (rank(s2) - adjust2)
+        };
+
+        idx as u64
+    }
+
+    /// Converts a stored value to a WDL score, or to a DTZ in plies
+    fn map_score(&self, file: usize, value: i32, wdl: i32) -> i32 {
+        if self.kind == TableKind::Wdl {
+            return value - 2;
+        }
+
+        // Map index of loss, blessed loss, draw, cursed win and win
+        const WDL_MAP: [usize; 5] = [1, 3, 0, 2, 0];
+
+        let d = self.get(0, file);
+        let mut value = value;
+
+        if d.flags & FLAG_MAPPED != 0 {
+            let idx = d.map_idx[WDL_MAP[(wdl + 2) as usize]] as usize + value as usize;
+            value = if d.flags & FLAG_WIDE != 0 {
+                read_u16_le(&self.mmap, self.map + 2 * idx) as i32
+            } else {
+                self.mmap[self.map + idx] as i32
+            };
+        }
+
+        // Values are stored in moves unless the plies flags are set
+        if (wdl == 2 && d.flags & FLAG_WIN_PLIES == 0)
+            || (wdl == -2 && d.flags & FLAG_LOSS_PLIES == 0)
+            || wdl == 1
+            || wdl == -1
+        {
+            value *= 2;
+        }
+
+        value + 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encoding_tables() {
+        let enc = &*ENCODING;
+
+        // 462 legal king placements, and 28 squares below the diagonal
+        let max_kk = enc.map_kk.iter().flatten().max().unwrap();
+        assert_eq!(*max_kk, 461);
+        assert_eq!(enc.map_b1h1h7[Square::H7.index()], 27);
+        assert_eq!(enc.map_a1d1d4[Square::D4.index()], 9);
+
+        assert_eq!(enc.binomial[2][5], 10);
+        assert_eq!(enc.binomial[5][63], 7028847);
+
+        // a2 is the first leading pawn square, h7 the last mapped one
+        assert_eq!(enc.map_pawns[Square::A2.index()], 47);
+        assert_eq!(enc.map_pawns[Square::E7.index()], 0);
+        assert_eq!(enc.lead_pawns_size[1], [6, 6, 6, 6]);
+    }
+
+    #[test]
+    fn test_table_info() {
+        let info = TableInfo::parse("KRPvKR").unwrap();
+        assert_eq!(info.piece_count, 5);
+        assert!(info.has_pawns && info.has_unique_pieces);
+        assert_eq!(info.pawn_count, [1, 0]);
+        assert_ne!(info.key, info.key2);
+
+        let info = TableInfo::parse("KPvKPP").unwrap();
+        assert_eq!(info.pawn_count, [1, 2]);
+
+        let info = TableInfo::parse("KNNvK").unwrap();
+        assert!(!info.has_unique_pieces);
+
+        let info = TableInfo::parse("KBvKB").unwrap();
+        assert_eq!(info.key, info.key2);
+
+        assert_eq!(TableInfo::parse("KRvKK"), None);
+        assert_eq!(TableInfo::parse("KXvK"), None);
+        assert_eq!(TableInfo::parse("KQQQQvKQQ"), None);
+    }
+
+    /// Builds the decoding groups of a table from its piece order
+    fn groups(name: &str, pieces: &[Piece], order: [u8; 2]) -> PairsData {
+        let info = TableInfo::parse(name).unwrap();
+        let mut d = PairsData::default();
+        for (k, &piece) in pieces.iter().enumerate() {
+            d.pieces[k] = piece_code(piece);
+        }
+        d.set_groups(&info, order, 0);
+        d
+    }
+
+    #[test]
+    fn test_set_groups() {
+        use Piece::*;
+
+        // The three unique pieces are encoded together with the 31332 KK + piece placements
+        let d = groups("KRvK", &[WhiteKing, WhiteRook, BlackKing], [0, 0xF]);
+        assert_eq!(d.group_len[..2], [3, 0]);
+        assert_eq!(d.group_idx[..2], [1, 31332]);
+
+        // The knight is placed on one of the 61 remaining squares
+        let d = groups(
+            "KRvKN",
+            &[WhiteKing, WhiteRook, BlackKing, BlackKnight],
+            [0, 0xF],
+        );
+        assert_eq!(d.group_len[..3], [3, 1, 0]);
+        assert_eq!(d.group_idx[..3], [1, 31332, 31332 * 61]);
+
+        // Equal pieces outside the leading group share a group
+        let d = groups(
+            "KRvKNN",
+            &[WhiteKing, WhiteRook, BlackKing, BlackKnight, BlackKnight],
+            [0, 0xF],
+        );
+        assert_eq!(d.group_len[..3], [3, 2, 0]);
+        assert_eq!(d.group_idx[2], 31332 * 1830);
+
+        // Without unique pieces, only the two kings lead
+        let d = groups(
+            "KNNvK",
+            &[WhiteKing, BlackKing, WhiteKnight, WhiteKnight],
+            [0, 0xF],
+        );
+        assert_eq!(d.group_len[..3], [2, 2, 0]);
+        assert_eq!(d.group_idx[..3], [1, 462, 462 * 1891]);
+
+        // Pawn tables lead with the pawns, the kings follow one by one
+        let d = groups("KPvK", &[WhitePawn, WhiteKing, BlackKing], [0, 0xF]);
+        assert_eq!(d.group_len[..4], [1, 1, 1, 0]);
+        assert_eq!(d.group_idx[..4], [1, 6, 6 * 63, 6 * 63 * 62]);
+
+        // The other colour's pawns are placed on the 47 remaining pawn squares
+        let d = groups(
+            "KPvKP",
+            &[WhitePawn, BlackPawn, WhiteKing, BlackKing],
+            [0, 1],
+        );
+        assert_eq!(d.group_len[..5], [1, 1, 1, 1, 0]);
+        assert_eq!(
+            d.group_idx[..5],
+            [1, 6, 6 * 47, 6 * 47 * 62, 6 * 47 * 62 * 61]
+        );
+    }
+
+    #[test]
+    fn test_material_keys() {
+        let info = TableInfo::parse("KRvK").unwrap();
+
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+        assert_eq!(board_material_key(&board), info.key);
+
+        let board = Board::from_fen("r3k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(board_material_key(&board), info.key2);
+    }
+}
@@ -1,3 +1,4 @@
 mod thread;
+mod worker;
 
 pub use thread::ThreadPool;
@@ -2,36 +2,117 @@ use std::{
     collections::HashMap,
     iter,
     sync::{
-        Arc,
+        Arc, MutexGuard,
         atomic::{AtomicBool, AtomicU64, Ordering},
     },
 };
 
-use chess::{Move, board::Board};
+use chess::{Castling, Move, board::Board};
 
 use crate::{
+    Depth,
+    constants::SYZYGY_PROBE_LIMIT,
+    eval::Eval,
     search::{SearchWorker, TT},
+    syzygy::Tablebases,
     time::Clock,
     time::TimeControl,
 };
 
+use super::worker::{Task, WorkerThread};
+
+/// Long lived search threads, parked between searches and woken up with a task
 pub struct ThreadPool {
-    pub main_worker: SearchWorker,
-    workers: Vec<SearchWorker>,
+    main_worker: WorkerThread,
+    workers: Vec<WorkerThread>,
     stop: Arc<AtomicBool>,
     nodes: Arc<AtomicU64>,
+    tb: Arc<Tablebases>,
+    tb_hits: Arc<AtomicU64>,
+    tb_probe_limit: usize,
+    // Print the result of every thread after the search
+    debug: bool,
+}
+
+/// The result of a thread's search: its best move, score and completed depth
+type ThreadResult = (Move, Eval, Depth);
+
+/// Returns whether the score is a proven win, either a mate or a tablebase win
+fn is_proven_win(eval: Eval) -> bool {
+    eval >= Eval::TB_WIN_BOUND && eval.is_valid()
+}
+
+/// Returns whether the score is a proven loss, either a mate or a tablebase loss
+fn is_proven_loss(eval: Eval) -> bool {
+    eval <= -Eval::TB_WIN_BOUND && eval.is_valid()
+}
+
+/// # Picks the best move among the results of the threads
+///
+/// - Each thread votes for its best move, weighted by how its score compares to the worst score
+///   and by its completed depth
+/// - Proven wins are preferred over any vote, the shortest one first, and proven losses are
+///   avoided whenever another thread found something better
+/// - The first result, the main thread's, wins ties
+fn select_best_move(results: &[ThreadResult]) -> Move {
+    let Some(min_eval) = results.iter().map(|&(_, eval, _)| eval).min() else {
+        return Move::NULL;
+    };
+
+    let weight =
+        |&(_, eval, depth): &ThreadResult| (eval - min_eval + Eval(14)).0 as i64 * depth as i64;
+
+    let mut votes = HashMap::new();
+    for result in results {
+        *votes.entry(result.0).or_insert(0) += weight(result);
+    }
+
+    let mut best = &results[0];
+    for result in &results[1..] {
+        let (best_eval, eval) = (best.1, result.1);
+
+        let better = if is_proven_win(best_eval) {
+            // Prefer the shortest proven win
+            eval > best_eval
+        } else if is_proven_win(eval) {
+            true
+        } else if is_proven_loss(best_eval) {
+            // Prefer the longest proven loss, or anything better
+            eval > best_eval
+        } else {
+            !is_proven_loss(eval)
+                && (votes[&result.0] > votes[&best.0]
+                    || (votes[&result.0] == votes[&best.0] && weight(result) > weight(best)))
+        };
+
+        if better {
+            best = result;
+        }
+    }
+
+    best.0
 }
 
 impl ThreadPool {
     pub fn new(stop: Arc<AtomicBool>) -> Self {
         let nodes = Arc::new(AtomicU64::new(0));
-        let main_worker = SearchWorker::new(0, stop.clone(), nodes.clone());
+        let tb_hits = Arc::new(AtomicU64::new(0));
+        let main_worker = WorkerThread::new(SearchWorker::new(
+            0,
+            stop.clone(),
+            nodes.clone(),
+            tb_hits.clone(),
+        ));
 
         Self {
             main_worker,
             workers: Vec::new(),
             stop,
             nodes,
+            tb: Arc::default(),
+            tb_hits,
+            tb_probe_limit: SYZYGY_PROBE_LIMIT,
+            debug: false,
         }
     }
 
@@ -45,10 +126,16 @@ impl ThreadPool {
         let current_size = self.workers.len() + 1; // Include the main thread
         if new_size > current_size {
             for i in current_size..new_size {
-                let worker = SearchWorker::new(i, self.stop.clone(), self.nodes.clone());
-                self.workers.push(worker);
+                let worker = SearchWorker::new(
+                    i,
+                    self.stop.clone(),
+                    self.nodes.clone(),
+                    self.tb_hits.clone(),
+                );
+                self.workers.push(WorkerThread::new(worker));
             }
         } else if new_size < current_size {
+            // Dropping a worker thread joins it
             self.workers.truncate(new_size - 1);
         }
 
@@ -56,68 +143,126 @@ impl ThreadPool {
     }
 
     pub fn reset(&mut self) {
-        self.main_worker.reset();
-        for worker in &mut self.workers {
-            worker.reset();
+        self.run(&|worker| worker.reset());
+    }
+
+    /// Gives access to the main thread's search worker while no search is running
+    pub(crate) fn main_worker(&self) -> MutexGuard<'_, SearchWorker> {
+        self.main_worker.lock()
+    }
+
+    /// Runs the task on every thread, returning once all of them are done
+    pub(crate) fn run(&self, task: &Task) {
+        for thread in self.threads() {
+            // SAFETY: the task outlives this call, which waits for every thread below
+            unsafe { thread.start(task) };
+        }
+
+        let panicked = self.threads().filter(|thread| !thread.wait()).count();
+
+        assert_eq!(panicked, 0, "{panicked} search thread(s) panicked");
+    }
+
+    pub fn set_tablebases(&mut self, tb: Tablebases) {
+        self.tb = Arc::new(tb);
+    }
+
+    pub fn set_tb_probe_limit(&mut self, limit: usize) {
+        self.tb_probe_limit = limit;
+    }
+
+    pub fn set_debug(&mut self, debug: bool) {
+        self.debug = debug;
+    }
+
+    /// Ranks the root moves with the tablebases, returning the moves worth searching
+    /// (empty if the root is not in the tablebases) and the probe limit used during search.
+    fn probe_root(&self, board: &Board) -> (Vec<Move>, usize) {
+        let probe_limit = self.tb_probe_limit.min(self.tb.max_pieces());
+        let pieces = board.all_occupied_bb().count_bits() as usize;
+
+        if pieces > probe_limit || board.castling() != Castling::NONE {
+            return (Vec::new(), probe_limit);
+        }
+
+        match self.tb.probe_root(&mut board.clone()) {
+            Some(root) => {
+                self.tb_hits.fetch_add(1, Ordering::Relaxed);
+                // The DTZ ranked moves already keep the win, and there is nothing to gain when
+                // not winning, so only probe during search when converting a win with WDL tables
+                let probe_limit = if root.dtz || root.best_rank() <= 0 {
+                    0
+                } else {
+                    probe_limit
+                };
+
+                (root.best_moves(), probe_limit)
+            }
+            None => (Vec::new(), probe_limit),
         }
     }
 
     pub fn start_search(&mut self, time_control: TimeControl, tt: &TT, board: &Board) {
         self.stop.store(false, Ordering::Relaxed);
         self.nodes.store(0, Ordering::Relaxed);
+        self.tb_hits.store(0, Ordering::Relaxed);
+
+        let (root_moves, probe_limit) = self.probe_root(board);
 
-        self.main_worker.clock = Clock::new(
+        self.main_worker().clock = Clock::new(
             self.stop.clone(),
             self.nodes.clone(),
             time_control,
             board.stm(),
         );
-        std::thread::scope(|s| {
-            let board_clone = board.clone();
-            let main_worker = &mut self.main_worker;
-            let workers = &mut self.workers;
-
-            main_worker.prepare_search();
-            s.spawn(move || {
-                main_worker.setup(board_clone);
-                main_worker.iterative_deepening(tt);
-            });
 
-            for worker in workers {
-                worker.prepare_search();
-                let board_clone = board.clone();
-                s.spawn(move || {
-                    worker.setup(board_clone);
-                    worker.iterative_deepening(tt);
-                });
-            }
+        self.run(&|worker| {
+            worker.prepare_search();
+            worker.setup_tablebases(self.tb.clone(), root_moves.clone(), probe_limit);
+            worker.setup(board.clone());
+            worker.iterative_deepening(tt);
         });
 
         self.stop.store(true, Ordering::Relaxed);
 
+        if self.debug {
+            self.print_thread_info(board);
+        }
+
         println!("bestmove {}", self.find_best_move().to_str(board));
     }
 
+    fn threads(&self) -> impl Iterator<Item = &WorkerThread> {
+        iter::once(&self.main_worker).chain(self.workers.iter())
+    }
+
     fn find_best_move(&self) -> Move {
-        let threads = iter::once(&self.main_worker).chain(self.workers.iter());
-        // Safe unwrap as main worker always exists
-        let highest_depth = threads
-            .clone()
-            .max_by(|a, b| a.depth.cmp(&b.depth))
-            .unwrap()
-            .depth;
-
-        threads
-            .filter(|w| w.depth == highest_depth)
-            .map(|w| w.best_move())
-            .fold(HashMap::new(), |mut acc, move_| {
-                *acc.entry(move_).or_insert(0) += 1;
-                acc
-            })
-            .into_iter()
-            .max_by_key(|(_, count)| *count)
-            .map(|(move_, _)| move_)
-            .unwrap_or(Move::NULL)
+        let results = self
+            .threads()
+            .map(|thread| thread.lock())
+            .filter(|w| w.completed_depth() > 0 && w.best_move().is_valid())
+            .map(|w| (w.best_move(), w.eval(), w.completed_depth()))
+            .collect::<Vec<_>>();
+
+        if results.is_empty() {
+            return self.main_worker().best_move();
+        }
+
+        select_best_move(&results)
+    }
+
+    fn print_thread_info(&self, board: &Board) {
+        for thread in self.threads() {
+            let w = thread.lock();
+            println!(
+                "info string thread {} depth {} nodes {} score {} bestmove {}",
+                thread.thread_id(),
+                w.completed_depth(),
+                w.nodes(),
+                w.eval(),
+                w.best_move().to_str(board)
+            );
+        }
     }
 
     /// Get the total nodes searched
@@ -241,6 +386,80 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_threads_persist_between_tasks() {
+        use std::{sync::Mutex, thread::ThreadId};
+
+        let stop = create_test_stop();
+        let mut pool = ThreadPool::new(Arc::clone(&stop));
+        pool.resize(3);
+
+        let run_ids = |pool: &ThreadPool| {
+            let ids: Mutex<Vec<(usize, ThreadId)>> = Mutex::default();
+            pool.run(&|worker| {
+                ids.lock()
+                    .unwrap()
+                    .push((worker.thread_id(), std::thread::current().id()))
+            });
+
+            let mut ids = ids.into_inner().unwrap();
+            ids.sort_by_key(|&(i, _)| i);
+            ids
+        };
+
+        let first = run_ids(&pool);
+        assert_eq!(first.iter().map(|&(i, _)| i).collect::<Vec<_>>(), [0, 1, 2]);
+        assert!(
+            first
+                .iter()
+                .all(|&(_, id)| id != std::thread::current().id())
+        );
+
+        // The same threads run the next task, even after a search
+        pool.start_search(
+            TimeControl::FixedDepth(2),
+            &create_test_tt(),
+            &Board::default(),
+        );
+        assert_eq!(run_ids(&pool), first);
+
+        // Growing the pool keeps the existing threads
+        pool.resize(4);
+        assert_eq!(run_ids(&pool)[..3], first);
+    }
+
+    #[test]
+    fn test_select_best_move() {
+        use chess::{MoveFlag, Square};
+
+        let e4 = Move::new(Square::E2, Square::E4, MoveFlag::DoublePawnPush);
+        let d4 = Move::new(Square::D2, Square::D4, MoveFlag::DoublePawnPush);
+        let nf3 = Move::new(Square::G1, Square::F3, MoveFlag::QuietMove);
+
+        assert_eq!(select_best_move(&[]), Move::NULL);
+        assert_eq!(select_best_move(&[(e4, Eval(20), 10)]), e4);
+
+        // Votes are weighted by score and depth
+        let results = [(e4, Eval(20), 10), (d4, Eval(30), 10), (d4, Eval(25), 10)];
+        assert_eq!(select_best_move(&results), d4);
+
+        let results = [(e4, Eval(60), 12), (d4, Eval(10), 10), (d4, Eval(10), 10)];
+        assert_eq!(select_best_move(&results), e4);
+
+        // Proven wins beat any vote, the shortest one first
+        let results = [
+            (e4, Eval(50), 14),
+            (d4, Eval::mate_in(9), 8),
+            (nf3, Eval::mate_in(5), 6),
+            (e4, Eval(50), 14),
+        ];
+        assert_eq!(select_best_move(&results), nf3);
+
+        // Proven losses are avoided
+        let results = [(e4, Eval::mated_in(4), 14), (d4, Eval(-300), 8)];
+        assert_eq!(select_best_move(&results), d4);
+    }
+
     #[test]
     fn test_start_search_runs_and_stops() {
         let stop = create_test_stop();
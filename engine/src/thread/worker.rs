@@ -0,0 +1,154 @@
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError},
+    thread::{self, JoinHandle},
+};
+
+use crate::search::SearchWorker;
+
+/// A task run by every worker thread on its own search worker
+pub(crate) type Task<'a> = dyn Fn(&mut SearchWorker) + Sync + 'a;
+
+/// Pointer to a borrowed task, which the pool keeps alive until every thread is done with it
+#[derive(Clone, Copy)]
+struct TaskPtr(*const Task<'static>);
+
+// SAFETY: the task is `Sync`, and it outlives its use as the pool waits for it to finish
+unsafe impl Send for TaskPtr {}
+
+#[derive(Default)]
+struct State {
+    task: Option<TaskPtr>,
+    // Whether a task was started and has not finished yet
+    busy: bool,
+    // Whether the last task panicked
+    panicked: bool,
+    quit: bool,
+}
+
+#[derive(Default)]
+struct Shared {
+    state: Mutex<State>,
+    cv: Condvar,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// A long lived search thread, parked on a condition variable until it is given a task
+pub(crate) struct WorkerThread {
+    thread_id: usize,
+    worker: Arc<Mutex<SearchWorker>>,
+    shared: Arc<Shared>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl WorkerThread {
+    pub fn new(worker: SearchWorker) -> Self {
+        let thread_id = worker.thread_id();
+        let worker = Arc::new(Mutex::new(worker));
+        let shared = Arc::new(Shared::default());
+
+        let handle = {
+            let worker = worker.clone();
+            let shared = shared.clone();
+            thread::spawn(move || idle_loop(&worker, &shared))
+        };
+
+        Self {
+            thread_id,
+            worker,
+            shared,
+            handle: Some(handle),
+        }
+    }
+
+    pub fn thread_id(&self) -> usize {
+        self.thread_id
+    }
+
+    /// Gives access to the search worker, which must not be held while the thread runs a task
+    pub fn lock(&self) -> MutexGuard<'_, SearchWorker> {
+        self.worker.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Wakes the thread up to run the task
+    ///
+    /// # Safety
+    ///
+    /// The task must stay alive until `wait` returns
+    pub unsafe fn start(&self, task: &Task) {
+        // SAFETY: only the lifetime is erased, the caller keeps the task alive
+        let task = unsafe { std::mem::transmute::<*const Task, *const Task<'static>>(task) };
+
+        let mut state = self.shared.lock();
+        state.task = Some(TaskPtr(task));
+        state.busy = true;
+        state.panicked = false;
+        self.shared.cv.notify_all();
+    }
+
+    /// Blocks until the current task is done, returning false if it panicked
+    pub fn wait(&self) -> bool {
+        let mut state = self.shared.lock();
+        while state.busy {
+            state = self
+                .shared
+                .cv
+                .wait(state)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+
+        !state.panicked
+    }
+}
+
+impl Drop for WorkerThread {
+    fn drop(&mut self) {
+        self.wait();
+
+        self.shared.lock().quit = true;
+        self.shared.cv.notify_all();
+
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Waits for tasks and runs them until the thread is told to quit
+fn idle_loop(worker: &Mutex<SearchWorker>, shared: &Shared) {
+    loop {
+        let task = {
+            let mut state = shared.lock();
+            loop {
+                if state.quit {
+                    return;
+                }
+                if let Some(task) = state.task.take() {
+                    break task;
+                }
+                state = shared
+                    .cv
+                    .wait(state)
+                    .unwrap_or_else(PoisonError::into_inner);
+            }
+        };
+
+        // A panicking task is reported to the pool instead of killing the thread,
+        // so that waiting for it never blocks forever
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let mut worker = worker.lock().unwrap_or_else(PoisonError::into_inner);
+            // SAFETY: the pool keeps the task alive until `busy` is cleared below
+            unsafe { (*task.0)(&mut worker) };
+        }));
+
+        let mut state = shared.lock();
+        state.busy = false;
+        state.panicked = result.is_err();
+        shared.cv.notify_all();
+    }
+}
@@ -77,6 +77,16 @@ impl Clock {
         self.node_count[m.from().index()][m.to().index()] += delta;
     }
 
+    /// Clears the root node counts, which only hold for one search from one root
+    pub fn reset_node_counts(&mut self) {
+        self.node_count = [[0; Square::NUM]; Square::NUM];
+    }
+
+    /// Returns the number of nodes spent searching a root move
+    pub fn node_count(&self, m: Move) -> u64 {
+        self.node_count[m.from().index()][m.to().index()]
+    }
+
     fn get_time_and_increment(
         stm: Colour,
         wtime: u64,
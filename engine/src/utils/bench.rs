@@ -118,3 +118,51 @@ pub fn run_bench(depth: Depth) {
         avg_branch_factor
     );
 }
+
+/// Thread counts compared by the SMP scaling benchmark
+const SMP_BENCH_THREADS: [usize; 4] = [1, 2, 4, 8];
+
+/// Runs the bench positions to a fixed depth with 1, 2, 4 and 8 threads,
+/// reporting the time to depth of each thread count and its speedup over a single thread.
+pub fn run_smp_bench(depth: Depth) {
+    let stop = Arc::new(AtomicBool::new(false));
+    let mut results = Vec::new();
+
+    for threads in SMP_BENCH_THREADS {
+        let mut tt = TT::default();
+        let mut thread = ThreadPool::new(Arc::clone(&stop));
+        thread.resize(threads);
+
+        let mut total_nodes = 0;
+        let mut total_time = 0;
+
+        tt.reset_age();
+
+        for fen in FENS.iter() {
+            let board = Board::from_fen(fen).unwrap();
+
+            thread.clear_hash_table(&tt);
+
+            let start = Instant::now();
+
+            thread.start_search(TimeControl::FixedDepth(depth), &tt, &board);
+
+            total_time += start.elapsed().as_micros();
+            total_nodes += thread.nodes();
+        }
+
+        results.push((threads, total_time, total_nodes));
+    }
+
+    let base_time = results[0].1.max(1);
+
+    println!("Time to depth {depth}:");
+    for (threads, time, nodes) in results {
+        println!(
+            "threads {threads:2} time {:8} ms speedup {:5.2} nodes {nodes:10} nps {}",
+            time / 1000,
+            base_time as f64 / time.max(1) as f64,
+            nodes * 1_000_000 / (time as u64).max(1)
+        );
+    }
+}
@@ -0,0 +1,64 @@
+use chess::{
+    Colour,
+    book::{BookBuilder, BookBuilderConfig},
+};
+
+/// Builds a Polyglot book from a PGN file.
+///
+/// Usage: `makebook <pgn> <book.bin> [winscore N] [drawscore N] [lossscore N] [maxply N] [mingames N] [colour white|black|both]`
+pub fn run_make_book(mut args: impl Iterator<Item = String>) {
+    let (Some(pgn_path), Some(book_path)) = (args.next(), args.next()) else {
+        println!("info string usage: makebook <pgn> <book.bin> [option value]...");
+        return;
+    };
+
+    let mut config = BookBuilderConfig::default();
+
+    while let (Some(name), Some(value)) = (args.next(), args.next()) {
+        let number = value.parse::<u32>();
+
+        match (name.to_ascii_lowercase().as_str(), number) {
+            ("winscore", Ok(n)) => config.win_score = n,
+            ("drawscore", Ok(n)) => config.draw_score = n,
+            ("lossscore", Ok(n)) => config.loss_score = n,
+            ("maxply", Ok(n)) => config.max_ply = n as usize,
+            ("mingames", Ok(n)) => config.min_games = n,
+            ("colour" | "color", _) => match value.to_ascii_lowercase().as_str() {
+                "white" => config.colour = Some(Colour::White),
+                "black" => config.colour = Some(Colour::Black),
+                "both" => config.colour = None,
+                _ => {
+                    println!(
+                        "info string invalid makebook colour '{value}', expected white, black or both."
+                    );
+                    return;
+                }
+            },
+            _ => {
+                println!("info string invalid makebook option '{name} {value}'.");
+                return;
+            }
+        }
+    }
+
+    let pgn = match std::fs::read_to_string(&pgn_path) {
+        Ok(pgn) => pgn,
+        Err(e) => {
+            println!("info string failed to read {pgn_path}: {e}");
+            return;
+        }
+    };
+
+    let mut builder = BookBuilder::new(config);
+    let summary = builder.add_pgn(&pgn);
+
+    match builder.write(&book_path) {
+        Ok(()) => println!(
+            "info string wrote {book_path}: {} entries from {} games ({} skipped).",
+            builder.entries().len(),
+            summary.games,
+            summary.skipped
+        ),
+        Err(e) => println!("info string failed to write {book_path}: {e}"),
+    }
+}
@@ -1,6 +1,8 @@
 mod bench;
+mod make_book;
 mod move_buffer;
 mod tune;
 
-pub use bench::run_bench;
+pub use bench::{run_bench, run_smp_bench};
+pub use make_book::run_make_book;
 pub(crate) use move_buffer::MoveBuffer;